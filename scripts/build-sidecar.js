@@ -0,0 +1,39 @@
+// Build the orion-mcp sidecar where Tauri's externalBin expects it
+//
+// Tauri requires binaries/orion-mcp-<target triple> to exist before it compiles
+// the app, while the sidecar is a binary of the same crate. An empty placeholder
+// satisfies that check until cargo has built the real one over it.
+import { execFileSync } from 'node:child_process';
+import { copyFileSync, existsSync, mkdirSync, writeFileSync } from 'node:fs';
+import { dirname, join } from 'node:path';
+import { fileURLToPath } from 'node:url';
+
+const release = process.argv.includes('--release');
+const tauriDir = join(dirname(fileURLToPath(import.meta.url)), '..', 'src-tauri');
+
+const hostTriple = () =>
+  execFileSync('rustc', ['-vV'], { encoding: 'utf8' })
+    .split('\n')
+    .find((line) => line.startsWith('host:'))
+    .slice('host:'.length)
+    .trim();
+const triple = process.env.TAURI_ENV_TARGET_TRIPLE || hostTriple();
+const extension = triple.includes('windows') ? '.exe' : '';
+
+const binariesDir = join(tauriDir, 'binaries');
+const sidecarPath = join(binariesDir, `orion-mcp-${triple}${extension}`);
+mkdirSync(binariesDir, { recursive: true });
+if (!existsSync(sidecarPath)) {
+  writeFileSync(sidecarPath, '');
+}
+
+const args = ['build', '--manifest-path', join(tauriDir, 'Cargo.toml')];
+args.push('--bin', 'orion-mcp', '--features', 'sidecar', '--target', triple);
+if (release) {
+  args.push('--release');
+}
+execFileSync('cargo', args, { stdio: 'inherit' });
+
+const profile = release ? 'release' : 'debug';
+const built = join(tauriDir, 'target', triple, profile, `orion-mcp${extension}`);
+copyFileSync(built, sidecarPath);
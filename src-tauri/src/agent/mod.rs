@@ -0,0 +1,3 @@
+pub mod runtime;
+
+pub use runtime::{AgentEvent, AgentLimits, AgentRequest, AgentRun, AgentRuntime, StopReason};
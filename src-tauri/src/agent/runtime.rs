@@ -0,0 +1,634 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+use crate::llm::runs::{stream_cancellable, STOPPED_FINISH_REASON};
+use crate::llm::{ChatChunk, ChatMessage, ChatProvider, ChatRequest, ModelChoice, ToolCall, Usage};
+use crate::mcp::{
+    ApprovalDecision, CallOptions, CancelToken, McpServerManager, OpenAITool, TimeoutError,
+};
+
+/// Limits that end a run early; whatever the run produced so far is kept
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentLimits {
+    /// Model calls per run
+    #[serde(default = "default_max_steps")]
+    pub max_steps: usize,
+    /// Total cost in USD, as reported by the provider; no limit if unset
+    #[serde(default)]
+    pub max_cost: Option<f64>,
+    /// Wall-clock time for the whole run, including tool calls
+    #[serde(default = "default_max_duration_ms")]
+    pub max_duration_ms: u64,
+}
+
+fn default_max_steps() -> usize {
+    10
+}
+
+fn default_max_duration_ms() -> u64 {
+    300_000
+}
+
+impl Default for AgentLimits {
+    fn default() -> Self {
+        Self {
+            max_steps: default_max_steps(),
+            max_cost: None,
+            max_duration_ms: default_max_duration_ms(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentRequest {
+    pub model: String,
+    /// Models to ask in order when `model` fails, e.g. from another provider
+    #[serde(default)]
+    pub fallbacks: Vec<ModelChoice>,
+    /// Conversation so far, ending with the message to answer
+    pub messages: Vec<ChatMessage>,
+    /// Conversation the run belongs to, for the audit log
+    #[serde(default)]
+    pub conversation_id: Option<String>,
+    #[serde(default)]
+    pub limits: AgentLimits,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StopReason {
+    /// The model answered without calling any more tools
+    Completed,
+    MaxSteps,
+    MaxCost,
+    Timeout,
+    /// Cancelled through the run's cancel token
+    Stopped,
+}
+
+/// Progress of a run, sent as it happens
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AgentEvent {
+    StepStarted {
+        step: usize,
+    },
+    /// Piece of the model's answer as it streams in
+    Chunk {
+        step: usize,
+        chunk: ChatChunk,
+    },
+    /// Complete model turn, possibly with tool calls to run next
+    AssistantMessage {
+        step: usize,
+        message: ChatMessage,
+        usage: Option<Usage>,
+        /// Model that wrote the message, which may be a fallback
+        model: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    ToolCallStarted {
+        step: usize,
+        tool_call_id: String,
+        name: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    ToolResult {
+        step: usize,
+        tool_call_id: String,
+        name: String,
+        content: String,
+        is_error: bool,
+    },
+    Finished {
+        reason: StopReason,
+        steps: usize,
+        cost: f64,
+    },
+}
+
+/// Outcome of a run
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentRun {
+    /// Messages the run added to the conversation, in order
+    pub messages: Vec<ChatMessage>,
+    pub reason: StopReason,
+    pub steps: usize,
+    /// Total cost in USD of the completions that reported one
+    pub cost: f64,
+    /// Model that wrote the last message, unless no model answered
+    pub model: Option<String>,
+}
+
+/// Runs the tool-calling loop: ask the model, run the tools it calls through the
+/// MCP servers, send back the results and repeat until it answers
+pub struct AgentRuntime<'a> {
+    provider: &'a dyn ChatProvider,
+    manager: &'a McpServerManager,
+    cancel: CancelToken,
+}
+
+impl<'a> AgentRuntime<'a> {
+    pub fn new(provider: &'a dyn ChatProvider, manager: &'a McpServerManager) -> Self {
+        Self {
+            provider,
+            manager,
+            cancel: CancelToken::new(),
+        }
+    }
+
+    /// Stop the run when `cancel` fires, aborting the completion or the tool calls
+    /// in flight
+    pub fn with_cancel(mut self, cancel: CancelToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Run until the model stops calling tools or a limit is hit
+    ///
+    /// Limits are checked before each model call, so the tool calls of the last
+    /// step always get their results and the conversation can be continued. Calls
+    /// still running when the time limit passes are cancelled and get error
+    /// results; those that finished keep theirs. A completion cut off by the time
+    /// limit is kept with the text streamed so far, like a cancelled one.
+    /// Tool failures are sent to the model as error results; model failures end
+    /// the run with an error. A cancelled completion is kept as an assistant
+    /// message with the text streamed so far.
+    pub async fn run(
+        &self,
+        request: AgentRequest,
+        on_event: impl Fn(AgentEvent) + Send + Sync,
+    ) -> Result<AgentRun> {
+        let limits = request.limits;
+        let deadline = Instant::now() + Duration::from_millis(limits.max_duration_ms);
+        let tools = self.available_tools().await;
+
+        let mut messages = request.messages;
+        let first_new = messages.len();
+        let mut steps = 0;
+        let mut cost = 0.0;
+        let mut answered_by = None;
+
+        let reason = loop {
+            if self.cancel.is_cancelled() {
+                break StopReason::Stopped;
+            }
+            if steps >= limits.max_steps {
+                break StopReason::MaxSteps;
+            }
+            if limits.max_cost.is_some_and(|max_cost| cost >= max_cost) {
+                break StopReason::MaxCost;
+            }
+            steps += 1;
+            on_event(AgentEvent::StepStarted { step: steps });
+
+            let chat = ChatRequest {
+                model: request.model.clone(),
+                messages: messages.clone(),
+                tools: tools.clone(),
+            };
+            let step = steps;
+            let partial = Mutex::new(String::new());
+            let streaming_model = Mutex::new(request.model.clone());
+            let on_chunk = |chunk: ChatChunk| {
+                match &chunk {
+                    ChatChunk::Content { text } => {
+                        if let Ok(mut partial) = partial.lock() {
+                            partial.push_str(text);
+                        }
+                    }
+                    ChatChunk::Model { model } => {
+                        if let Ok(mut streaming_model) = streaming_model.lock() {
+                            streaming_model.clone_from(model);
+                        }
+                    }
+                    _ => {}
+                }
+                on_event(AgentEvent::Chunk { step, chunk });
+            };
+            let stream = stream_cancellable(self.provider, &chat, &on_chunk, &self.cancel);
+            let Ok(response) = tokio::time::timeout_at(deadline, stream).await else {
+                // Keep the text streamed before the time limit, as for a cancellation
+                let text = partial.lock().map(|text| text.clone()).unwrap_or_default();
+                if !text.is_empty() {
+                    let model = streaming_model
+                        .lock()
+                        .map(|model| model.clone())
+                        .unwrap_or_else(|_| request.model.clone());
+                    let message = ChatMessage::assistant(text);
+                    on_event(AgentEvent::AssistantMessage {
+                        step: steps,
+                        message: message.clone(),
+                        usage: None,
+                        model: model.clone(),
+                    });
+                    answered_by = Some(model);
+                    messages.push(message);
+                }
+                break StopReason::Timeout;
+            };
+            let response = response?;
+            let stopped = response.finish_reason.as_deref() == Some(STOPPED_FINISH_REASON);
+            let text = response.message.content.as_deref().unwrap_or_default();
+            if stopped && text.is_empty() {
+                break StopReason::Stopped;
+            }
+
+            cost += response
+                .usage
+                .as_ref()
+                .and_then(|usage| usage.cost)
+                .unwrap_or(0.0);
+            let model = response.model.unwrap_or_else(|| request.model.clone());
+            on_event(AgentEvent::AssistantMessage {
+                step: steps,
+                message: response.message.clone(),
+                usage: response.usage,
+                model: model.clone(),
+            });
+            answered_by = Some(model);
+            let tool_calls = response.message.tool_calls.clone();
+            messages.push(response.message);
+            if stopped {
+                break StopReason::Stopped;
+            }
+            if tool_calls.is_empty() {
+                break StopReason::Completed;
+            }
+
+            for call in &tool_calls {
+                on_event(AgentEvent::ToolCallStarted {
+                    step: steps,
+                    tool_call_id: call.id.clone(),
+                    name: call.function.name.clone(),
+                });
+            }
+            let results = futures::future::join_all(
+                tool_calls
+                    .iter()
+                    .map(|call| self.call_tool(call, request.conversation_id.clone(), deadline)),
+            )
+            .await;
+
+            for (call, (content, is_error)) in tool_calls.into_iter().zip(results) {
+                on_event(AgentEvent::ToolResult {
+                    step: steps,
+                    tool_call_id: call.id.clone(),
+                    name: call.function.name,
+                    content: content.clone(),
+                    is_error,
+                });
+                messages.push(ChatMessage::tool(call.id, content));
+            }
+            if Instant::now() >= deadline {
+                break StopReason::Timeout;
+            }
+            // Cancelled calls end with an error result, checked at the top of the loop
+        };
+
+        on_event(AgentEvent::Finished {
+            reason,
+            steps,
+            cost,
+        });
+        Ok(AgentRun {
+            messages: messages.split_off(first_new),
+            reason,
+            steps,
+            cost,
+            model: answered_by,
+        })
+    }
+
+    /// Tools of every running server plus the built-in ones, with provider-safe names
+    ///
+    /// Servers that fail to list their tools are left out of the run.
+    async fn available_tools(&self) -> Vec<OpenAITool> {
+        let mut tools = Vec::new();
+        for server_id in self.manager.list_servers().await {
+            match self.manager.list_provider_tools(&server_id).await {
+                Ok(server_tools) => tools.extend(server_tools),
+                Err(e) => eprintln!("Agent skipped MCP server '{}': {}", server_id, e),
+            }
+        }
+        tools.extend(
+            self.manager
+                .list_builtin_tools()
+                .await
+                .into_iter()
+                .map(OpenAITool::from),
+        );
+        tools
+    }
+
+    /// Run one tool call, returning the text for the model and whether it failed
+    ///
+    /// Calls still running at the run's deadline are cancelled and answered with an
+    /// error, so every call has a result and the conversation stays valid.
+    async fn call_tool(
+        &self,
+        call: &ToolCall,
+        conversation_id: Option<String>,
+        deadline: Instant,
+    ) -> (String, bool) {
+        let outcome = async {
+            let tool = self.manager.resolve_tool_name(&call.function.name).await?;
+            let arguments = parse_arguments(&call.function.arguments)?;
+            let options = CallOptions {
+                timeout_ms: None,
+                conversation_id,
+                approval: ApprovalDecision::Auto,
+                cancel: Some(self.cancel.clone()),
+                deadline: Some(deadline),
+            };
+            self.manager
+                .call_tool(&tool.server_id, &tool.tool_name, arguments, options)
+                .await
+        }
+        .await;
+
+        match outcome {
+            Ok(result) => {
+                let text = result
+                    .content
+                    .iter()
+                    .filter_map(|content| content.text.as_deref())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                (text, result.is_error == Some(true))
+            }
+            Err(e) if e.is::<TimeoutError>() && Instant::now() >= deadline => {
+                ("Error: the run's time limit was reached".to_string(), true)
+            }
+            Err(e) => (format!("Error: {}", e), true),
+        }
+    }
+}
+
+/// Tool arguments from the model's JSON string; an empty string means none
+fn parse_arguments(arguments: &str) -> Result<Option<Value>> {
+    if arguments.trim().is_empty() {
+        return Ok(None);
+    }
+    serde_json::from_str(arguments)
+        .map(Some)
+        .map_err(|e| anyhow!("Invalid tool arguments: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{ChatResponse, ChunkSink, FallbackChain, FunctionCall, ModelInfo};
+    use crate::mcp::SecretVault;
+    use futures::future::BoxFuture;
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+
+    /// Answers with canned responses in order, remembering what it was sent
+    struct ScriptedProvider {
+        responses: Mutex<VecDeque<ChatResponse>>,
+        requests: Mutex<Vec<ChatRequest>>,
+    }
+
+    impl ScriptedProvider {
+        fn new(responses: Vec<ChatResponse>) -> Self {
+            Self {
+                responses: Mutex::new(responses.into()),
+                requests: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl ChatProvider for ScriptedProvider {
+        fn stream<'a>(
+            &'a self,
+            request: &'a ChatRequest,
+            _: ChunkSink<'a>,
+        ) -> BoxFuture<'a, Result<ChatResponse>> {
+            self.requests.lock().unwrap().push(request.clone());
+            let response = self.responses.lock().unwrap().pop_front();
+            Box::pin(async move { response.ok_or_else(|| anyhow!("No response left")) })
+        }
+
+        fn list_models(&self) -> BoxFuture<'_, Result<Vec<ModelInfo>>> {
+            Box::pin(async { Ok(Vec::new()) })
+        }
+    }
+
+    fn answer(message: ChatMessage, cost: f64) -> ChatResponse {
+        ChatResponse {
+            message,
+            usage: Some(Usage {
+                cost: Some(cost),
+                ..Usage::default()
+            }),
+            finish_reason: None,
+            model: None,
+        }
+    }
+
+    fn calling(name: &str, arguments: &str) -> ChatMessage {
+        ChatMessage {
+            content: None,
+            tool_calls: vec![ToolCall {
+                id: "call-1".to_string(),
+                call_type: "function".to_string(),
+                function: FunctionCall {
+                    name: name.to_string(),
+                    arguments: arguments.to_string(),
+                },
+            }],
+            ..ChatMessage::assistant("")
+        }
+    }
+
+    fn manager(name: &str) -> McpServerManager {
+        let dir =
+            std::env::temp_dir().join(format!("orion-agent-test-{}-{}", std::process::id(), name));
+        let secrets = SecretVault::open_with_key_file(&dir).unwrap();
+        McpServerManager::new(Arc::new(secrets), &dir).unwrap()
+    }
+
+    fn request(limits: AgentLimits) -> AgentRequest {
+        AgentRequest {
+            model: "test/model".to_string(),
+            fallbacks: Vec::new(),
+            messages: vec![ChatMessage::user("hi")],
+            conversation_id: Some("c1".to_string()),
+            limits,
+        }
+    }
+
+    #[tokio::test]
+    async fn feeds_tool_results_back_until_the_model_answers() {
+        let manager = manager("loop");
+        let provider = ScriptedProvider::new(vec![
+            answer(
+                calling("Orion__read_tool_result", r#"{"handle":"abc"}"#),
+                0.01,
+            ),
+            answer(calling("missing__tool", "{}"), 0.01),
+            answer(ChatMessage::assistant("done"), 0.01),
+        ]);
+        let events = Mutex::new(Vec::new());
+
+        let run = AgentRuntime::new(&provider, &manager)
+            .run(request(AgentLimits::default()), |event| {
+                events.lock().unwrap().push(event)
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(run.reason, StopReason::Completed);
+        assert_eq!(run.steps, 3);
+        assert!((run.cost - 0.03).abs() < 1e-9);
+        assert_eq!(run.messages.len(), 5);
+        assert_eq!(run.messages[4].content.as_deref(), Some("done"));
+        assert_eq!(run.model.as_deref(), Some("test/model"));
+
+        // Both tool failures went back to the model instead of ending the run
+        let second = &provider.requests.lock().unwrap()[1];
+        let result = second.messages.last().unwrap();
+        assert_eq!(result.tool_call_id.as_deref(), Some("call-1"));
+        assert!(result
+            .content
+            .as_ref()
+            .unwrap()
+            .contains("No stored result"));
+        assert!(second
+            .tools
+            .iter()
+            .any(|tool| tool.function.name == "Orion__read_tool_result"));
+
+        let events = events.lock().unwrap();
+        assert!(matches!(
+            events.last(),
+            Some(AgentEvent::Finished {
+                reason: StopReason::Completed,
+                steps: 3,
+                ..
+            })
+        ));
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, AgentEvent::ToolResult { is_error: true, .. })));
+    }
+
+    #[tokio::test]
+    async fn stops_at_the_step_and_cost_limits() {
+        let manager = manager("limits");
+        let looping = || {
+            (0..5)
+                .map(|_| answer(calling("missing__tool", ""), 0.5))
+                .collect()
+        };
+
+        let provider = ScriptedProvider::new(looping());
+        let limits = AgentLimits {
+            max_steps: 2,
+            ..AgentLimits::default()
+        };
+        let run = AgentRuntime::new(&provider, &manager)
+            .run(request(limits), |_| {})
+            .await
+            .unwrap();
+        assert_eq!((run.reason, run.steps), (StopReason::MaxSteps, 2));
+        // The last step's tool call still got its result
+        assert_eq!(run.messages.len(), 4);
+
+        let provider = ScriptedProvider::new(looping());
+        let limits = AgentLimits {
+            max_cost: Some(1.0),
+            ..AgentLimits::default()
+        };
+        let run = AgentRuntime::new(&provider, &manager)
+            .run(request(limits), |_| {})
+            .await
+            .unwrap();
+        assert_eq!((run.reason, run.steps), (StopReason::MaxCost, 2));
+    }
+
+    struct SlowProvider;
+
+    impl ChatProvider for SlowProvider {
+        fn stream<'a>(
+            &'a self,
+            _: &'a ChatRequest,
+            on_chunk: ChunkSink<'a>,
+        ) -> BoxFuture<'a, Result<ChatResponse>> {
+            Box::pin(async move {
+                on_chunk(ChatChunk::Content {
+                    text: "Let me".to_string(),
+                });
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(answer(ChatMessage::assistant("late"), 0.0))
+            })
+        }
+
+        fn list_models(&self) -> BoxFuture<'_, Result<Vec<ModelInfo>>> {
+            Box::pin(async { Ok(Vec::new()) })
+        }
+    }
+
+    struct FailingProvider;
+
+    impl ChatProvider for FailingProvider {
+        fn stream<'a>(
+            &'a self,
+            _: &'a ChatRequest,
+            _: ChunkSink<'a>,
+        ) -> BoxFuture<'a, Result<ChatResponse>> {
+            Box::pin(async { Err(anyhow!("model unavailable")) })
+        }
+
+        fn list_models(&self) -> BoxFuture<'_, Result<Vec<ModelInfo>>> {
+            Box::pin(async { Ok(Vec::new()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn stops_at_the_deadline() {
+        let manager = manager("deadline");
+        let limits = AgentLimits {
+            max_duration_ms: 50,
+            ..AgentLimits::default()
+        };
+        let run = AgentRuntime::new(&SlowProvider, &manager)
+            .run(request(limits), |_| {})
+            .await
+            .unwrap();
+        assert_eq!((run.reason, run.steps), (StopReason::Timeout, 1));
+        assert_eq!(run.messages.len(), 1);
+        assert_eq!(run.messages[0].content.as_deref(), Some("Let me"));
+    }
+
+    #[tokio::test]
+    async fn keeps_the_partial_answer_when_stopped() {
+        let manager = manager("stopped");
+        let cancel = CancelToken::new();
+        let trigger = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            trigger.cancel();
+        });
+
+        // The model that took over from a failed one is named in the stopped answer
+        let provider = FallbackChain::new("primary", Box::new(FailingProvider))
+            .or_else("fallback", Box::new(SlowProvider));
+        let run = AgentRuntime::new(&provider, &manager)
+            .with_cancel(cancel)
+            .run(request(AgentLimits::default()), |_| {})
+            .await
+            .unwrap();
+        assert_eq!((run.reason, run.steps), (StopReason::Stopped, 1));
+        assert_eq!(run.messages.len(), 1);
+        assert_eq!(run.messages[0].content.as_deref(), Some("Let me"));
+        assert_eq!(run.model.as_deref(), Some("fallback"));
+    }
+}
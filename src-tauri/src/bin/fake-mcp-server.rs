@@ -0,0 +1,269 @@
+//! Scriptable fake MCP server for tests and local development
+//!
+//! Usage: `fake-mcp-server [<script json>]`, built with the `test-support` feature
+//!
+//! The script describes the tools the server offers and how each one misbehaves:
+//!
+//! ```json
+//! {
+//!   "banner": "printed to stdout before anything else",
+//!   "stderr": ["lines written to stderr at startup"],
+//!   "initializeDelayMs": 0,
+//!   "initializeError": "fail initialize with this message",
+//!   "tools": [{
+//!     "name": "slow",
+//!     "annotations": { "readOnlyHint": true },
+//!     "delayMs": 500,
+//!     "progress": 5,
+//!     "result": "fixed text; arguments are echoed back when omitted",
+//!     "isError": false,
+//!     "error": "answer with a JSON-RPC error instead",
+//!     "notify": true,
+//!     "malformed": true,
+//!     "crash": 3,
+//!     "listChanged": true
+//!   }]
+//! }
+//! ```
+//!
+//! `progress` spreads that many progress notifications over the delay, `notify`
+//! sends a log notification first, `malformed` writes a non-JSON line first and
+//! `crash` exits with the given code instead of answering. `listChanged` sends a
+//! tools list changed notification after answering. The `crash` tool of the
+//! default script exits immediately.
+//!
+//! Tool calls are answered on their own threads, so slow calls overlap the way
+//! they would on a real server.
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Script {
+    #[serde(default)]
+    banner: Option<String>,
+    #[serde(default)]
+    stderr: Vec<String>,
+    #[serde(default)]
+    initialize_delay_ms: u64,
+    #[serde(default)]
+    initialize_error: Option<String>,
+    #[serde(default = "default_tools")]
+    tools: Vec<ToolScript>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolScript {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    annotations: Option<Value>,
+    #[serde(default)]
+    delay_ms: u64,
+    #[serde(default)]
+    progress: u32,
+    #[serde(default)]
+    result: Option<String>,
+    #[serde(default)]
+    is_error: bool,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    notify: bool,
+    #[serde(default)]
+    malformed: bool,
+    #[serde(default)]
+    crash: Option<i32>,
+    #[serde(default)]
+    list_changed: bool,
+}
+
+impl ToolScript {
+    fn named(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            description: None,
+            annotations: None,
+            delay_ms: 0,
+            progress: 0,
+            result: None,
+            is_error: false,
+            error: None,
+            notify: false,
+            malformed: false,
+            crash: None,
+            list_changed: false,
+        }
+    }
+}
+
+fn default_tools() -> Vec<ToolScript> {
+    vec![
+        ToolScript::named("echo"),
+        ToolScript {
+            crash: Some(1),
+            ..ToolScript::named("crash")
+        },
+    ]
+}
+
+fn main() {
+    let script: Script = match std::env::args().nth(1) {
+        Some(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            eprintln!("fake-mcp-server: invalid script: {}", e);
+            std::process::exit(2);
+        }),
+        None => serde_json::from_value(json!({})).expect("default script"),
+    };
+    let script = Arc::new(script);
+
+    for line in &script.stderr {
+        eprintln!("{}", line);
+    }
+    if let Some(banner) = &script.banner {
+        println!("{}", banner);
+    }
+
+    let stdin = std::io::stdin();
+    for line in stdin.lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let Ok(message) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        let id = match message.get("id") {
+            None | Some(Value::Null) => continue,
+            Some(id) => id.clone(),
+        };
+        let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        match method {
+            "initialize" => {
+                std::thread::sleep(Duration::from_millis(script.initialize_delay_ms));
+                match &script.initialize_error {
+                    Some(error) => send_error(&id, -32603, error),
+                    None => send_result(
+                        &id,
+                        json!({
+                            "protocolVersion": "2024-11-05",
+                            "capabilities": { "tools": {} },
+                            "serverInfo": { "name": "fake-mcp-server", "version": "1.0.0" },
+                        }),
+                    ),
+                }
+            }
+            "tools/list" => {
+                let tools: Vec<Value> = script
+                    .tools
+                    .iter()
+                    .map(|tool| {
+                        let mut definition = json!({
+                            "name": tool.name,
+                            "description": tool.description.clone().unwrap_or_else(|| format!("Fake tool {}", tool.name)),
+                            "inputSchema": { "type": "object" },
+                        });
+                        if let Some(annotations) = &tool.annotations {
+                            definition["annotations"] = annotations.clone();
+                        }
+                        definition
+                    })
+                    .collect();
+                send_result(&id, json!({ "tools": tools }));
+            }
+            "tools/call" => {
+                let script = script.clone();
+                std::thread::spawn(move || call_tool(&script, &id, &params));
+            }
+            "ping" => send_result(&id, json!({})),
+            _ => send_error(&id, -32601, &format!("Method '{}' not found", method)),
+        }
+    }
+}
+
+fn call_tool(script: &Script, id: &Value, params: &Value) {
+    let name = params.get("name").and_then(Value::as_str).unwrap_or("");
+    let Some(tool) = script.tools.iter().find(|tool| tool.name == name) else {
+        send_error(id, -32602, &format!("Unknown tool '{}'", name));
+        return;
+    };
+
+    if tool.notify {
+        send(&json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/message",
+            "params": { "level": "info", "data": format!("calling {}", name) },
+        }));
+    }
+    if tool.malformed {
+        send_line("this is not JSON-RPC");
+    }
+
+    let token = params
+        .get("_meta")
+        .and_then(|meta| meta.get("progressToken"));
+    let steps = tool.progress.max(1);
+    for step in 1..=steps {
+        std::thread::sleep(Duration::from_millis(tool.delay_ms / steps as u64));
+        if let (Some(token), true) = (token, tool.progress > 0) {
+            send(&json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/progress",
+                "params": { "progressToken": token, "progress": step, "total": tool.progress },
+            }));
+        }
+    }
+
+    if let Some(code) = tool.crash {
+        std::process::exit(code);
+    }
+    if let Some(error) = &tool.error {
+        send_error(id, -32603, error);
+        return;
+    }
+
+    let text = match &tool.result {
+        Some(text) => text.clone(),
+        None => params
+            .get("arguments")
+            .cloned()
+            .unwrap_or(Value::Null)
+            .to_string(),
+    };
+    send_result(
+        id,
+        json!({
+            "content": [{ "type": "text", "text": text }],
+            "isError": tool.is_error,
+        }),
+    );
+
+    if tool.list_changed {
+        send(&json!({ "jsonrpc": "2.0", "method": "notifications/tools/list_changed" }));
+    }
+}
+
+fn send_result(id: &Value, result: Value) {
+    send(&json!({ "jsonrpc": "2.0", "id": id, "result": result }));
+}
+
+fn send_error(id: &Value, code: i32, message: &str) {
+    send(&json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } }));
+}
+
+fn send(message: &Value) {
+    send_line(&message.to_string());
+}
+
+fn send_line(line: &str) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{}", line);
+    let _ = stdout.flush();
+}
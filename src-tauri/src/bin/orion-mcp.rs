@@ -0,0 +1,40 @@
+//! Stdio MCP server for other MCP clients
+//!
+//! Serves Orion conversations, settings and saved prompts, or with `--gateway`
+//! forwards to the running app's MCP gateway, which proxies its servers.
+
+use orion_lib::SidecarOptions;
+use std::path::PathBuf;
+
+const USAGE: &str = "Usage: orion-mcp [--gateway] [--data-dir <path>]";
+
+fn main() {
+    let mut options = SidecarOptions::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--gateway" => options.gateway = true,
+            "--data-dir" => options.data_dir = Some(PathBuf::from(path_arg(&mut args, &arg))),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => exit_with(&format!("Unknown argument '{}'\n{}", arg, USAGE)),
+        }
+    }
+
+    // stdout carries the protocol, so errors go to stderr
+    if let Err(e) = orion_lib::run_mcp_sidecar(options) {
+        exit_with(&e.to_string());
+    }
+}
+
+fn path_arg(args: &mut impl Iterator<Item = String>, flag: &str) -> String {
+    args.next()
+        .unwrap_or_else(|| exit_with(&format!("{} requires a path", flag)))
+}
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("orion-mcp: {}", message);
+    std::process::exit(1);
+}
@@ -0,0 +1,90 @@
+use serde_json::Map;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::ipc::Channel;
+use tauri::{AppHandle, Manager, State};
+
+use crate::agent::{AgentEvent, AgentRequest, AgentRun, AgentRuntime, StopReason};
+use crate::commands::chat::{chat_provider, ChatCommandError};
+use crate::commands::storage::{Message, StorageState};
+use crate::llm::{FallbackChain, GenerationRuns, Role};
+use crate::mcp::McpServerManager;
+
+/// Run the tool-calling loop for a conversation in the backend
+///
+/// Every step is pushed to `on_event` as it happens; the returned run holds the
+/// messages to append to the conversation. Uses OpenRouter when no `provider_id`
+/// is given. Cancelling `run_id` through `cancel_generation` ends the run with
+/// the `stopped` reason, keeping the partial answer; it is also saved in the
+/// request's conversation as `message_id`, marked as stopped.
+///
+/// The request's fallback models are asked in order when the model fails;
+/// fallbacks whose provider isn't available, like a stopped local server, are
+/// left out.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn run_agent(
+    app: AppHandle,
+    manager: State<'_, McpServerManager>,
+    runs: State<'_, GenerationRuns>,
+    run_id: String,
+    message_id: Option<String>,
+    provider_id: Option<String>,
+    request: AgentRequest,
+    on_event: Channel<AgentEvent>,
+) -> Result<AgentRun, ChatCommandError> {
+    // Registered first, so the run can be cancelled while its provider is set up
+    let run = runs.register(&run_id)?;
+    let mut model =
+        FallbackChain::new(&request.model, chat_provider(&app, provider_id.as_deref())?);
+    for fallback in &request.fallbacks {
+        match chat_provider(&app, fallback.provider_id.as_deref()) {
+            Ok(provider) => model = model.or_else(&fallback.model, provider),
+            Err(error) => eprintln!("Skipping fallback '{}': {}", fallback.model, error),
+        }
+    }
+
+    let conversation_id = request.conversation_id.clone();
+    let result = AgentRuntime::new(&model, &manager)
+        .with_cancel(run.cancel_token().clone())
+        .run(request, |event| {
+            let _ = on_event.send(event);
+        })
+        .await?;
+
+    if let (StopReason::Stopped, Some(conversation_id), Some(message_id)) =
+        (result.reason, conversation_id, message_id)
+    {
+        let message = stopped_message(message_id, &result);
+        let storage = app.state::<StorageState>();
+        if let Err(e) = storage.save_stopped_message(&conversation_id, message) {
+            eprintln!("Failed to save the stopped answer: {}", e);
+        }
+    }
+    Ok(result)
+}
+
+/// Assistant message with the text of a stopped run, as the frontend shows it
+fn stopped_message(id: String, run: &AgentRun) -> Message {
+    let content = run
+        .messages
+        .iter()
+        .filter(|msg| msg.role == Role::Assistant)
+        .filter_map(|msg| msg.content.as_deref())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default();
+
+    Message {
+        id,
+        role: "assistant".to_string(),
+        content,
+        timestamp,
+        model: run.model.clone(),
+        stopped: true,
+        extra: Map::new(),
+    }
+}
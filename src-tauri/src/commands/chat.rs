@@ -0,0 +1,144 @@
+use serde::Serialize;
+use tauri::ipc::Channel;
+use tauri::{AppHandle, Manager, State};
+
+use crate::commands::storage::{StorageState, API_KEY_SECRET};
+use crate::llm::provider::{self, DEFAULT_PROVIDER_ID};
+use crate::llm::runs::stream_cancellable;
+use crate::llm::{
+    ChatChunk, ChatProvider, ChatRequest, ChatResponse, GenerationRuns, InferenceServerManager,
+    OpenAiClient, ProviderError, ProviderErrorKind, ProviderStore, RequestScheduler,
+};
+use crate::mcp::McpServerManager;
+
+/// Error returned by commands that talk to a model provider
+///
+/// Provider failures keep their kind so the UI can point at the API key settings
+/// or offer a retry after `retryAfterMs`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ChatCommandError {
+    #[serde(rename_all = "camelCase")]
+    Provider {
+        message: String,
+        reason: ProviderErrorKind,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        status: Option<u16>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        retry_after_ms: Option<u64>,
+    },
+    Other {
+        message: String,
+    },
+}
+
+impl From<anyhow::Error> for ChatCommandError {
+    fn from(error: anyhow::Error) -> Self {
+        match error.downcast::<ProviderError>() {
+            Ok(provider) => Self::Provider {
+                message: provider.message,
+                reason: provider.kind,
+                status: provider.status,
+                retry_after_ms: provider.retry_after_ms,
+            },
+            Err(error) => Self::Other {
+                message: error.to_string(),
+            },
+        }
+    }
+}
+
+impl From<String> for ChatCommandError {
+    fn from(message: String) -> Self {
+        Self::Other { message }
+    }
+}
+
+impl std::fmt::Display for ChatCommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Provider { message, .. } | Self::Other { message } => f.write_str(message),
+        }
+    }
+}
+
+/// Client for a configured provider, or OpenRouter with the key from the settings
+///
+/// Providers with a managed server are only available once it is ready. Requests
+/// wait in the provider's queue of the [`RequestScheduler`] and are retried there,
+/// for OpenRouter with the `openrouter_limits` from `settings.json`.
+pub(crate) fn chat_provider(
+    app: &AppHandle,
+    provider_id: Option<&str>,
+) -> Result<Box<dyn ChatProvider>, ChatCommandError> {
+    let scheduler = app.state::<RequestScheduler>();
+    match provider_id.filter(|id| *id != DEFAULT_PROVIDER_ID) {
+        Some(id) => {
+            let config = app.state::<ProviderStore>().get(id)?;
+            if config.managed.is_some() {
+                let servers = app.state::<InferenceServerManager>();
+                servers.ensure_ready(&config)?;
+            }
+            let manager = app.state::<McpServerManager>();
+            let client = provider::connect(&config, manager.secrets())?;
+            Ok(scheduler.schedule(&config.id, &config.limits, client)?)
+        }
+        None => {
+            let api_key = app
+                .state::<McpServerManager>()
+                .secrets()
+                .get(API_KEY_SECRET)?
+                .filter(|key| !key.is_empty())
+                .ok_or_else(|| ChatCommandError::Provider {
+                    message: "Please set your OpenRouter API key in settings".to_string(),
+                    reason: ProviderErrorKind::Auth,
+                    status: None,
+                    retry_after_ms: None,
+                })?;
+            let limits = app
+                .state::<StorageState>()
+                .settings
+                .lock()
+                .map_err(|e| e.to_string())?
+                .as_ref()
+                .and_then(|settings| settings.openrouter_limits.clone())
+                .unwrap_or_default();
+            let client = Box::new(OpenAiClient::openrouter(api_key));
+            Ok(scheduler.schedule(DEFAULT_PROVIDER_ID, &limits, client)?)
+        }
+    }
+}
+
+/// Stream one chat completion, pushing each chunk to `on_chunk`
+///
+/// Returns the complete response, with tool call fragments already assembled.
+/// Uses OpenRouter when no `provider_id` is given. Cancelling `run_id` through
+/// `cancel_generation` returns the text so far with a `stopped` finish reason.
+#[tauri::command]
+pub async fn stream_chat(
+    app: AppHandle,
+    runs: State<'_, GenerationRuns>,
+    run_id: String,
+    provider_id: Option<String>,
+    request: ChatRequest,
+    on_chunk: Channel<ChatChunk>,
+) -> Result<ChatResponse, ChatCommandError> {
+    let run = runs.register(&run_id)?;
+    let client = chat_provider(&app, provider_id.as_deref())?;
+    let send = |chunk| {
+        let _ = on_chunk.send(chunk);
+    };
+    Ok(stream_cancellable(client.as_ref(), &request, &send, run.cancel_token()).await?)
+}
+
+/// Stop a `stream_chat` or `run_agent` run
+///
+/// The completion in flight is aborted and pending MCP tool calls are
+/// cancelled; the run then returns what it generated so far.
+#[tauri::command]
+pub async fn cancel_generation(
+    runs: State<'_, GenerationRuns>,
+    run_id: String,
+) -> Result<(), String> {
+    runs.cancel(&run_id).map_err(|e| e.to_string())
+}
@@ -1,18 +1,66 @@
+use serde::Serialize;
 use serde_json::Value;
 use tauri::State;
 
-use crate::mcp::{CallToolResult, McpServerConfig, McpServerManager, OpenAITool};
+use crate::mcp::{
+    ApprovalDecision, BatchCall, CallOptions, CallQueueStatus, CallToolResult, GatewayStatus,
+    McpGateway, McpServerConfig, McpServerManager, MetricsFormat, MetricsReport, OpenAITool,
+    ProcessInfo, SandboxError, ServerDiagnostics, TimeoutError, ToolRef, TraceEntry,
+};
+
+/// Number of trace entries returned when no limit is given
+const DEFAULT_TRACE_LIMIT: usize = 200;
+
+/// Error returned by every MCP command
+///
+/// Timeouts are reported separately so the UI can offer a retry with a longer limit,
+/// and sandbox failures so it can point at the server's sandbox profile.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum McpCommandError {
+    #[serde(rename_all = "camelCase")]
+    Timeout {
+        message: String,
+        method: String,
+        timeout_ms: u64,
+    },
+    Sandbox {
+        message: String,
+        denials: Vec<String>,
+    },
+    Other { message: String },
+}
+
+impl From<anyhow::Error> for McpCommandError {
+    fn from(error: anyhow::Error) -> Self {
+        if let Some(timeout) = error.downcast_ref::<TimeoutError>() {
+            return Self::Timeout {
+                message: timeout.to_string(),
+                method: timeout.method.clone(),
+                timeout_ms: timeout.timeout_ms,
+            };
+        }
+
+        if let Some(sandbox) = error.downcast_ref::<SandboxError>() {
+            return Self::Sandbox {
+                message: sandbox.to_string(),
+                denials: sandbox.denials.clone(),
+            };
+        }
+
+        Self::Other {
+            message: error.to_string(),
+        }
+    }
+}
 
 /// Start a new MCP server
 #[tauri::command]
 pub async fn start_mcp_server(
     state: State<'_, McpServerManager>,
     config: McpServerConfig,
-) -> Result<String, String> {
-    state
-        .start_server(config)
-        .await
-        .map_err(|e| e.to_string())
+) -> Result<String, McpCommandError> {
+    Ok(state.start_server(config).await?)
 }
 
 /// Stop an MCP server
@@ -20,46 +68,124 @@ pub async fn start_mcp_server(
 pub async fn stop_mcp_server(
     state: State<'_, McpServerManager>,
     server_id: String,
-) -> Result<(), String> {
-    state
-        .stop_server(&server_id)
-        .await
-        .map_err(|e| e.to_string())
+) -> Result<(), McpCommandError> {
+    Ok(state.stop_server(&server_id).await?)
+}
+
+/// Restart an MCP server with its current configuration
+#[tauri::command]
+pub async fn restart_mcp_server(
+    state: State<'_, McpServerManager>,
+    server_id: String,
+) -> Result<(), McpCommandError> {
+    Ok(state.restart_server(&server_id).await?)
 }
 
 /// List all running MCP servers
 #[tauri::command]
-pub async fn list_mcp_servers(state: State<'_, McpServerManager>) -> Result<Vec<String>, String> {
+pub async fn list_mcp_servers(
+    state: State<'_, McpServerManager>,
+) -> Result<Vec<String>, McpCommandError> {
     Ok(state.list_servers().await)
 }
 
 /// List tools from a specific MCP server (returns OpenAI-compatible format)
+///
+/// Tool names are mangled into the `^[a-zA-Z0-9_-]{1,64}$` form required by LLM
+/// providers; use `resolve_mcp_tool_name` to map a model's tool call back.
 #[tauri::command]
 pub async fn list_mcp_tools(
     state: State<'_, McpServerManager>,
     server_id: String,
-) -> Result<Vec<OpenAITool>, String> {
-    let mcp_tools = state
-        .list_tools(&server_id)
+) -> Result<Vec<OpenAITool>, McpCommandError> {
+    Ok(state.list_provider_tools(&server_id).await?)
+}
+
+/// List the tools Orion provides itself, like `read_tool_result` for paging
+/// through truncated results
+#[tauri::command]
+pub async fn list_builtin_mcp_tools(
+    state: State<'_, McpServerManager>,
+) -> Result<Vec<OpenAITool>, McpCommandError> {
+    Ok(state
+        .list_builtin_tools()
         .await
-        .map_err(|e| e.to_string())?;
+        .into_iter()
+        .map(OpenAITool::from)
+        .collect())
+}
 
-    // Convert MCP tools to OpenAI format
-    Ok(mcp_tools.into_iter().map(|tool| tool.into()).collect())
+/// Resolve a provider-safe tool name to its server and original tool name
+#[tauri::command]
+pub async fn resolve_mcp_tool_name(
+    state: State<'_, McpServerManager>,
+    name: String,
+) -> Result<ToolRef, McpCommandError> {
+    Ok(state.resolve_tool_name(&name).await?)
 }
 
 /// Call a tool on a specific MCP server
+///
+/// `timeout_ms` overrides the configured timeout, e.g. when retrying after a timeout.
+/// The call is recorded in the audit log with its conversation and approval decision;
+/// a `denied` decision is logged without calling the server.
 #[tauri::command]
 pub async fn call_mcp_tool(
     state: State<'_, McpServerManager>,
     server_id: String,
     tool_name: String,
     arguments: Option<Value>,
-) -> Result<CallToolResult, String> {
-    state
-        .call_tool(&server_id, &tool_name, arguments)
+    timeout_ms: Option<u64>,
+    conversation_id: Option<String>,
+    approval: Option<ApprovalDecision>,
+) -> Result<CallToolResult, McpCommandError> {
+    let options = CallOptions {
+        timeout_ms,
+        conversation_id,
+        approval: approval.unwrap_or_default(),
+        cancel: None,
+        deadline: None,
+    };
+    Ok(state
+        .call_tool(&server_id, &tool_name, arguments, options)
+        .await?)
+}
+
+/// Outcome of one call in a batch; exactly one of the fields is set
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchCallResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<CallToolResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<McpCommandError>,
+}
+
+/// Call several tools, possibly on different servers, concurrently
+///
+/// Results come back in the order of `calls`. A failing call reports its error in
+/// its own slot instead of failing the batch.
+#[tauri::command]
+pub async fn call_mcp_tools_batch(
+    state: State<'_, McpServerManager>,
+    calls: Vec<BatchCall>,
+    conversation_id: Option<String>,
+) -> Result<Vec<BatchCallResult>, McpCommandError> {
+    Ok(state
+        .call_tools_batch(calls, conversation_id)
         .await
-        .map_err(|e| e.to_string())
+        .into_iter()
+        .map(|outcome| match outcome {
+            Ok(result) => BatchCallResult {
+                result: Some(result),
+                error: None,
+            },
+            Err(e) => BatchCallResult {
+                result: None,
+                error: Some(e.into()),
+            },
+        })
+        .collect())
 }
 
 /// Get server info for a specific MCP server
@@ -67,10 +193,103 @@ pub async fn call_mcp_tool(
 pub async fn get_mcp_server_info(
     state: State<'_, McpServerManager>,
     server_id: String,
-) -> Result<Option<crate::mcp::InitializeResult>, String> {
-    let server = state
-        .get_server(&server_id)
-        .await
-        .map_err(|e| e.to_string())?;
+) -> Result<Option<crate::mcp::InitializeResult>, McpCommandError> {
+    let server = state.get_server(&server_id).await?;
     Ok(server.server_info.clone())
 }
+
+/// Get details about the process backing an MCP server, including the resolved binary
+#[tauri::command]
+pub async fn get_mcp_server_process(
+    state: State<'_, McpServerManager>,
+    server_id: String,
+) -> Result<ProcessInfo, McpCommandError> {
+    let server = state.get_server(&server_id).await?;
+    Ok(server.process.clone())
+}
+
+/// Get output an MCP server produced besides valid JSON-RPC messages, plus recent stderr
+#[tauri::command]
+pub async fn get_mcp_server_diagnostics(
+    state: State<'_, McpServerManager>,
+    server_id: String,
+) -> Result<ServerDiagnostics, McpCommandError> {
+    let server = state.get_server(&server_id).await?;
+    Ok(server.diagnostics())
+}
+
+/// Get how many calls to a server are in flight and how many wait for its
+/// concurrency and rate limits
+#[tauri::command]
+pub async fn get_mcp_server_status(
+    state: State<'_, McpServerManager>,
+    server_id: String,
+) -> Result<CallQueueStatus, McpCommandError> {
+    let server = state.get_server(&server_id).await?;
+    Ok(server.queue_status())
+}
+
+/// Get the most recent recorded JSON-RPC frames of a server, oldest first
+///
+/// Only servers with `recordTraffic` enabled have a trace.
+#[tauri::command]
+pub async fn get_mcp_server_trace(
+    state: State<'_, McpServerManager>,
+    server_id: String,
+    limit: Option<usize>,
+) -> Result<Vec<TraceEntry>, McpCommandError> {
+    Ok(state.trace(&server_id, limit.unwrap_or(DEFAULT_TRACE_LIMIT))?)
+}
+
+/// Start a server that plays back a recorded trace file instead of a real process
+#[tauri::command]
+pub async fn replay_mcp_trace(
+    state: State<'_, McpServerManager>,
+    server_id: String,
+    name: String,
+    path: String,
+) -> Result<String, McpCommandError> {
+    Ok(state
+        .start_replay(&server_id, &name, std::path::Path::new(&path))
+        .await?)
+}
+
+/// Get call counts, error and timeout counts, latency histograms, startup times
+/// and restart counts per server and tool
+///
+/// `format` defaults to JSON; `prometheus` returns the text exposition format.
+#[tauri::command]
+pub async fn get_mcp_metrics(
+    state: State<'_, McpServerManager>,
+    format: Option<MetricsFormat>,
+) -> Result<MetricsReport, McpCommandError> {
+    Ok(state.metrics().report(format.unwrap_or_default()))
+}
+
+/// Start the HTTP MCP gateway proxying to the running servers
+///
+/// Listens on localhost only; `port` defaults to a free port.
+#[tauri::command]
+pub async fn start_mcp_gateway(
+    gateway: State<'_, McpGateway>,
+    manager: State<'_, McpServerManager>,
+    port: Option<u16>,
+) -> Result<GatewayStatus, McpCommandError> {
+    Ok(gateway
+        .start(manager.inner().clone(), port.unwrap_or(0))
+        .await?)
+}
+
+/// Stop the HTTP MCP gateway; the servers themselves keep running
+#[tauri::command]
+pub async fn stop_mcp_gateway(gateway: State<'_, McpGateway>) -> Result<(), McpCommandError> {
+    Ok(gateway.stop().await?)
+}
+
+/// Get the URL and token of the running HTTP MCP gateway, if any
+#[tauri::command]
+pub async fn get_mcp_gateway_status(
+    gateway: State<'_, McpGateway>,
+) -> Result<Option<GatewayStatus>, McpCommandError> {
+    Ok(gateway.status().await)
+}
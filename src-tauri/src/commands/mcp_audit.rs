@@ -0,0 +1,65 @@
+use serde::Serialize;
+use tauri::State;
+
+use crate::mcp::audit::ExportFormat;
+use crate::mcp::{AuditEntry, AuditQuery, AuditRetention, McpServerManager};
+
+/// Search the tool-call audit log, newest first
+#[tauri::command]
+pub async fn query_mcp_audit_log(
+    state: State<'_, McpServerManager>,
+    query: Option<AuditQuery>,
+) -> Result<Vec<AuditEntry>, String> {
+    state
+        .audit_log()
+        .query(&query.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+/// Where an audit log export was written
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditExport {
+    pub path: String,
+    pub entries: usize,
+}
+
+/// Export matching audit log entries to a JSONL or CSV file
+///
+/// The file is written to the audit log's export directory under `file_name`.
+#[tauri::command]
+pub async fn export_mcp_audit_log(
+    state: State<'_, McpServerManager>,
+    file_name: String,
+    format: ExportFormat,
+    query: Option<AuditQuery>,
+) -> Result<AuditExport, String> {
+    let (path, entries) = state
+        .audit_log()
+        .export(&query.unwrap_or_default(), format, &file_name)
+        .map_err(|e| e.to_string())?;
+    Ok(AuditExport {
+        path: path.display().to_string(),
+        entries,
+    })
+}
+
+/// Get how long audit log entries are kept
+#[tauri::command]
+pub async fn get_mcp_audit_retention(
+    state: State<'_, McpServerManager>,
+) -> Result<AuditRetention, String> {
+    state.audit_log().retention().map_err(|e| e.to_string())
+}
+
+/// Change how long audit log entries are kept; older entries are deleted right away
+#[tauri::command]
+pub async fn set_mcp_audit_retention(
+    state: State<'_, McpServerManager>,
+    retention: AuditRetention,
+) -> Result<(), String> {
+    state
+        .audit_log()
+        .set_retention(retention)
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,97 @@
+use std::path::Path;
+use tauri::State;
+
+use crate::mcp::interop::{self, ImportReport};
+use crate::mcp::{McpConfigStore, McpServerConfig, McpServerManager};
+
+/// List saved MCP server configurations
+#[tauri::command]
+pub async fn list_mcp_server_configs(
+    store: State<'_, McpConfigStore>,
+) -> Result<Vec<McpServerConfig>, String> {
+    store.list().map_err(|e| e.to_string())
+}
+
+/// Save a new MCP server configuration
+#[tauri::command]
+pub async fn add_mcp_server_config(
+    store: State<'_, McpConfigStore>,
+    config: McpServerConfig,
+) -> Result<(), String> {
+    store.add(config).map_err(|e| e.to_string())
+}
+
+/// Replace a saved MCP server configuration
+#[tauri::command]
+pub async fn update_mcp_server_config(
+    store: State<'_, McpConfigStore>,
+    config: McpServerConfig,
+) -> Result<(), String> {
+    store.update(config).map_err(|e| e.to_string())
+}
+
+/// Delete a saved MCP server configuration
+#[tauri::command]
+pub async fn delete_mcp_server_config(
+    store: State<'_, McpConfigStore>,
+    server_id: String,
+) -> Result<(), String> {
+    store.remove(&server_id).map_err(|e| e.to_string())
+}
+
+/// Parse a Claude Desktop, Cursor or VS Code MCP config file
+///
+/// Nothing is saved; the frontend adds the servers the user picks.
+#[tauri::command]
+pub async fn import_mcp_server_configs(path: String) -> Result<ImportReport, String> {
+    interop::import_file(Path::new(&path)).map_err(|e| e.to_string())
+}
+
+/// Export saved MCP server configurations in the Claude Desktop format
+///
+/// Exports every saved server when `server_ids` is omitted. Literal environment
+/// values are written in plain text; vault references stay references.
+#[tauri::command]
+pub async fn export_mcp_server_configs(
+    store: State<'_, McpConfigStore>,
+    server_ids: Option<Vec<String>>,
+) -> Result<String, String> {
+    let servers: Vec<McpServerConfig> = store
+        .list()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|config| {
+            server_ids
+                .as_ref()
+                .is_none_or(|ids| ids.contains(&config.id))
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&interop::export_claude_desktop(&servers))
+        .map_err(|e| e.to_string())
+}
+
+/// List the names of secrets in the vault (values never leave the backend)
+#[tauri::command]
+pub async fn list_mcp_secrets(state: State<'_, McpServerManager>) -> Result<Vec<String>, String> {
+    state.secrets().names().map_err(|e| e.to_string())
+}
+
+/// Store a secret for use as a `secret:NAME` environment reference
+#[tauri::command]
+pub async fn set_mcp_secret(
+    state: State<'_, McpServerManager>,
+    name: String,
+    value: String,
+) -> Result<(), String> {
+    state.secrets().set(&name, &value).map_err(|e| e.to_string())
+}
+
+/// Delete a secret from the vault
+#[tauri::command]
+pub async fn delete_mcp_secret(
+    state: State<'_, McpServerManager>,
+    name: String,
+) -> Result<(), String> {
+    state.secrets().remove(&name).map_err(|e| e.to_string())
+}
@@ -1,5 +1,15 @@
+pub mod agent;
+pub mod chat;
 pub mod mcp;
+pub mod mcp_audit;
+pub mod mcp_config;
+pub mod providers;
 pub mod storage;
 
+pub use agent::*;
+pub use chat::*;
 pub use mcp::*;
+pub use mcp_audit::*;
+pub use mcp_config::*;
+pub use providers::*;
 pub use storage::*;
@@ -0,0 +1,100 @@
+use tauri::{AppHandle, State};
+
+use crate::commands::chat::{chat_provider, ChatCommandError};
+use crate::llm::{
+    InferenceServerManager, InferenceServerStatus, ModelInfo, ProviderConfig, ProviderStore,
+};
+
+/// List saved chat providers, not including the default OpenRouter one
+#[tauri::command]
+pub async fn list_chat_providers(
+    store: State<'_, ProviderStore>,
+) -> Result<Vec<ProviderConfig>, String> {
+    store.list().map_err(|e| e.to_string())
+}
+
+/// Save a new chat provider
+#[tauri::command]
+pub async fn add_chat_provider(
+    store: State<'_, ProviderStore>,
+    config: ProviderConfig,
+) -> Result<(), String> {
+    store.add(config).map_err(|e| e.to_string())
+}
+
+/// Replace a saved chat provider
+#[tauri::command]
+pub async fn update_chat_provider(
+    store: State<'_, ProviderStore>,
+    config: ProviderConfig,
+) -> Result<(), String> {
+    store.update(config).map_err(|e| e.to_string())
+}
+
+/// Delete a saved chat provider, stopping its local server if Orion launched one
+#[tauri::command]
+pub async fn delete_chat_provider(
+    store: State<'_, ProviderStore>,
+    servers: State<'_, InferenceServerManager>,
+    provider_id: String,
+) -> Result<(), String> {
+    store.remove(&provider_id).map_err(|e| e.to_string())?;
+    let _ = servers.stop(&provider_id).await;
+    Ok(())
+}
+
+/// Models offered by a provider
+///
+/// Uses the provider's configured model list if it has one and asks its API
+/// otherwise. Uses OpenRouter when no `provider_id` is given.
+#[tauri::command]
+pub async fn list_provider_models(
+    app: AppHandle,
+    store: State<'_, ProviderStore>,
+    provider_id: Option<String>,
+) -> Result<Vec<ModelInfo>, ChatCommandError> {
+    if let Some(id) = provider_id.as_deref() {
+        let configured = store.list()?.into_iter().find(|config| config.id == id);
+        if let Some(config) = configured.filter(|config| !config.models.is_empty()) {
+            return Ok(config
+                .models
+                .into_iter()
+                .map(|id| ModelInfo { id, name: None })
+                .collect());
+        }
+    }
+
+    let provider = chat_provider(&app, provider_id.as_deref())?;
+    Ok(provider.list_models().await?)
+}
+
+/// Launch the local inference server of a managed provider
+///
+/// Returns once the server has loaded its model and answers requests; the
+/// provider can be chatted with from then on.
+#[tauri::command]
+pub async fn start_inference_server(
+    store: State<'_, ProviderStore>,
+    servers: State<'_, InferenceServerManager>,
+    provider_id: String,
+) -> Result<InferenceServerStatus, String> {
+    let config = store.get(&provider_id).map_err(|e| e.to_string())?;
+    servers.start(&config).await.map_err(|e| e.to_string())
+}
+
+/// Stop the local inference server of a managed provider
+#[tauri::command]
+pub async fn stop_inference_server(
+    servers: State<'_, InferenceServerManager>,
+    provider_id: String,
+) -> Result<(), String> {
+    servers.stop(&provider_id).await.map_err(|e| e.to_string())
+}
+
+/// Status and recent log of every local inference server Orion launched
+#[tauri::command]
+pub async fn list_inference_servers(
+    servers: State<'_, InferenceServerManager>,
+) -> Result<Vec<InferenceServerStatus>, String> {
+    servers.list().map_err(|e| e.to_string())
+}
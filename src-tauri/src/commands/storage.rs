@@ -1,8 +1,23 @@
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use tauri::State;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
+use crate::llm::RequestLimits;
+use crate::mcp::{McpServerManager, SecretVault};
+
+/// Files under the app data directory holding persisted storage
+pub const CONVERSATIONS_FILE_NAME: &str = "conversations.json";
+pub const SETTINGS_FILE_NAME: &str = "settings.json";
+pub const PROMPTS_FILE_NAME: &str = "prompts.json";
+
+/// Vault secret holding the OpenRouter API key
+pub const API_KEY_SECRET: &str = "OPENROUTER_API_KEY";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Conversation {
     pub id: String,
     pub title: String,
@@ -10,9 +25,13 @@ pub struct Conversation {
     pub created_at: u64,
     pub updated_at: u64,
     pub model: String,
+    /// Fields only the frontend uses, e.g. fallback models, kept as it sent them
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Message {
     pub id: String,
     pub role: String,
@@ -20,18 +39,170 @@ pub struct Message {
     pub timestamp: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
+    /// The user stopped the generation, so the content is partial
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stopped: bool,
+    /// Fields only the frontend uses, e.g. tool calls, kept as it sent them
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
+/// Settings saved in `settings.json`; the API key is kept in the secret vault
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
-    pub api_key: Option<String>,
     pub selected_model: String,
+    /// Concurrency, rate limit and retries of requests with the OpenRouter key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub openrouter_limits: Option<RequestLimits>,
+}
+
+/// `settings.json` as read, with the API key earlier builds kept there
+#[derive(Deserialize)]
+struct StoredSettings {
+    #[serde(flatten)]
+    settings: Settings,
+    #[serde(default)]
+    api_key: Option<String>,
+}
+
+/// Reusable prompt template; `{{name}}` placeholders become prompt arguments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedPrompt {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub content: String,
 }
 
 #[derive(Default)]
 pub struct StorageState {
     pub conversations: Mutex<Vec<Conversation>>,
     pub settings: Mutex<Option<Settings>>,
+    pub prompts: Mutex<Vec<SavedPrompt>>,
+    /// Directory the state is persisted to; `None` keeps it in memory only
+    data_dir: Option<PathBuf>,
+}
+
+impl StorageState {
+    /// Load persisted storage from `data_dir`, writing changes back there
+    ///
+    /// A file that can't be read is set aside and its part starts empty, so one
+    /// bad file doesn't keep the app from starting. An API key left in
+    /// `settings.json` by an earlier build is moved to `secrets`.
+    pub fn load(data_dir: &Path, secrets: &SecretVault) -> anyhow::Result<Self> {
+        let conversations = read_json_or_set_aside(&data_dir.join(CONVERSATIONS_FILE_NAME));
+        let prompts = read_json_or_set_aside(&data_dir.join(PROMPTS_FILE_NAME));
+
+        let settings_path = data_dir.join(SETTINGS_FILE_NAME);
+        let (settings, legacy_key) = match read_json_or_set_aside(&settings_path) {
+            Some(StoredSettings { settings, api_key }) => (Some(settings), api_key),
+            None => (None, None),
+        };
+        if let Some(key) = legacy_key {
+            if !key.is_empty() {
+                secrets.set(API_KEY_SECRET, &key)?;
+            }
+            write_json(&settings_path, &settings)?;
+        }
+
+        Ok(Self {
+            conversations: Mutex::new(conversations.unwrap_or_default()),
+            settings: Mutex::new(settings),
+            prompts: Mutex::new(prompts.unwrap_or_default()),
+            data_dir: Some(data_dir.to_path_buf()),
+        })
+    }
+
+    /// Save the partial answer of a stopped run in its conversation
+    ///
+    /// A message the frontend already saved under the same ID is replaced, keeping
+    /// the fields only the frontend uses, so the answer is kept without the webview.
+    pub fn save_stopped_message(
+        &self,
+        conversation_id: &str,
+        message: Message,
+    ) -> Result<(), String> {
+        let mut storage = self.conversations.lock().map_err(|e| e.to_string())?;
+        let mut conversations = storage.clone();
+        let conversation = conversations
+            .iter_mut()
+            .find(|conv| conv.id == conversation_id)
+            .ok_or_else(|| format!("Conversation '{}' not found", conversation_id))?;
+
+        conversation.updated_at = message.timestamp;
+        match conversation
+            .messages
+            .iter_mut()
+            .find(|msg| msg.id == message.id)
+        {
+            Some(existing) => {
+                existing.content = message.content;
+                existing.model = message.model.or(existing.model.take());
+                existing.stopped = true;
+            }
+            None => conversation.messages.push(Message {
+                stopped: true,
+                ..message
+            }),
+        }
+
+        self.persist(CONVERSATIONS_FILE_NAME, &conversations)?;
+        *storage = conversations;
+        Ok(())
+    }
+
+    fn persist<T: Serialize + ?Sized>(&self, file_name: &str, value: &T) -> Result<(), String> {
+        match &self.data_dir {
+            Some(dir) => write_json(&dir.join(file_name), value).map_err(|e| e.to_string()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Read a JSON file, treating a missing file as empty
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> anyhow::Result<Option<T>> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(serde_json::from_str(&contents).map_err(|e| {
+            anyhow::anyhow!("Failed to parse {}: {}", path.display(), e)
+        })?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(anyhow::anyhow!("Failed to read {}: {}", path.display(), e)),
+    }
+}
+
+/// Read a JSON file, or set one that can't be read aside and treat it as missing
+///
+/// As with the config stores, the file gets an `.invalid` extension so the next
+/// save doesn't overwrite it.
+fn read_json_or_set_aside<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    match read_json(path) {
+        Ok(value) => value,
+        Err(e) => {
+            let aside = path.with_extension("json.invalid");
+            eprintln!(
+                "Starting without {}, moving it to {}: {:#}",
+                path.display(),
+                aside.display(),
+                e
+            );
+            if let Err(e) = std::fs::rename(path, &aside) {
+                eprintln!("Failed to move {}: {}", path.display(), e);
+            }
+            None
+        }
+    }
+}
+
+/// Write a JSON file atomically so readers never see a partial file
+fn write_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, serde_json::to_string_pretty(value)?)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
 }
 
 #[tauri::command]
@@ -40,6 +211,7 @@ pub async fn save_conversations(
     conversations: Vec<Conversation>,
 ) -> Result<(), String> {
     let mut storage = state.conversations.lock().map_err(|e| e.to_string())?;
+    state.persist(CONVERSATIONS_FILE_NAME, &conversations)?;
     *storage = conversations;
     Ok(())
 }
@@ -52,16 +224,55 @@ pub async fn load_conversations(
     Ok(storage.clone())
 }
 
+/// Save the settings; OpenRouter limits that aren't sent are kept
 #[tauri::command]
 pub async fn save_settings(
     state: State<'_, StorageState>,
-    settings: Settings,
+    mut settings: Settings,
 ) -> Result<(), String> {
     let mut storage = state.settings.lock().map_err(|e| e.to_string())?;
+    if settings.openrouter_limits.is_none() {
+        settings.openrouter_limits = storage
+            .as_ref()
+            .and_then(|stored| stored.openrouter_limits.clone());
+    }
+    state.persist(SETTINGS_FILE_NAME, &settings)?;
     *storage = Some(settings);
     Ok(())
 }
 
+/// Store the OpenRouter API key in the secret vault; an empty key removes it
+#[tauri::command]
+pub async fn set_api_key(
+    manager: State<'_, McpServerManager>,
+    api_key: String,
+) -> Result<(), String> {
+    let api_key = api_key.trim();
+    let result = if api_key.is_empty() {
+        remove_api_key(manager.secrets())
+    } else {
+        manager.secrets().set(API_KEY_SECRET, api_key)
+    };
+    result.map_err(|e| e.to_string())
+}
+
+/// Whether an OpenRouter API key is stored; the key itself never leaves the backend
+#[tauri::command]
+pub async fn has_api_key(manager: State<'_, McpServerManager>) -> Result<bool, String> {
+    let key = manager
+        .secrets()
+        .get(API_KEY_SECRET)
+        .map_err(|e| e.to_string())?;
+    Ok(key.is_some())
+}
+
+fn remove_api_key(secrets: &SecretVault) -> anyhow::Result<()> {
+    if secrets.get(API_KEY_SECRET)?.is_some() {
+        secrets.remove(API_KEY_SECRET)?;
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn load_settings(
     state: State<'_, StorageState>,
@@ -71,12 +282,45 @@ pub async fn load_settings(
 }
 
 #[tauri::command]
-pub async fn clear_all_data(state: State<'_, StorageState>) -> Result<(), String> {
+pub async fn save_prompts(
+    state: State<'_, StorageState>,
+    prompts: Vec<SavedPrompt>,
+) -> Result<(), String> {
+    let mut storage = state.prompts.lock().map_err(|e| e.to_string())?;
+    state.persist(PROMPTS_FILE_NAME, &prompts)?;
+    *storage = prompts;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn load_prompts(
+    state: State<'_, StorageState>,
+) -> Result<Vec<SavedPrompt>, String> {
+    let storage = state.prompts.lock().map_err(|e| e.to_string())?;
+    Ok(storage.clone())
+}
+
+#[tauri::command]
+pub async fn clear_all_data(
+    state: State<'_, StorageState>,
+    manager: State<'_, McpServerManager>,
+) -> Result<(), String> {
     let mut conversations = state.conversations.lock().map_err(|e| e.to_string())?;
     let mut settings = state.settings.lock().map_err(|e| e.to_string())?;
+    let mut prompts = state.prompts.lock().map_err(|e| e.to_string())?;
+
+    if let Some(dir) = &state.data_dir {
+        for file_name in [CONVERSATIONS_FILE_NAME, SETTINGS_FILE_NAME, PROMPTS_FILE_NAME] {
+            match std::fs::remove_file(dir.join(file_name)) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.to_string()),
+                _ => {}
+            }
+        }
+    }
 
     *conversations = Vec::new();
     *settings = None;
+    *prompts = Vec::new();
 
-    Ok(())
+    remove_api_key(manager.secrets()).map_err(|e| e.to_string())
 }
@@ -1,8 +1,21 @@
+pub mod agent;
 mod commands;
-mod mcp;
+pub mod llm;
+pub mod mcp;
+pub mod store;
 
 use commands::storage::StorageState;
-use mcp::McpServerManager;
+use llm::provider::PROVIDERS_FILE_NAME;
+use llm::{GenerationRuns, InferenceServerManager, ProviderStore, RequestScheduler};
+use mcp::config::CONFIG_FILE_NAME;
+use mcp::gateway::GatewayClient;
+use mcp::{HistoryHandler, McpConfigStore, McpGateway, McpServerManager, SecretVault};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::Manager;
+
+/// Bundle identifier from tauri.conf.json, which names the app data directory
+const APP_IDENTIFIER: &str = "com.craig.orion";
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
@@ -16,22 +29,159 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_store::Builder::default().build())
         .plugin(tauri_plugin_shell::init())
-        .manage(StorageState::default())
-        .manage(McpServerManager::default())
+        .setup(|app| {
+            let data_dir = app.path().app_data_dir()?;
+            let secrets = SecretVault::open(&data_dir)?;
+            app.manage(StorageState::load(&data_dir, &secrets)?);
+            app.manage(McpServerManager::new(Arc::new(secrets), &data_dir)?);
+            app.manage(McpGateway::new(&data_dir));
+
+            let config_dir = app.path().app_config_dir()?;
+            let provider_store = ProviderStore::load_or_empty(config_dir.join(PROVIDERS_FILE_NAME));
+            let autostart_providers = provider_store.autostart_configs()?;
+            app.manage(provider_store);
+            app.manage(InferenceServerManager::new());
+            app.manage(GenerationRuns::new());
+            app.manage(RequestScheduler::new());
+
+            let config_store = McpConfigStore::load_or_empty(config_dir.join(CONFIG_FILE_NAME));
+            let autostart = config_store.autostart_configs()?;
+            app.manage(config_store);
+
+            // Start autostart servers in the background so a slow server doesn't block launch
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let manager = handle.state::<McpServerManager>();
+                for config in autostart {
+                    let name = config.name.clone();
+                    if let Err(e) = manager.start_server(config).await {
+                        eprintln!("Failed to autostart MCP server '{}': {}", name, e);
+                    }
+                }
+            });
+
+            // Local models can take a while to load, so they start alongside the MCP servers
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let servers = handle.state::<InferenceServerManager>();
+                for config in autostart_providers {
+                    if let Err(e) = servers.start(&config).await {
+                        eprintln!("Failed to start the server for '{}': {}", config.name, e);
+                    }
+                }
+            });
+
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             greet,
             commands::save_conversations,
             commands::load_conversations,
             commands::save_settings,
+            commands::set_api_key,
+            commands::has_api_key,
             commands::load_settings,
+            commands::save_prompts,
+            commands::load_prompts,
             commands::clear_all_data,
             commands::start_mcp_server,
             commands::stop_mcp_server,
+            commands::restart_mcp_server,
             commands::list_mcp_servers,
             commands::list_mcp_tools,
+            commands::list_builtin_mcp_tools,
+            commands::resolve_mcp_tool_name,
             commands::call_mcp_tool,
+            commands::call_mcp_tools_batch,
+            commands::stream_chat,
+            commands::run_agent,
+            commands::cancel_generation,
+            commands::list_chat_providers,
+            commands::add_chat_provider,
+            commands::update_chat_provider,
+            commands::delete_chat_provider,
+            commands::list_provider_models,
+            commands::start_inference_server,
+            commands::stop_inference_server,
+            commands::list_inference_servers,
             commands::get_mcp_server_info,
+            commands::get_mcp_server_process,
+            commands::get_mcp_server_diagnostics,
+            commands::get_mcp_server_status,
+            commands::get_mcp_server_trace,
+            commands::query_mcp_audit_log,
+            commands::export_mcp_audit_log,
+            commands::get_mcp_audit_retention,
+            commands::set_mcp_audit_retention,
+            commands::get_mcp_metrics,
+            commands::replay_mcp_trace,
+            commands::start_mcp_gateway,
+            commands::stop_mcp_gateway,
+            commands::get_mcp_gateway_status,
+            commands::list_mcp_server_configs,
+            commands::add_mcp_server_config,
+            commands::update_mcp_server_config,
+            commands::delete_mcp_server_config,
+            commands::import_mcp_server_configs,
+            commands::export_mcp_server_configs,
+            commands::list_mcp_secrets,
+            commands::set_mcp_secret,
+            commands::delete_mcp_secret,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            // Managed state isn't dropped on exit, so local models would outlive the app
+            if let tauri::RunEvent::Exit = event {
+                app.state::<InferenceServerManager>().stop_all();
+            }
+        });
+}
+
+/// Options for the `orion-mcp` stdio sidecar
+#[derive(Debug, Default)]
+pub struct SidecarOptions {
+    /// Directory with conversations, settings and the gateway's address
+    pub data_dir: Option<PathBuf>,
+    /// Forward to the running app's MCP gateway instead of serving history
+    pub gateway: bool,
+}
+
+/// Run Orion's MCP server on stdin/stdout
+///
+/// The data directory defaults to the one the desktop app uses. With `gateway`
+/// the app must be running with its gateway started, so clients share the app's
+/// servers rather than each starting their own.
+pub fn run_mcp_sidecar(options: SidecarOptions) -> anyhow::Result<()> {
+    let data_dir = match options.data_dir {
+        Some(dir) => dir,
+        None => default_data_dir()?,
+    };
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    if options.gateway {
+        let client = GatewayClient::connect(&data_dir)?;
+        return runtime.block_on(client.proxy_stdio());
+    }
+    let handler = Arc::new(HistoryHandler::new(data_dir));
+    runtime.block_on(mcp::serve::serve_stdio(handler))
+}
+
+/// Same location as Tauri's `app_data_dir`, resolved without a running app
+fn default_data_dir() -> anyhow::Result<PathBuf> {
+    let home = || std::env::var_os("HOME").map(PathBuf::from);
+    let base = if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library/Application Support"))
+    } else if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| home().map(|home| home.join(".local/share")))
+    };
+
+    base.map(|base| base.join(APP_IDENTIFIER)).ok_or_else(|| {
+        anyhow::anyhow!("Could not determine the app data directory; pass --data-dir")
+    })
 }
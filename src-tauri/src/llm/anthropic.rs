@@ -0,0 +1,431 @@
+use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use super::error::{send, ProviderError, ProviderErrorKind};
+use super::sse::SseDecoder;
+use super::stream::{
+    Delta, FunctionDelta, StreamAccumulator, StreamChoice, StreamEvent, ToolCallDelta,
+};
+use super::types::{
+    ChatMessage, ChatProvider, ChatRequest, ChatResponse, ChunkSink, ModelInfo, Role, Usage,
+};
+
+pub const ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com/v1";
+
+const API_VERSION: &str = "2023-06-01";
+
+const NAME: &str = "Anthropic";
+
+/// Longest answer asked for, since the Messages API requires a limit
+const DEFAULT_MAX_TOKENS: u32 = 8192;
+
+/// Client for Anthropic's native Messages API
+///
+/// Conversations are kept in the OpenAI format everywhere else, so they are
+/// converted on the way in and the stream is turned back into OpenAI deltas.
+pub struct AnthropicClient {
+    http: reqwest::Client,
+    base_url: String,
+    api_key: String,
+    max_tokens: u32,
+}
+
+#[derive(Deserialize)]
+struct ModelList {
+    data: Vec<ListedModel>,
+}
+
+#[derive(Deserialize)]
+struct ListedModel {
+    id: String,
+    display_name: Option<String>,
+}
+
+impl AnthropicClient {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: ANTHROPIC_BASE_URL.to_string(),
+            api_key: api_key.into(),
+            max_tokens: DEFAULT_MAX_TOKENS,
+        }
+    }
+
+    /// Send requests somewhere other than api.anthropic.com, e.g. a gateway or a
+    /// mock server in tests
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        self.http
+            .request(method, format!("{}/{}", self.base_url, path))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", API_VERSION)
+    }
+
+    fn body(&self, request: &ChatRequest) -> Value {
+        let (system, messages) = to_anthropic_messages(&request.messages);
+        let mut body = json!({
+            "model": request.model,
+            "messages": messages,
+            "max_tokens": self.max_tokens,
+            "stream": true,
+        });
+        if let Some(system) = system {
+            body["system"] = json!(system);
+        }
+        if !request.tools.is_empty() {
+            let tools: Vec<Value> = request
+                .tools
+                .iter()
+                .map(|tool| {
+                    json!({
+                        "name": tool.function.name,
+                        "description": tool.function.description,
+                        "input_schema": tool.function.parameters,
+                    })
+                })
+                .collect();
+            body["tools"] = json!(tools);
+        }
+        body
+    }
+
+    /// Stream a message, passing each chunk to `on_chunk` as it arrives
+    pub async fn stream(
+        &self,
+        request: &ChatRequest,
+        on_chunk: ChunkSink<'_>,
+    ) -> Result<ChatResponse> {
+        let response = send(
+            NAME,
+            self.request(reqwest::Method::POST, "messages")
+                .json(&self.body(request)),
+        )
+        .await?;
+
+        let mut bytes = response.bytes_stream();
+        let mut decoder = SseDecoder::default();
+        let mut stream = StreamAccumulator::default();
+        let mut events = EventConverter::default();
+
+        let mut finished = false;
+        while !finished {
+            let data = match bytes.next().await {
+                Some(Ok(chunk)) => decoder.push(&chunk),
+                Some(Err(e)) => return Err(ProviderError::network(NAME, e).into()),
+                None => {
+                    finished = true;
+                    decoder.finish().into_iter().collect()
+                }
+            };
+
+            for data in data {
+                let event: Value = match serde_json::from_str(&data) {
+                    Ok(event) => event,
+                    Err(e) => {
+                        eprintln!("Skipped unparseable {} event: {}: {}", NAME, e, data);
+                        continue;
+                    }
+                };
+                match event["type"].as_str() {
+                    Some("message_stop") => finished = true,
+                    Some("error") => return Err(stream_error(&event["error"]).into()),
+                    _ => {}
+                }
+                if let Some(event) = events.convert(&event) {
+                    for chunk in stream.apply(event)? {
+                        on_chunk(chunk);
+                    }
+                }
+            }
+        }
+
+        Ok(stream.finish())
+    }
+
+    pub async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        let response = send(NAME, self.request(reqwest::Method::GET, "models")).await?;
+        let models: ModelList = response
+            .json()
+            .await
+            .context("Failed to parse the Anthropic model list")?;
+        Ok(models
+            .data
+            .into_iter()
+            .map(|model| ModelInfo {
+                id: model.id,
+                name: model.display_name,
+            })
+            .collect())
+    }
+}
+
+impl ChatProvider for AnthropicClient {
+    fn stream<'a>(
+        &'a self,
+        request: &'a ChatRequest,
+        on_chunk: ChunkSink<'a>,
+    ) -> BoxFuture<'a, Result<ChatResponse>> {
+        Box::pin(AnthropicClient::stream(self, request, on_chunk))
+    }
+
+    fn list_models(&self) -> BoxFuture<'_, Result<Vec<ModelInfo>>> {
+        Box::pin(AnthropicClient::list_models(self))
+    }
+}
+
+/// Split off the system prompt and convert the rest to Messages API turns
+///
+/// Tool results become `tool_result` blocks of a user turn, and consecutive
+/// turns of the same role are merged, as the API expects.
+fn to_anthropic_messages(messages: &[ChatMessage]) -> (Option<String>, Vec<Value>) {
+    let mut system = Vec::new();
+    let mut turns: Vec<Value> = Vec::new();
+
+    for message in messages {
+        let text = message.content.as_deref().unwrap_or_default();
+        let text_block = (!text.is_empty()).then(|| json!({ "type": "text", "text": text }));
+
+        let (role, blocks) = match message.role {
+            Role::System => {
+                system.push(text);
+                continue;
+            }
+            Role::User => ("user", text_block.into_iter().collect()),
+            Role::Assistant => {
+                let mut blocks: Vec<Value> = text_block.into_iter().collect();
+                blocks.extend(message.tool_calls.iter().map(|call| {
+                    let input = serde_json::from_str::<Value>(&call.function.arguments)
+                        .ok()
+                        .filter(Value::is_object)
+                        .unwrap_or_else(|| json!({}));
+                    json!({
+                        "type": "tool_use",
+                        "id": call.id,
+                        "name": call.function.name,
+                        "input": input,
+                    })
+                }));
+                ("assistant", blocks)
+            }
+            Role::Tool => (
+                "user",
+                vec![json!({
+                    "type": "tool_result",
+                    "tool_use_id": message.tool_call_id,
+                    "content": text,
+                })],
+            ),
+        };
+        if blocks.is_empty() {
+            continue;
+        }
+
+        match turns.last_mut() {
+            Some(last) if last["role"] == role => {
+                if let Some(content) = last["content"].as_array_mut() {
+                    content.extend(blocks);
+                }
+            }
+            _ => turns.push(json!({ "role": role, "content": blocks })),
+        }
+    }
+
+    let system = system.join("\n\n");
+    ((!system.is_empty()).then_some(system), turns)
+}
+
+/// Turns Messages API stream events into OpenAI-style deltas
+#[derive(Default)]
+struct EventConverter {
+    input_tokens: u64,
+    /// Tool call index of each `tool_use` content block index
+    tool_calls: HashMap<u64, usize>,
+}
+
+impl EventConverter {
+    fn convert(&mut self, event: &Value) -> Option<StreamEvent> {
+        let block_index = event["index"].as_u64().unwrap_or_default();
+
+        match event["type"].as_str()? {
+            "message_start" => {
+                self.input_tokens = event["message"]["usage"]["input_tokens"]
+                    .as_u64()
+                    .unwrap_or_default();
+                None
+            }
+            "content_block_start" => {
+                let block = &event["content_block"];
+                match block["type"].as_str()? {
+                    "tool_use" => {
+                        let index = self.tool_calls.len();
+                        self.tool_calls.insert(block_index, index);
+                        Some(tool_call_delta(
+                            index,
+                            block["id"].as_str().map(str::to_string),
+                            block["name"].as_str().map(str::to_string),
+                            String::new(),
+                        ))
+                    }
+                    "text" => text_delta(block["text"].as_str()?),
+                    _ => None,
+                }
+            }
+            "content_block_delta" => {
+                let delta = &event["delta"];
+                match delta["type"].as_str()? {
+                    "text_delta" => text_delta(delta["text"].as_str()?),
+                    "thinking_delta" => Some(delta_event(Delta {
+                        reasoning: Some(delta["thinking"].as_str()?.to_string()),
+                        ..Delta::default()
+                    })),
+                    "input_json_delta" => Some(tool_call_delta(
+                        *self.tool_calls.get(&block_index)?,
+                        None,
+                        None,
+                        delta["partial_json"].as_str()?.to_string(),
+                    )),
+                    _ => None,
+                }
+            }
+            "message_delta" => {
+                let output_tokens = event["usage"]["output_tokens"].as_u64().unwrap_or_default();
+                let finish_reason = event["delta"]["stop_reason"].as_str().map(|reason| {
+                    match reason {
+                        "end_turn" | "stop_sequence" => "stop",
+                        "tool_use" => "tool_calls",
+                        "max_tokens" => "length",
+                        other => other,
+                    }
+                    .to_string()
+                });
+                Some(StreamEvent {
+                    choices: vec![StreamChoice {
+                        delta: Delta::default(),
+                        finish_reason,
+                    }],
+                    usage: Some(Usage {
+                        prompt_tokens: self.input_tokens,
+                        completion_tokens: output_tokens,
+                        total_tokens: self.input_tokens + output_tokens,
+                        cost: None,
+                    }),
+                    error: None,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+fn delta_event(delta: Delta) -> StreamEvent {
+    StreamEvent {
+        choices: vec![StreamChoice {
+            delta,
+            finish_reason: None,
+        }],
+        usage: None,
+        error: None,
+    }
+}
+
+fn text_delta(text: &str) -> Option<StreamEvent> {
+    (!text.is_empty()).then(|| {
+        delta_event(Delta {
+            content: Some(text.to_string()),
+            ..Delta::default()
+        })
+    })
+}
+
+fn tool_call_delta(
+    index: usize,
+    id: Option<String>,
+    name: Option<String>,
+    arguments: String,
+) -> StreamEvent {
+    delta_event(Delta {
+        tool_calls: vec![ToolCallDelta {
+            index,
+            id,
+            function: Some(FunctionDelta {
+                name,
+                arguments: Some(arguments),
+            }),
+        }],
+        ..Delta::default()
+    })
+}
+
+/// Error event sent in the middle of a stream, e.g. `overloaded_error`
+fn stream_error(error: &Value) -> ProviderError {
+    let kind = match error["type"].as_str() {
+        Some("authentication_error" | "permission_error") => ProviderErrorKind::Auth,
+        Some("rate_limit_error") => ProviderErrorKind::RateLimited,
+        Some("invalid_request_error" | "not_found_error" | "request_too_large") => {
+            ProviderErrorKind::InvalidRequest
+        }
+        _ => ProviderErrorKind::Server,
+    };
+    ProviderError {
+        kind,
+        message: error["message"]
+            .as_str()
+            .unwrap_or("Anthropic stream failed")
+            .to_string(),
+        status: None,
+        retry_after_ms: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{FunctionCall, ToolCall};
+
+    #[test]
+    fn converts_conversations_to_messages_api_turns() {
+        let call = |id: &str| ToolCall {
+            id: id.to_string(),
+            call_type: "function".to_string(),
+            function: FunctionCall {
+                name: "fs__read".to_string(),
+                arguments: r#"{"path":"/"}"#.to_string(),
+            },
+        };
+        let messages = vec![
+            ChatMessage {
+                role: Role::System,
+                ..ChatMessage::user("Be brief")
+            },
+            ChatMessage::user("Read /"),
+            ChatMessage {
+                tool_calls: vec![call("a"), call("b")],
+                ..ChatMessage::assistant("")
+            },
+            ChatMessage::tool("a", "one"),
+            ChatMessage::tool("b", "two"),
+            ChatMessage::user("Thanks"),
+        ];
+
+        let (system, turns) = to_anthropic_messages(&messages);
+        assert_eq!(system.as_deref(), Some("Be brief"));
+        assert_eq!(turns.len(), 3);
+        assert_eq!(turns[1]["content"][0]["type"], "tool_use");
+        assert_eq!(turns[1]["content"][1]["input"]["path"], "/");
+
+        // Both results and the next user message share one user turn
+        let results = turns[2]["content"].as_array().unwrap();
+        assert_eq!(turns[2]["role"], "user");
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[1]["tool_use_id"], "b");
+        assert_eq!(results[2]["text"], "Thanks");
+    }
+}
@@ -0,0 +1,162 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use std::time::SystemTime;
+
+/// What went wrong with a provider request, so callers can react without parsing messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProviderErrorKind {
+    /// Missing, invalid or revoked API key
+    Auth,
+    /// The account is out of credits
+    InsufficientCredits,
+    RateLimited,
+    /// The request was rejected, e.g. an unknown model or too long a context
+    InvalidRequest,
+    /// The provider or the model behind it failed
+    Server,
+    /// The provider could not be reached or the connection dropped
+    Network,
+}
+
+/// A chat completion request failed
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderError {
+    pub kind: ProviderErrorKind,
+    pub message: String,
+    /// HTTP status, or the error code the provider sent mid-stream
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// How long the provider asked us to wait before retrying
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_ms: Option<u64>,
+}
+
+impl ProviderError {
+    /// Error for a failed response, using the message from its `{"error": {...}}` or
+    /// `{"error": "..."}` body if any
+    pub fn from_status(
+        provider: &str,
+        status: u16,
+        body: Option<&Value>,
+        retry_after_ms: Option<u64>,
+    ) -> Self {
+        let kind = match status {
+            401 | 403 => ProviderErrorKind::Auth,
+            402 => ProviderErrorKind::InsufficientCredits,
+            429 => ProviderErrorKind::RateLimited,
+            400..=499 => ProviderErrorKind::InvalidRequest,
+            _ => ProviderErrorKind::Server,
+        };
+        let message = body
+            .and_then(|body| {
+                body["error"]["message"]
+                    .as_str()
+                    .or_else(|| body["error"].as_str())
+            })
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{} API error: {}", provider, status));
+
+        Self {
+            kind,
+            message,
+            status: Some(status),
+            retry_after_ms,
+        }
+    }
+
+    pub fn network(provider: &str, error: impl fmt::Display) -> Self {
+        Self {
+            kind: ProviderErrorKind::Network,
+            message: format!("Failed to reach {}: {}", provider, error),
+            status: None,
+            retry_after_ms: None,
+        }
+    }
+
+    /// Whether the same request may succeed if sent again later
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self.kind,
+            ProviderErrorKind::RateLimited | ProviderErrorKind::Server | ProviderErrorKind::Network
+        )
+    }
+}
+
+impl fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ProviderError {}
+
+/// Send a request, turning failed responses into a [`ProviderError`]
+pub async fn send(
+    provider: &str,
+    request: reqwest::RequestBuilder,
+) -> anyhow::Result<reqwest::Response> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| ProviderError::network(provider, e))?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let retry_after_ms = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_retry_after);
+    let body = response.json::<Value>().await.ok();
+    Err(ProviderError::from_status(provider, status.as_u16(), body.as_ref(), retry_after_ms).into())
+}
+
+/// Milliseconds from a `Retry-After` header, given in seconds or as an HTTP date
+///
+/// A date that has already passed means the request may be retried right away.
+pub fn parse_retry_after(value: &str) -> Option<u64> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<f64>() {
+        return (seconds.is_finite() && seconds >= 0.0).then_some((seconds * 1000.0) as u64);
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    let wait = date.duration_since(SystemTime::now()).unwrap_or_default();
+    Some(wait.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::time::Duration;
+
+    #[test]
+    fn maps_statuses_to_kinds() {
+        let body = json!({ "error": { "code": 402, "message": "Insufficient credits" } });
+        let error = ProviderError::from_status("OpenRouter", 402, Some(&body), None);
+        assert_eq!(error.kind, ProviderErrorKind::InsufficientCredits);
+        assert_eq!(error.message, "Insufficient credits");
+        assert!(!error.is_retryable());
+
+        let error = ProviderError::from_status("OpenRouter", 429, None, parse_retry_after("1.5"));
+        assert_eq!(error.kind, ProviderErrorKind::RateLimited);
+        assert_eq!(error.retry_after_ms, Some(1500));
+        assert_eq!(error.message, "OpenRouter API error: 429");
+        assert!(error.is_retryable());
+
+        assert_eq!(
+            ProviderError::from_status("OpenRouter", 503, None, None).kind,
+            ProviderErrorKind::Server
+        );
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(0));
+        let date = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(30));
+        let wait = parse_retry_after(&date).unwrap();
+        assert!((28_000..=30_000).contains(&wait), "{}", wait);
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::Result;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::stream::ChatChunk;
+use super::types::{ChatProvider, ChatRequest, ChatResponse, ChunkSink, ModelInfo};
+
+/// A model and the provider to ask for it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelChoice {
+    /// OpenRouter with the key from the settings when not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_id: Option<String>,
+    pub model: String,
+}
+
+/// Models to try in order until one answers
+///
+/// A model is skipped when its request fails before streaming anything,
+/// including after its provider's retries ran out. Each attempt starts with a
+/// [`ChatChunk::Model`] chunk, and the response names the model that answered.
+pub struct FallbackChain {
+    models: Vec<(String, Box<dyn ChatProvider>)>,
+}
+
+impl FallbackChain {
+    /// Chain starting with `model` on `provider`
+    pub fn new(model: impl Into<String>, provider: Box<dyn ChatProvider>) -> Self {
+        Self {
+            models: vec![(model.into(), provider)],
+        }
+    }
+
+    /// Try `model` on `provider` when every model so far failed
+    pub fn or_else(mut self, model: impl Into<String>, provider: Box<dyn ChatProvider>) -> Self {
+        self.models.push((model.into(), provider));
+        self
+    }
+
+    async fn stream(&self, request: &ChatRequest, on_chunk: ChunkSink<'_>) -> Result<ChatResponse> {
+        let started = AtomicBool::new(false);
+        let forward = |chunk: ChatChunk| {
+            started.store(true, Ordering::Relaxed);
+            on_chunk(chunk);
+        };
+
+        for (index, (model, provider)) in self.models.iter().enumerate() {
+            let request = ChatRequest {
+                model: model.clone(),
+                ..request.clone()
+            };
+            on_chunk(ChatChunk::Model {
+                model: model.clone(),
+            });
+            match provider.stream(&request, &forward).await {
+                Ok(response) => {
+                    return Ok(ChatResponse {
+                        model: Some(model.clone()),
+                        ..response
+                    })
+                }
+                Err(error) if started.load(Ordering::Relaxed) => return Err(error),
+                Err(error) => match self.models.get(index + 1) {
+                    Some((next, _)) => {
+                        eprintln!("'{}' failed, falling back to '{}': {}", model, next, error)
+                    }
+                    None => return Err(error),
+                },
+            }
+        }
+        unreachable!("a fallback chain has at least one model")
+    }
+}
+
+impl ChatProvider for FallbackChain {
+    fn stream<'a>(
+        &'a self,
+        request: &'a ChatRequest,
+        on_chunk: ChunkSink<'a>,
+    ) -> BoxFuture<'a, Result<ChatResponse>> {
+        Box::pin(FallbackChain::stream(self, request, on_chunk))
+    }
+
+    /// Models of the first provider in the chain
+    fn list_models(&self) -> BoxFuture<'_, Result<Vec<ModelInfo>>> {
+        self.models[0].1.list_models()
+    }
+}
@@ -0,0 +1,128 @@
+use anyhow::Result;
+use futures::future::BoxFuture;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::error::{ProviderError, ProviderErrorKind};
+use super::ollama::{OllamaClient, OLLAMA_BASE_URL};
+use super::openai::OpenAiClient;
+use super::stream::ChatChunk;
+use super::types::{ChatProvider, ChatRequest, ChatResponse, ChunkSink, ModelInfo};
+
+/// Default address of llama.cpp's `llama-server`
+pub const LLAMA_CPP_BASE_URL: &str = "http://localhost:8080/v1";
+
+/// A model server running on this machine
+///
+/// Wraps the client for its API to explain a refused connection as the server
+/// not running, and to retry without tools when the model can't call them.
+pub struct LocalProvider {
+    client: Box<dyn ChatProvider>,
+    name: &'static str,
+    base_url: String,
+    /// How to start the server, shown when it isn't running
+    start_hint: &'static str,
+}
+
+impl LocalProvider {
+    pub fn ollama(base_url: Option<String>) -> Self {
+        let base_url = base_url.unwrap_or_else(|| OLLAMA_BASE_URL.to_string());
+        Self {
+            client: Box::new(OllamaClient::new(&base_url)),
+            name: "Ollama",
+            base_url,
+            start_hint: "Start it with `ollama serve` or by opening the Ollama app.",
+        }
+    }
+
+    pub fn llama_cpp(base_url: Option<String>) -> Self {
+        let base_url = base_url.unwrap_or_else(|| LLAMA_CPP_BASE_URL.to_string());
+        Self {
+            client: Box::new(OpenAiClient::compatible("llama.cpp", &base_url, None)),
+            name: "llama.cpp",
+            base_url,
+            start_hint: "Start it with `llama-server -m <model.gguf> --jinja`.",
+        }
+    }
+
+    /// Replace a connection failure with instructions to start the server
+    fn explain(&self, error: anyhow::Error) -> anyhow::Error {
+        match error.downcast::<ProviderError>() {
+            Ok(error) if error.kind == ProviderErrorKind::Network => ProviderError {
+                message: format!(
+                    "{} is not running at {}. {}",
+                    self.name, self.base_url, self.start_hint
+                ),
+                ..error
+            }
+            .into(),
+            Ok(error) => error.into(),
+            Err(error) => error,
+        }
+    }
+
+    async fn stream(&self, request: &ChatRequest, on_chunk: ChunkSink<'_>) -> Result<ChatResponse> {
+        // A connection dropped mid-answer is not a server that isn't running
+        let started = AtomicBool::new(false);
+        let forward = |chunk: ChatChunk| {
+            started.store(true, Ordering::Relaxed);
+            on_chunk(chunk);
+        };
+
+        let result = match self.client.stream(request, &forward).await {
+            Err(error) if !request.tools.is_empty() && rejects_tools(&error) => {
+                eprintln!(
+                    "{} model '{}' can't call tools, answering without them",
+                    self.name, request.model
+                );
+                let request = ChatRequest {
+                    tools: Vec::new(),
+                    ..request.clone()
+                };
+                self.client.stream(&request, &forward).await
+            }
+            result => result,
+        };
+        result.map_err(|error| {
+            if started.load(Ordering::Relaxed) {
+                error
+            } else {
+                self.explain(error)
+            }
+        })
+    }
+}
+
+impl ChatProvider for LocalProvider {
+    fn stream<'a>(
+        &'a self,
+        request: &'a ChatRequest,
+        on_chunk: ChunkSink<'a>,
+    ) -> BoxFuture<'a, Result<ChatResponse>> {
+        Box::pin(LocalProvider::stream(self, request, on_chunk))
+    }
+
+    fn list_models(&self) -> BoxFuture<'_, Result<Vec<ModelInfo>>> {
+        Box::pin(async move {
+            self.client
+                .list_models()
+                .await
+                .map_err(|error| self.explain(error))
+        })
+    }
+}
+
+/// Whether a request failed because the model or server doesn't support tools
+///
+/// Ollama answers "<model> does not support tools" and llama.cpp started without
+/// `--jinja` answers "tools param requires --jinja flag".
+fn rejects_tools(error: &anyhow::Error) -> bool {
+    let Some(error) = error.downcast_ref::<ProviderError>() else {
+        return false;
+    };
+    let message = error.message.to_lowercase();
+    matches!(
+        error.kind,
+        ProviderErrorKind::InvalidRequest | ProviderErrorKind::Server
+    ) && message.contains("tool")
+        && (message.contains("support") || message.contains("jinja"))
+}
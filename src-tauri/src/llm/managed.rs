@@ -0,0 +1,617 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::time::{Duration, Instant};
+
+use super::provider::{ProviderConfig, ProviderKind};
+use crate::mcp::process::{self, ProcessInfo};
+use crate::mcp::transport::TransportLog;
+use crate::mcp::{ServerDiagnostics, DEFAULT_INHERITED_ENV};
+
+/// How long a server may take to load its model before startup is abandoned
+pub const DEFAULT_STARTUP_TIMEOUT_MS: u64 = 120_000;
+
+/// Pause between health checks while a server starts
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Variables choosing GPUs and finding their drivers, inherited besides the defaults
+const GPU_ENV: &[&str] = &[
+    "CUDA_VISIBLE_DEVICES",
+    "CUDA_DEVICE_ORDER",
+    "HIP_VISIBLE_DEVICES",
+    "ROCR_VISIBLE_DEVICES",
+    "GPU_DEVICE_ORDINAL",
+    "HSA_OVERRIDE_GFX_VERSION",
+    "GGML_VK_VISIBLE_DEVICES",
+    "LD_LIBRARY_PATH",
+    "DYLD_LIBRARY_PATH",
+];
+
+/// Local inference server that Orion launches itself for a provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManagedServer {
+    /// Binary to run; `llama-server` or `ollama` from PATH by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// GGUF file for llama.cpp, or the models directory for Ollama
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_path: Option<String>,
+    /// Defaults to the engine's usual port, 8080 or 11434
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// Context window in tokens; the model's default if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_size: Option<u32>,
+    /// CPU threads for llama.cpp; picked by the server if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threads: Option<u32>,
+    /// Further command line arguments
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Environment variables for the server, e.g. Ollama settings
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    /// Start with the app
+    #[serde(default)]
+    pub autostart: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub startup_timeout_ms: Option<u64>,
+}
+
+impl ManagedServer {
+    pub fn port(&self, kind: ProviderKind) -> u16 {
+        self.port.unwrap_or(match kind {
+            ProviderKind::Ollama => 11434,
+            _ => 8080,
+        })
+    }
+
+    /// Address of the server's chat API, as the provider client expects it
+    pub fn base_url(&self, kind: ProviderKind) -> String {
+        match kind {
+            ProviderKind::Ollama => format!("http://127.0.0.1:{}", self.port(kind)),
+            _ => format!("http://127.0.0.1:{}/v1", self.port(kind)),
+        }
+    }
+
+    /// Endpoint answering 200 once the server can take requests
+    ///
+    /// llama.cpp answers 503 on `/health` until the model is loaded.
+    fn health_url(&self, kind: ProviderKind) -> String {
+        match kind {
+            ProviderKind::Ollama => format!("http://127.0.0.1:{}/api/version", self.port(kind)),
+            _ => format!("http://127.0.0.1:{}/health", self.port(kind)),
+        }
+    }
+
+    fn default_command(kind: ProviderKind) -> &'static str {
+        match kind {
+            ProviderKind::Ollama => "ollama",
+            _ => "llama-server",
+        }
+    }
+
+    /// Validate the launch settings of a provider of the given kind
+    pub fn validate(&self, kind: ProviderKind, name: &str) -> Result<()> {
+        if !kind.is_local() {
+            return Err(anyhow!(
+                "Provider '{}' is not a local server Orion can launch",
+                name
+            ));
+        }
+        match self.model_path.as_deref() {
+            Some(path) if !Path::new(path).is_absolute() => {
+                return Err(anyhow!(
+                    "Provider '{}' model path must be an absolute path",
+                    name
+                ));
+            }
+            None if kind == ProviderKind::LlamaCpp => {
+                return Err(anyhow!("Provider '{}' must have a model path", name));
+            }
+            _ => {}
+        }
+        if self.threads.is_some() && kind == ProviderKind::Ollama {
+            return Err(anyhow!(
+                "Provider '{}' can't set threads; Ollama picks them per model",
+                name
+            ));
+        }
+        if self.context_size == Some(0) || self.threads == Some(0) || self.port == Some(0) {
+            return Err(anyhow!(
+                "Provider '{}' context size, threads and port must be positive",
+                name
+            ));
+        }
+        Ok(())
+    }
+
+    /// Command line for the server, listening on loopback only
+    ///
+    /// The server doesn't inherit Orion's environment, which may hold provider
+    /// keys; only the usual MCP defaults, GPU variables and `env` are set.
+    async fn command(&self, kind: ProviderKind) -> Result<(Command, ProcessInfo)> {
+        let path = process::search_path(&[]).await?;
+        let command = self
+            .command
+            .as_deref()
+            .unwrap_or(Self::default_command(kind));
+        let executable = process::find_executable(command, &path, None)?;
+
+        let mut cmd = Command::new(&executable);
+        cmd.env_clear();
+        for name in DEFAULT_INHERITED_ENV.iter().chain(GPU_ENV) {
+            if let Some(value) = std::env::var_os(name) {
+                cmd.env(name, value);
+            }
+        }
+        cmd.env("PATH", path).envs(&self.env);
+        let port = self.port(kind);
+        match kind {
+            ProviderKind::Ollama => {
+                cmd.arg("serve")
+                    .env("OLLAMA_HOST", format!("127.0.0.1:{}", port));
+                if let Some(models) = &self.model_path {
+                    cmd.env("OLLAMA_MODELS", models);
+                }
+                if let Some(context_size) = self.context_size {
+                    cmd.env("OLLAMA_CONTEXT_LENGTH", context_size.to_string());
+                }
+            }
+            _ => {
+                let model = self.model_path.as_deref().unwrap_or_default();
+                if !Path::new(model).is_file() {
+                    return Err(anyhow!("Model file '{}' does not exist", model));
+                }
+                // --jinja enables the chat templates that tool calling needs
+                cmd.args(["-m", model, "--host", "127.0.0.1", "--jinja"])
+                    .args(["--port", &port.to_string()]);
+                if let Some(context_size) = self.context_size {
+                    cmd.args(["-c", &context_size.to_string()]);
+                }
+                if let Some(threads) = self.threads {
+                    cmd.args(["-t", &threads.to_string()]);
+                }
+            }
+        }
+        cmd.args(&self.args);
+
+        let info = ProcessInfo {
+            executable: executable.display().to_string(),
+            pid: None,
+            cwd: None,
+        };
+        Ok((cmd, info))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InferenceServerState {
+    /// Launched and loading its model
+    Starting,
+    Ready,
+    /// The process ended on its own
+    Exited,
+}
+
+/// State of a managed server, with the tail of its log
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InferenceServerStatus {
+    pub provider_id: String,
+    pub state: InferenceServerState,
+    pub base_url: String,
+    pub process: ProcessInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    pub diagnostics: ServerDiagnostics,
+}
+
+struct RunningServer {
+    child: Child,
+    status: InferenceServerStatus,
+    log: TransportLog,
+}
+
+impl RunningServer {
+    /// Refresh the state from the process, returning the current status
+    fn poll(&mut self) -> InferenceServerStatus {
+        if self.status.state != InferenceServerState::Exited {
+            if let Ok(Some(exit)) = self.child.try_wait() {
+                self.status.state = InferenceServerState::Exited;
+                self.status.exit_code = exit.code();
+            }
+        }
+        InferenceServerStatus {
+            diagnostics: self.log.snapshot(),
+            ..self.status.clone()
+        }
+    }
+}
+
+/// Entry of a provider in the manager
+enum ServerSlot {
+    /// Reserved by the numbered launch spawning the process, so a second start fails
+    Launching(u64),
+    Running(Box<RunningServer>),
+}
+
+impl ServerSlot {
+    fn running(&mut self) -> Option<&mut RunningServer> {
+        match self {
+            Self::Launching(_) => None,
+            Self::Running(server) => Some(server.as_mut()),
+        }
+    }
+}
+
+/// Launches, health-checks and stops the local inference servers of providers
+/// configured with a [`ManagedServer`]
+///
+/// Processes are killed when stopped or by [`Self::stop_all`], which the app
+/// calls on exit since Tauri doesn't drop its managed state.
+#[derive(Default)]
+pub struct InferenceServerManager {
+    servers: Mutex<HashMap<String, ServerSlot>>,
+    launches: AtomicU64,
+}
+
+impl InferenceServerManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Launch a provider's server and wait until it answers its health check
+    pub async fn start(&self, config: &ProviderConfig) -> Result<InferenceServerStatus> {
+        let managed = config
+            .managed
+            .as_ref()
+            .ok_or_else(|| anyhow!("Provider '{}' is not a managed server", config.name))?;
+        managed.validate(config.kind, &config.name)?;
+
+        let launch = self.launches.fetch_add(1, Ordering::Relaxed);
+        {
+            let mut servers = self.servers.lock().map_err(|e| anyhow!(e.to_string()))?;
+            let state = servers
+                .get_mut(&config.id)
+                .map(|slot| slot.running().map(|server| server.poll().state));
+            match state {
+                Some(Some(InferenceServerState::Exited)) | None => {
+                    servers.insert(config.id.clone(), ServerSlot::Launching(launch));
+                }
+                Some(_) => {
+                    return Err(anyhow!("Server for '{}' is already running", config.name));
+                }
+            }
+        }
+
+        let launched = self.launch(config, managed).await;
+        {
+            let mut servers = self.servers.lock().map_err(|e| anyhow!(e.to_string()))?;
+            let reserved = matches!(
+                servers.get(&config.id),
+                Some(ServerSlot::Launching(id)) if *id == launch
+            );
+            match launched {
+                Ok(server) if reserved => {
+                    servers.insert(config.id.clone(), ServerSlot::Running(Box::new(server)));
+                }
+                // Stopped while launching; dropping the server kills it
+                Ok(_) => {
+                    return Err(anyhow!("Server for '{}' was stopped", config.name));
+                }
+                Err(e) => {
+                    if reserved {
+                        servers.remove(&config.id);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        match self.wait_until_ready(config, managed).await {
+            Ok(()) => self.status(&config.id),
+            Err(e) => {
+                // Give the log a moment to catch the last lines, as for MCP servers
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                let stderr = self
+                    .status(&config.id)
+                    .map(|status| status.diagnostics.stderr)
+                    .unwrap_or_default();
+                let _ = self.stop(&config.id).await;
+                match stderr.last() {
+                    Some(line) => Err(anyhow!("{}: {}", e, line)),
+                    None => Err(e),
+                }
+            }
+        }
+    }
+
+    /// Spawn a provider's server process
+    async fn launch(
+        &self,
+        config: &ProviderConfig,
+        managed: &ManagedServer,
+    ) -> Result<RunningServer> {
+        // A server already on the port would pass the health check in our place
+        let port = managed.port(config.kind);
+        if std::net::TcpListener::bind(("127.0.0.1", port)).is_err() {
+            return Err(anyhow!(
+                "Port {} is already in use; is another server running?",
+                port
+            ));
+        }
+
+        let (mut cmd, mut process) = managed.command(config.kind).await?;
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| anyhow!("Failed to spawn '{}': {}", process.executable, e))?;
+        process.pid = child.id();
+
+        // Servers log model loading and errors to stderr
+        let log = TransportLog::default();
+        if let Some(stderr) = child.stderr.take() {
+            let log = log.clone();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    log.push_stderr(line);
+                }
+            });
+        }
+
+        let status = InferenceServerStatus {
+            provider_id: config.id.clone(),
+            state: InferenceServerState::Starting,
+            base_url: managed.base_url(config.kind),
+            process,
+            exit_code: None,
+            diagnostics: ServerDiagnostics {
+                diagnostics: Vec::new(),
+                stderr: Vec::new(),
+            },
+        };
+        Ok(RunningServer { child, status, log })
+    }
+
+    async fn wait_until_ready(
+        &self,
+        config: &ProviderConfig,
+        managed: &ManagedServer,
+    ) -> Result<()> {
+        let timeout_ms = managed
+            .startup_timeout_ms
+            .unwrap_or(DEFAULT_STARTUP_TIMEOUT_MS);
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+        let health_url = managed.health_url(config.kind);
+        let http = reqwest::Client::new();
+
+        loop {
+            let status = self.status(&config.id)?;
+            if status.state == InferenceServerState::Exited {
+                return Err(anyhow!(
+                    "Server for '{}' exited during startup with code {}",
+                    config.name,
+                    status
+                        .exit_code
+                        .map_or("unknown".to_string(), |code| code.to_string())
+                ));
+            }
+
+            let healthy = http
+                .get(&health_url)
+                .timeout(Duration::from_secs(2))
+                .send()
+                .await
+                .is_ok_and(|response| response.status().is_success());
+            if healthy {
+                if let Some(server) = self
+                    .servers
+                    .lock()
+                    .map_err(|e| anyhow!(e.to_string()))?
+                    .get_mut(&config.id)
+                    .and_then(ServerSlot::running)
+                {
+                    server.status.state = InferenceServerState::Ready;
+                }
+                return Ok(());
+            }
+
+            if Instant::now() >= deadline {
+                return Err(anyhow!(
+                    "Server for '{}' was not ready after {}ms",
+                    config.name,
+                    timeout_ms
+                ));
+            }
+            tokio::time::sleep(HEALTH_CHECK_INTERVAL).await;
+        }
+    }
+
+    /// Kill a provider's server
+    pub async fn stop(&self, provider_id: &str) -> Result<()> {
+        let server = self
+            .servers
+            .lock()
+            .map_err(|e| anyhow!(e.to_string()))?
+            .remove(provider_id);
+        match server {
+            Some(ServerSlot::Running(mut server)) => {
+                let _ = server.child.kill().await;
+                Ok(())
+            }
+            // The launch notices and kills the process it spawns
+            Some(ServerSlot::Launching(_)) => Ok(()),
+            None => Err(anyhow!("No server running for provider '{}'", provider_id)),
+        }
+    }
+
+    /// Kill every server without waiting for them to exit, e.g. when the app quits
+    pub fn stop_all(&self) {
+        let Ok(mut servers) = self.servers.lock() else {
+            return;
+        };
+        for (_, slot) in servers.drain() {
+            if let ServerSlot::Running(mut server) = slot {
+                let _ = server.child.start_kill();
+            }
+        }
+    }
+
+    pub fn status(&self, provider_id: &str) -> Result<InferenceServerStatus> {
+        self.servers
+            .lock()
+            .map_err(|e| anyhow!(e.to_string()))?
+            .get_mut(provider_id)
+            .and_then(ServerSlot::running)
+            .map(RunningServer::poll)
+            .ok_or_else(|| anyhow!("No server running for provider '{}'", provider_id))
+    }
+
+    /// Status of every server launched and not stopped since
+    pub fn list(&self) -> Result<Vec<InferenceServerStatus>> {
+        let mut servers = self.servers.lock().map_err(|e| anyhow!(e.to_string()))?;
+        Ok(servers
+            .values_mut()
+            .filter_map(ServerSlot::running)
+            .map(RunningServer::poll)
+            .collect())
+    }
+
+    /// Fail unless a managed provider's server can take requests
+    pub fn ensure_ready(&self, config: &ProviderConfig) -> Result<()> {
+        let state = self.status(&config.id).map(|status| status.state);
+        match state {
+            Ok(InferenceServerState::Ready) => Ok(()),
+            Ok(InferenceServerState::Starting) => Err(anyhow!(
+                "The server for '{}' is still loading its model",
+                config.name
+            )),
+            _ => Err(anyhow!(
+                "The server for '{}' is not running; start it from the provider settings",
+                config.name
+            )),
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[tokio::test]
+    async fn reports_servers_that_exit_during_startup() {
+        let dir = std::env::temp_dir().join(format!("orion-managed-{}", std::process::id()));
+        let config = fake_server(
+            &dir,
+            "#!/bin/sh\necho \"error: failed to load model\" >&2\nexit 1\n",
+        );
+
+        let manager = InferenceServerManager::new();
+        let error = manager.start(&config).await.unwrap_err().to_string();
+        assert!(error.contains("exited during startup"), "{}", error);
+        assert!(error.contains("failed to load model"), "{}", error);
+        assert!(manager.list().unwrap().is_empty());
+        assert!(manager.ensure_ready(&config).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn launches_one_server_when_started_twice_at_once() {
+        let dir = std::env::temp_dir().join(format!("orion-managed-twice-{}", std::process::id()));
+        let config = fake_server(&dir, "#!/bin/sh\nsleep 5\n");
+
+        let manager = InferenceServerManager::new();
+        let (first, second) = tokio::join!(manager.start(&config), manager.start(&config));
+        let errors = [first.unwrap_err(), second.unwrap_err()].map(|e| e.to_string());
+        assert_eq!(
+            errors
+                .iter()
+                .filter(|e| e.contains("is already running"))
+                .count(),
+            1,
+            "{:?}",
+            errors
+        );
+        assert!(manager.list().unwrap().is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn keeps_orion_environment_from_the_server() {
+        let dir = std::env::temp_dir().join(format!("orion-managed-env-{}", std::process::id()));
+        let env_file = dir.join("env.txt");
+        let mut config = fake_server(
+            &dir,
+            &format!("#!/bin/sh\nenv > '{}'\nexit 1\n", env_file.display()),
+        );
+        let managed = config.managed.as_mut().unwrap();
+        managed
+            .env
+            .insert("OLLAMA_KEEP_ALIVE".to_string(), "5m".to_string());
+        std::env::set_var("ORION_MANAGED_TEST_API_KEY", "sk-test");
+
+        let manager = InferenceServerManager::new();
+        assert!(manager.start(&config).await.is_err());
+        let env = std::fs::read_to_string(&env_file).unwrap();
+        assert!(
+            env.lines().any(|line| line == "OLLAMA_KEEP_ALIVE=5m"),
+            "{}",
+            env
+        );
+        assert!(env.lines().any(|line| line.starts_with("PATH=")), "{}", env);
+        assert!(!env.contains("ORION_MANAGED_TEST_API_KEY"), "{}", env);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// Provider whose llama-server is the given shell script
+    fn fake_server(dir: &Path, script: &str) -> ProviderConfig {
+        std::fs::create_dir_all(dir).unwrap();
+        let model = dir.join("model.gguf");
+        std::fs::write(&model, b"").unwrap();
+        let server = dir.join("llama-server");
+        std::fs::write(&server, script).unwrap();
+        std::fs::set_permissions(&server, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        ProviderConfig {
+            id: "local".to_string(),
+            name: "Local".to_string(),
+            kind: ProviderKind::LlamaCpp,
+            base_url: None,
+            api_key: None,
+            models: Vec::new(),
+            managed: Some(ManagedServer {
+                command: Some(server.display().to_string()),
+                model_path: Some(model.display().to_string()),
+                port: Some(port),
+                context_size: Some(4096),
+                threads: Some(2),
+                args: Vec::new(),
+                env: HashMap::new(),
+                autostart: false,
+                startup_timeout_ms: Some(1_000),
+            }),
+            limits: Default::default(),
+        }
+    }
+}
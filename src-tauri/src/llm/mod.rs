@@ -0,0 +1,26 @@
+pub mod anthropic;
+pub mod error;
+pub mod fallback;
+pub mod local;
+pub mod managed;
+pub mod ollama;
+pub mod openai;
+pub mod provider;
+pub mod runs;
+pub mod scheduler;
+pub mod sse;
+pub mod stream;
+pub mod types;
+
+pub use anthropic::AnthropicClient;
+pub use error::{ProviderError, ProviderErrorKind};
+pub use fallback::{FallbackChain, ModelChoice};
+pub use local::LocalProvider;
+pub use managed::{InferenceServerManager, InferenceServerStatus, ManagedServer};
+pub use ollama::OllamaClient;
+pub use openai::OpenAiClient;
+pub use provider::{ProviderConfig, ProviderKind, ProviderStore};
+pub use runs::{GenerationRuns, RunHandle};
+pub use scheduler::{RequestLimits, RequestScheduler, RetryPolicy};
+pub use stream::ChatChunk;
+pub use types::*;
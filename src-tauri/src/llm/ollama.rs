@@ -0,0 +1,298 @@
+use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use super::error::{send, ProviderError, ProviderErrorKind};
+use super::stream::{
+    Delta, FunctionDelta, StreamAccumulator, StreamChoice, StreamEvent, ToolCallDelta,
+};
+use super::types::{
+    ChatMessage, ChatProvider, ChatRequest, ChatResponse, ChunkSink, ModelInfo, Role, Usage,
+};
+
+pub const OLLAMA_BASE_URL: &str = "http://localhost:11434";
+
+const NAME: &str = "Ollama";
+
+/// Client for Ollama's native `/api/chat`, which streams one JSON object per line
+pub struct OllamaClient {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+/// Line of a streamed `/api/chat` response
+#[derive(Deserialize)]
+struct ChatLine {
+    #[serde(default)]
+    message: Option<LineMessage>,
+    #[serde(default)]
+    done: bool,
+    done_reason: Option<String>,
+    prompt_eval_count: Option<u64>,
+    eval_count: Option<u64>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LineMessage {
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    thinking: String,
+    #[serde(default)]
+    tool_calls: Vec<LineToolCall>,
+}
+
+/// Ollama sends each tool call whole, with the arguments as an object
+#[derive(Deserialize)]
+struct LineToolCall {
+    function: LineFunction,
+}
+
+#[derive(Deserialize)]
+struct LineFunction {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+#[derive(Deserialize)]
+struct TagList {
+    models: Vec<Tag>,
+}
+
+#[derive(Deserialize)]
+struct Tag {
+    name: String,
+}
+
+impl OllamaClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn body(&self, request: &ChatRequest) -> Value {
+        let mut body = json!({
+            "model": request.model,
+            "messages": to_ollama_messages(&request.messages),
+            "stream": true,
+        });
+        if !request.tools.is_empty() {
+            body["tools"] = json!(request.tools);
+        }
+        body
+    }
+
+    /// Stream a chat, passing each chunk to `on_chunk` as it arrives
+    pub async fn stream(
+        &self,
+        request: &ChatRequest,
+        on_chunk: ChunkSink<'_>,
+    ) -> Result<ChatResponse> {
+        let response = send(
+            NAME,
+            self.http
+                .post(format!("{}/api/chat", self.base_url))
+                .json(&self.body(request)),
+        )
+        .await?;
+
+        let mut bytes = response.bytes_stream();
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut stream = StreamAccumulator::default();
+        let mut tool_calls = 0;
+
+        let mut finished = false;
+        while !finished {
+            let lines: Vec<Vec<u8>> = match bytes.next().await {
+                Some(Ok(chunk)) => {
+                    buffer.extend_from_slice(&chunk);
+                    let mut lines = Vec::new();
+                    while let Some(end) = buffer.iter().position(|byte| *byte == b'\n') {
+                        lines.push(buffer.drain(..=end).collect());
+                    }
+                    lines
+                }
+                Some(Err(e)) => return Err(ProviderError::network(NAME, e).into()),
+                None => {
+                    finished = true;
+                    vec![std::mem::take(&mut buffer)]
+                }
+            };
+
+            for line in lines {
+                let line = String::from_utf8_lossy(&line);
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+                let line: ChatLine = match serde_json::from_str(line) {
+                    Ok(line) => line,
+                    Err(e) => {
+                        eprintln!("Skipped unparseable {} line: {}: {}", NAME, e, line);
+                        continue;
+                    }
+                };
+                if let Some(message) = line.error {
+                    return Err(ProviderError {
+                        kind: ProviderErrorKind::Server,
+                        message,
+                        status: None,
+                        retry_after_ms: None,
+                    }
+                    .into());
+                }
+                finished |= line.done;
+                for event in to_stream_events(line, &mut tool_calls) {
+                    for chunk in stream.apply(event)? {
+                        on_chunk(chunk);
+                    }
+                }
+            }
+        }
+
+        Ok(stream.finish())
+    }
+
+    /// Models pulled into the local Ollama library
+    pub async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        let response = send(NAME, self.http.get(format!("{}/api/tags", self.base_url))).await?;
+        let tags: TagList = response
+            .json()
+            .await
+            .context("Failed to parse the Ollama model list")?;
+        Ok(tags
+            .models
+            .into_iter()
+            .map(|tag| ModelInfo {
+                id: tag.name,
+                name: None,
+            })
+            .collect())
+    }
+}
+
+impl ChatProvider for OllamaClient {
+    fn stream<'a>(
+        &'a self,
+        request: &'a ChatRequest,
+        on_chunk: ChunkSink<'a>,
+    ) -> BoxFuture<'a, Result<ChatResponse>> {
+        Box::pin(OllamaClient::stream(self, request, on_chunk))
+    }
+
+    fn list_models(&self) -> BoxFuture<'_, Result<Vec<ModelInfo>>> {
+        Box::pin(OllamaClient::list_models(self))
+    }
+}
+
+/// Convert to Ollama's message format, where tool call arguments are objects and
+/// tool results name their tool instead of the call ID
+fn to_ollama_messages(messages: &[ChatMessage]) -> Vec<Value> {
+    let tool_names: HashMap<&str, &str> = messages
+        .iter()
+        .flat_map(|message| &message.tool_calls)
+        .map(|call| (call.id.as_str(), call.function.name.as_str()))
+        .collect();
+
+    messages
+        .iter()
+        .map(|message| {
+            let mut converted = json!({
+                "role": message.role,
+                "content": message.content.as_deref().unwrap_or_default(),
+            });
+            match message.role {
+                Role::Assistant if !message.tool_calls.is_empty() => {
+                    let calls: Vec<Value> = message
+                        .tool_calls
+                        .iter()
+                        .map(|call| {
+                            let arguments = serde_json::from_str::<Value>(&call.function.arguments)
+                                .ok()
+                                .filter(Value::is_object)
+                                .unwrap_or_else(|| json!({}));
+                            json!({
+                                "function": { "name": call.function.name, "arguments": arguments }
+                            })
+                        })
+                        .collect();
+                    converted["tool_calls"] = json!(calls);
+                }
+                Role::Tool => {
+                    let name = message
+                        .tool_call_id
+                        .as_deref()
+                        .and_then(|id| tool_names.get(id));
+                    if let Some(name) = name {
+                        converted["tool_name"] = json!(name);
+                    }
+                }
+                _ => {}
+            }
+            converted
+        })
+        .collect()
+}
+
+/// Turn a streamed line into OpenAI-style deltas
+///
+/// Ollama doesn't give tool calls IDs, so they are numbered across the response.
+fn to_stream_events(line: ChatLine, tool_calls: &mut usize) -> Vec<StreamEvent> {
+    let mut delta = Delta::default();
+    if let Some(message) = line.message {
+        delta.content = (!message.content.is_empty()).then_some(message.content);
+        delta.reasoning = (!message.thinking.is_empty()).then_some(message.thinking);
+        for call in message.tool_calls {
+            let index = *tool_calls;
+            *tool_calls += 1;
+            delta.tool_calls.push(ToolCallDelta {
+                index,
+                id: Some(format!("call_{}", index)),
+                function: Some(FunctionDelta {
+                    name: Some(call.function.name),
+                    arguments: Some(call.function.arguments.to_string()),
+                }),
+            });
+        }
+    }
+
+    let mut events = vec![StreamEvent {
+        choices: vec![StreamChoice {
+            delta,
+            finish_reason: None,
+        }],
+        usage: None,
+        error: None,
+    }];
+    if line.done {
+        let prompt_tokens = line.prompt_eval_count.unwrap_or_default();
+        let completion_tokens = line.eval_count.unwrap_or_default();
+        // A model that called tools still reports "stop"
+        let finish_reason = if *tool_calls > 0 {
+            Some("tool_calls".to_string())
+        } else {
+            line.done_reason
+        };
+        events.push(StreamEvent {
+            choices: vec![StreamChoice {
+                delta: Delta::default(),
+                finish_reason,
+            }],
+            usage: Some(Usage {
+                prompt_tokens,
+                completion_tokens,
+                total_tokens: prompt_tokens + completion_tokens,
+                cost: None,
+            }),
+            error: None,
+        });
+    }
+    events
+}
@@ -0,0 +1,221 @@
+use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::error::{send, ProviderError, ProviderErrorKind};
+use super::sse::SseDecoder;
+use super::stream::{StreamAccumulator, StreamEvent};
+use super::types::{ChatProvider, ChatRequest, ChatResponse, ChunkSink, ModelInfo};
+
+pub const OPENROUTER_BASE_URL: &str = "https://openrouter.ai/api/v1";
+
+pub const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// Marks the end of a `chat/completions` stream
+const DONE: &str = "[DONE]";
+
+/// How a server wants to be asked for token usage in a stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Flavor {
+    /// `usage: {include: true}`, which also reports the cost
+    OpenRouter,
+    /// `stream_options: {include_usage: true}`, understood by OpenAI and most compatible servers
+    OpenAi,
+}
+
+/// Client for the OpenAI chat completions API and the many servers that copy it
+pub struct OpenAiClient {
+    http: reqwest::Client,
+    /// Name used in error messages
+    name: String,
+    base_url: String,
+    /// Local servers often need none
+    api_key: Option<String>,
+    flavor: Flavor,
+}
+
+#[derive(Deserialize)]
+struct ModelList {
+    data: Vec<ListedModel>,
+}
+
+#[derive(Deserialize)]
+struct ListedModel {
+    id: String,
+    name: Option<String>,
+}
+
+impl OpenAiClient {
+    pub fn openrouter(api_key: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            name: "OpenRouter".to_string(),
+            base_url: OPENROUTER_BASE_URL.to_string(),
+            api_key: Some(api_key.into()),
+            flavor: Flavor::OpenRouter,
+        }
+    }
+
+    pub fn openai(api_key: impl Into<String>) -> Self {
+        Self {
+            name: "OpenAI".to_string(),
+            base_url: OPENAI_BASE_URL.to_string(),
+            flavor: Flavor::OpenAi,
+            ..Self::openrouter(api_key)
+        }
+    }
+
+    /// Any server with an OpenAI-compatible `chat/completions` endpoint under `base_url`
+    pub fn compatible(
+        name: impl Into<String>,
+        base_url: impl Into<String>,
+        api_key: Option<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            api_key,
+            ..Self::openai(String::new())
+        }
+        .with_base_url(base_url)
+    }
+
+    /// Send requests somewhere other than the provider's public API, e.g. a proxy
+    /// or a mock server in tests
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Request body for a streamed `chat/completions`, asking for the usage
+    fn body(&self, request: &ChatRequest) -> Value {
+        let mut body = json!({
+            "model": request.model,
+            "messages": request.messages,
+            "stream": true,
+        });
+        match self.flavor {
+            Flavor::OpenRouter => body["usage"] = json!({ "include": true }),
+            Flavor::OpenAi => body["stream_options"] = json!({ "include_usage": true }),
+        }
+        if !request.tools.is_empty() {
+            body["tools"] = json!(request.tools);
+        }
+        body
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let mut builder = self
+            .http
+            .request(method, format!("{}/{}", self.base_url, path));
+        if let Some(api_key) = self.api_key.as_deref().filter(|key| !key.is_empty()) {
+            builder = builder.bearer_auth(api_key);
+        }
+        if self.flavor == Flavor::OpenRouter {
+            builder = builder.header("X-Title", "Orion AI Chat");
+        }
+        builder
+    }
+
+    /// Stream a completion, passing each chunk to `on_chunk` as it arrives
+    ///
+    /// Failures are returned as a [`ProviderError`], including errors reported
+    /// in the middle of a stream.
+    pub async fn stream(
+        &self,
+        request: &ChatRequest,
+        on_chunk: ChunkSink<'_>,
+    ) -> Result<ChatResponse> {
+        let response = send(
+            &self.name,
+            self.request(reqwest::Method::POST, "chat/completions")
+                .json(&self.body(request)),
+        )
+        .await?;
+
+        let mut bytes = response.bytes_stream();
+        let mut decoder = SseDecoder::default();
+        let mut stream = StreamAccumulator::default();
+
+        let mut finished = false;
+        while !finished {
+            let events = match bytes.next().await {
+                Some(Ok(chunk)) => decoder.push(&chunk),
+                Some(Err(e)) => return Err(ProviderError::network(&self.name, e).into()),
+                None => {
+                    finished = true;
+                    decoder.finish().into_iter().collect()
+                }
+            };
+
+            for data in events {
+                if data == DONE {
+                    finished = true;
+                    break;
+                }
+                let event: StreamEvent = match serde_json::from_str(&data) {
+                    Ok(event) => event,
+                    Err(e) => {
+                        eprintln!("Skipped unparseable {} chunk: {}: {}", self.name, e, data);
+                        continue;
+                    }
+                };
+                if let Some(error) = event.error {
+                    return Err(self.stream_error(error.code.as_ref(), error.message).into());
+                }
+                for chunk in stream.apply(event)? {
+                    on_chunk(chunk);
+                }
+            }
+        }
+
+        Ok(stream.finish())
+    }
+
+    pub async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        let response = send(&self.name, self.request(reqwest::Method::GET, "models")).await?;
+        let models: ModelList = response
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse the {} model list", self.name))?;
+        Ok(models
+            .data
+            .into_iter()
+            .map(|model| ModelInfo {
+                id: model.id,
+                name: model.name,
+            })
+            .collect())
+    }
+
+    /// Error sent inside a stream, whose code is an HTTP status when it is a number
+    fn stream_error(&self, code: Option<&Value>, message: String) -> ProviderError {
+        match code.and_then(Value::as_u64) {
+            Some(status) => ProviderError {
+                message,
+                ..ProviderError::from_status(&self.name, status as u16, None, None)
+            },
+            None => ProviderError {
+                kind: ProviderErrorKind::Server,
+                message,
+                status: None,
+                retry_after_ms: None,
+            },
+        }
+    }
+}
+
+impl ChatProvider for OpenAiClient {
+    fn stream<'a>(
+        &'a self,
+        request: &'a ChatRequest,
+        on_chunk: ChunkSink<'a>,
+    ) -> BoxFuture<'a, Result<ChatResponse>> {
+        Box::pin(OpenAiClient::stream(self, request, on_chunk))
+    }
+
+    fn list_models(&self) -> BoxFuture<'_, Result<Vec<ModelInfo>>> {
+        Box::pin(OpenAiClient::list_models(self))
+    }
+}
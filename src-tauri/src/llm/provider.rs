@@ -0,0 +1,259 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::anthropic::AnthropicClient;
+use super::local::LocalProvider;
+use super::managed::ManagedServer;
+use super::openai::OpenAiClient;
+use super::scheduler::RequestLimits;
+use super::types::ChatProvider;
+use crate::mcp::SecretVault;
+use crate::store::{ConfigStore, StoredConfig};
+
+/// File name of the chat provider configuration in the app config dir
+pub const PROVIDERS_FILE_NAME: &str = "providers.json";
+
+/// Current version of the `providers.json` format
+pub const PROVIDERS_VERSION: u32 = 1;
+
+/// ID of the OpenRouter provider that uses the API key from the settings
+pub const DEFAULT_PROVIDER_ID: &str = "openrouter";
+
+/// API a provider speaks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProviderKind {
+    OpenRouter,
+    OpenAi,
+    Anthropic,
+    /// Any other server with an OpenAI-compatible `chat/completions` endpoint
+    OpenAiCompatible,
+    /// Ollama's native API, on `localhost:11434` unless a base URL is set
+    Ollama,
+    /// llama.cpp's `llama-server`, on `localhost:8080` unless a base URL is set
+    LlamaCpp,
+}
+
+impl ProviderKind {
+    /// Whether the provider runs on this machine and needs no API key
+    pub fn is_local(self) -> bool {
+        matches!(self, Self::Ollama | Self::LlamaCpp)
+    }
+}
+
+/// A configured model provider with its own credentials
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderConfig {
+    pub id: String,
+    pub name: String,
+    pub kind: ProviderKind,
+    /// Overrides the provider's default address; required for compatible servers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Plain key or a `secret:`, `env:` or `file:` reference
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// Models offered for this provider; fetched from its API when empty
+    #[serde(default)]
+    pub models: Vec<String>,
+    /// Local server Orion launches itself, which then sets the address
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub managed: Option<ManagedServer>,
+    /// Concurrency, rate limit and retries of its completion requests
+    #[serde(default)]
+    pub limits: RequestLimits,
+}
+
+/// Validate a provider configuration before it is saved
+pub fn validate_config(config: &ProviderConfig) -> Result<()> {
+    if config.id.trim().is_empty() {
+        return Err(anyhow!("Provider ID must not be empty"));
+    }
+    if config.id == DEFAULT_PROVIDER_ID {
+        return Err(anyhow!(
+            "Provider ID '{}' is reserved for the OpenRouter key in settings",
+            config.id
+        ));
+    }
+    if config.name.trim().is_empty() {
+        return Err(anyhow!("Provider '{}' must have a name", config.id));
+    }
+
+    match config.base_url.as_deref().map(str::trim) {
+        Some(url) if !url.starts_with("http://") && !url.starts_with("https://") => {
+            return Err(anyhow!(
+                "Provider '{}' base URL must start with http:// or https://",
+                config.name
+            ));
+        }
+        None | Some("") if config.kind == ProviderKind::OpenAiCompatible => {
+            return Err(anyhow!("Provider '{}' must have a base URL", config.name));
+        }
+        _ => {}
+    }
+
+    let needs_key = config.kind != ProviderKind::OpenAiCompatible && !config.kind.is_local();
+    if needs_key && config.api_key.as_deref().unwrap_or_default().is_empty() {
+        return Err(anyhow!("Provider '{}' must have an API key", config.name));
+    }
+
+    if let Some(managed) = &config.managed {
+        managed.validate(config.kind, &config.name)?;
+    }
+
+    Ok(())
+}
+
+/// Create a client for a provider, resolving its API key through the vault
+pub fn connect(config: &ProviderConfig, secrets: &SecretVault) -> Result<Box<dyn ChatProvider>> {
+    let api_key = config
+        .api_key
+        .as_deref()
+        .filter(|key| !key.is_empty())
+        .map(|key| secrets.resolve(key))
+        .transpose()
+        .with_context(|| format!("Failed to resolve the API key of '{}'", config.name))?;
+    let required_key = || {
+        api_key
+            .clone()
+            .ok_or_else(|| anyhow!("Provider '{}' has no API key", config.name))
+    };
+
+    let provider: Box<dyn ChatProvider> = match config.kind {
+        ProviderKind::OpenAiCompatible => {
+            let base_url = config
+                .base_url
+                .clone()
+                .ok_or_else(|| anyhow!("Provider '{}' has no base URL", config.name))?;
+            Box::new(OpenAiClient::compatible(&config.name, base_url, api_key))
+        }
+        ProviderKind::Ollama => Box::new(LocalProvider::ollama(local_base_url(config))),
+        ProviderKind::LlamaCpp => Box::new(LocalProvider::llama_cpp(local_base_url(config))),
+        ProviderKind::OpenRouter => {
+            let client = OpenAiClient::openrouter(required_key()?);
+            Box::new(match &config.base_url {
+                Some(base_url) => client.with_base_url(base_url),
+                None => client,
+            })
+        }
+        ProviderKind::OpenAi => {
+            let client = OpenAiClient::openai(required_key()?);
+            Box::new(match &config.base_url {
+                Some(base_url) => client.with_base_url(base_url),
+                None => client,
+            })
+        }
+        ProviderKind::Anthropic => {
+            let client = AnthropicClient::new(required_key()?);
+            Box::new(match &config.base_url {
+                Some(base_url) => client.with_base_url(base_url),
+                None => client,
+            })
+        }
+    };
+    Ok(provider)
+}
+
+/// Address of a local server, which for a managed one is wherever Orion launched it
+fn local_base_url(config: &ProviderConfig) -> Option<String> {
+    match &config.managed {
+        Some(managed) => Some(managed.base_url(config.kind)),
+        None => config.base_url.clone(),
+    }
+}
+
+impl StoredConfig for ProviderConfig {
+    const LIST_KEY: &'static str = "providers";
+    const ENTRY_NAME: &'static str = "Provider";
+    const VERSION: u32 = PROVIDERS_VERSION;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn validate(&self) -> Result<()> {
+        validate_config(self)
+    }
+}
+
+/// Persistent store for chat provider configurations in `providers.json`
+///
+/// Kept apart from the settings, which the frontend replaces wholesale.
+pub type ProviderStore = ConfigStore<ProviderConfig>;
+
+impl ProviderStore {
+    /// Providers whose local server should be launched with the app
+    pub fn autostart_configs(&self) -> Result<Vec<ProviderConfig>> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter(|config| {
+                config
+                    .managed
+                    .as_ref()
+                    .is_some_and(|managed| managed.autostart)
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(kind: ProviderKind) -> ProviderConfig {
+        ProviderConfig {
+            id: "work".to_string(),
+            name: "Work".to_string(),
+            kind,
+            base_url: None,
+            api_key: Some("secret:WORK_KEY".to_string()),
+            models: Vec::new(),
+            managed: None,
+            limits: RequestLimits::default(),
+        }
+    }
+
+    #[test]
+    fn validates_provider_configs() {
+        assert!(validate_config(&config(ProviderKind::Anthropic)).is_ok());
+
+        let reserved = ProviderConfig {
+            id: DEFAULT_PROVIDER_ID.to_string(),
+            ..config(ProviderKind::OpenRouter)
+        };
+        assert!(validate_config(&reserved).is_err());
+
+        let keyless = ProviderConfig {
+            api_key: None,
+            ..config(ProviderKind::OpenAi)
+        };
+        assert!(validate_config(&keyless).is_err());
+
+        // Compatible servers need an address but, often being local, no key
+        let compatible = ProviderConfig {
+            api_key: None,
+            ..config(ProviderKind::OpenAiCompatible)
+        };
+        assert!(validate_config(&compatible).is_err());
+        let compatible = ProviderConfig {
+            base_url: Some("http://localhost:8080/v1".to_string()),
+            ..compatible
+        };
+        assert!(validate_config(&compatible).is_ok());
+
+        let bad_url = ProviderConfig {
+            base_url: Some("localhost:8080".to_string()),
+            ..compatible
+        };
+        assert!(validate_config(&bad_url).is_err());
+
+        // Local servers have a default address
+        let ollama = ProviderConfig {
+            api_key: None,
+            ..config(ProviderKind::Ollama)
+        };
+        assert!(validate_config(&ollama).is_ok());
+    }
+}
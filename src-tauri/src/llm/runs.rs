@@ -0,0 +1,118 @@
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use super::stream::ChatChunk;
+use super::types::{ChatMessage, ChatProvider, ChatRequest, ChatResponse, ChunkSink};
+use crate::mcp::CancelToken;
+
+/// Finish reason of a completion cut short by [`stream_cancellable`]
+pub const STOPPED_FINISH_REASON: &str = "stopped";
+
+/// Generations in flight, by the run ID the frontend chose for them
+///
+/// Lets `cancel_generation` reach a completion or agent run started by another
+/// command.
+#[derive(Default)]
+pub struct GenerationRuns {
+    runs: Mutex<HashMap<String, CancelToken>>,
+}
+
+/// Registration of a run, removed again when dropped
+pub struct RunHandle<'a> {
+    runs: &'a GenerationRuns,
+    id: String,
+    cancel: CancelToken,
+}
+
+impl RunHandle<'_> {
+    /// Token that fires when the run is cancelled
+    pub fn cancel_token(&self) -> &CancelToken {
+        &self.cancel
+    }
+}
+
+impl Drop for RunHandle<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut runs) = self.runs.runs.lock() {
+            runs.remove(&self.id);
+        }
+    }
+}
+
+impl GenerationRuns {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a run under `run_id`, which must not be in use
+    pub fn register(&self, run_id: &str) -> Result<RunHandle<'_>> {
+        let mut runs = self.runs.lock().map_err(|e| anyhow!(e.to_string()))?;
+        if runs.contains_key(run_id) {
+            return Err(anyhow!("Run '{}' is already in progress", run_id));
+        }
+        let cancel = CancelToken::new();
+        runs.insert(run_id.to_string(), cancel.clone());
+        Ok(RunHandle {
+            runs: self,
+            id: run_id.to_string(),
+            cancel,
+        })
+    }
+
+    /// Stop a run; it ends soon after, keeping what it generated so far
+    pub fn cancel(&self, run_id: &str) -> Result<()> {
+        let runs = self.runs.lock().map_err(|e| anyhow!(e.to_string()))?;
+        let cancel = runs
+            .get(run_id)
+            .ok_or_else(|| anyhow!("Run '{}' is not in progress", run_id))?;
+        cancel.cancel();
+        Ok(())
+    }
+}
+
+/// Stream a completion until it finishes or `cancel` fires
+///
+/// The HTTP stream is dropped on cancellation, which aborts the completion. It
+/// is then returned with the text streamed so far, no tool calls and a
+/// [`STOPPED_FINISH_REASON`], naming the model of the last [`ChatChunk::Model`]
+/// or else the requested one.
+pub async fn stream_cancellable(
+    provider: &dyn ChatProvider,
+    request: &ChatRequest,
+    on_chunk: ChunkSink<'_>,
+    cancel: &CancelToken,
+) -> Result<ChatResponse> {
+    let partial = Mutex::new(String::new());
+    let model = Mutex::new(request.model.clone());
+    let forward = |chunk: ChatChunk| {
+        match &chunk {
+            ChatChunk::Content { text } => {
+                if let Ok(mut partial) = partial.lock() {
+                    partial.push_str(text);
+                }
+            }
+            ChatChunk::Model { model: current } => {
+                if let Ok(mut model) = model.lock() {
+                    model.clone_from(current);
+                }
+            }
+            _ => {}
+        }
+        on_chunk(chunk);
+    };
+
+    tokio::select! {
+        response = provider.stream(request, &forward) => response,
+        _ = cancel.cancelled() => {
+            let text = partial.lock().map(|text| text.clone()).unwrap_or_default();
+            let model = model.lock().map(|model| model.clone()).ok();
+            Ok(ChatResponse {
+                message: ChatMessage::assistant(text),
+                usage: None,
+                finish_reason: Some(STOPPED_FINISH_REASON.to_string()),
+                model,
+            })
+        }
+    }
+}
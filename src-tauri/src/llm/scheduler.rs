@@ -0,0 +1,266 @@
+use anyhow::{anyhow, Result};
+use futures::future::BoxFuture;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::time::{Duration, Instant};
+
+use super::error::{ProviderError, ProviderErrorKind};
+use super::stream::ChatChunk;
+use super::types::{ChatProvider, ChatRequest, ChatResponse, ChunkSink, ModelInfo};
+use crate::mcp::limits::CallLimiter;
+use crate::mcp::RateLimit;
+
+/// How failed completion requests are retried
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; zero disables retrying
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Backoff before the first retry, doubled for every further one
+    #[serde(default = "default_base_delay_ms")]
+    pub base_delay_ms: u64,
+    /// Longest wait before a retry; a longer `Retry-After` fails the request instead
+    #[serde(default = "default_max_delay_ms")]
+    pub max_delay_ms: u64,
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+fn default_base_delay_ms() -> u64 {
+    1000
+}
+
+fn default_max_delay_ms() -> u64 {
+    60_000
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: default_max_retries(),
+            base_delay_ms: default_base_delay_ms(),
+            max_delay_ms: default_max_delay_ms(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait before retrying after the given number of retries
+    ///
+    /// Honors the provider's `Retry-After` when it sent one, or backs off
+    /// exponentially otherwise. Either way a random part of up to half the base
+    /// delay is added so requests that failed together don't retry together.
+    /// `None` if the provider asked to wait longer than `max_delay_ms`.
+    pub fn delay(&self, retries: u32, retry_after_ms: Option<u64>) -> Option<Duration> {
+        let jitter = |max_ms: u64| rand::thread_rng().gen_range(0..=max_ms);
+        let delay_ms = match retry_after_ms {
+            Some(ms) if ms > self.max_delay_ms => return None,
+            Some(ms) => ms + jitter(self.base_delay_ms / 2),
+            None => {
+                let backoff = self
+                    .base_delay_ms
+                    .saturating_mul(1 << retries.min(20))
+                    .min(self.max_delay_ms);
+                backoff / 2 + jitter(backoff / 2)
+            }
+        };
+        Some(Duration::from_millis(delay_ms))
+    }
+}
+
+/// How hard Orion may use a provider
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestLimits {
+    /// Completions streaming at once; more wait in line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_requests: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimit>,
+    #[serde(default)]
+    pub retry: RetryPolicy,
+}
+
+/// Queue of one provider's completion requests
+struct ProviderLane {
+    limits: RequestLimits,
+    limiter: CallLimiter,
+    /// Set when the provider rate limits a request, so every request to it waits
+    paused_until: Mutex<Option<Instant>>,
+}
+
+impl ProviderLane {
+    fn new(limits: &RequestLimits) -> Self {
+        Self {
+            limits: limits.clone(),
+            limiter: CallLimiter::with_limits(
+                limits.max_concurrent_requests,
+                limits.rate_limit.clone(),
+            ),
+            paused_until: Mutex::new(None),
+        }
+    }
+
+    fn pause_for(&self, delay: Duration) {
+        let until = Instant::now() + delay;
+        if let Ok(mut paused_until) = self.paused_until.lock() {
+            if paused_until.is_none_or(|current| current < until) {
+                *paused_until = Some(until);
+            }
+        }
+    }
+
+    async fn wait_until_resumed(&self) {
+        loop {
+            let until = self.paused_until.lock().ok().and_then(|until| *until);
+            match until {
+                Some(until) if until > Instant::now() => tokio::time::sleep_until(until).await,
+                _ => return,
+            }
+        }
+    }
+}
+
+/// Queues completion requests per provider, across all conversations
+///
+/// Every request to a provider waits for its concurrency and rate limits, and
+/// for any pause the provider asked for by rate limiting an earlier request.
+#[derive(Default)]
+pub struct RequestScheduler {
+    lanes: Mutex<HashMap<String, Arc<ProviderLane>>>,
+}
+
+impl RequestScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send the requests of a provider's client through its queue, retrying failures
+    ///
+    /// Requests still in flight keep the old limits when a provider's limits change.
+    pub fn schedule(
+        &self,
+        provider_id: &str,
+        limits: &RequestLimits,
+        client: Box<dyn ChatProvider>,
+    ) -> Result<Box<dyn ChatProvider>> {
+        let mut lanes = self.lanes.lock().map_err(|e| anyhow!(e.to_string()))?;
+        let lane = match lanes.get(provider_id) {
+            Some(lane) if lane.limits == *limits => lane.clone(),
+            _ => {
+                let lane = Arc::new(ProviderLane::new(limits));
+                lanes.insert(provider_id.to_string(), lane.clone());
+                lane
+            }
+        };
+        Ok(Box::new(ScheduledProvider {
+            client,
+            name: provider_id.to_string(),
+            lane,
+        }))
+    }
+}
+
+/// A provider's client whose requests wait in the provider's queue
+struct ScheduledProvider {
+    client: Box<dyn ChatProvider>,
+    name: String,
+    lane: Arc<ProviderLane>,
+}
+
+impl ScheduledProvider {
+    async fn stream(&self, request: &ChatRequest, on_chunk: ChunkSink<'_>) -> Result<ChatResponse> {
+        // Retrying after part of the answer was shown would show it twice
+        let started = AtomicBool::new(false);
+        let forward = |chunk: ChatChunk| {
+            started.store(true, Ordering::Relaxed);
+            on_chunk(chunk);
+        };
+
+        let mut retries = 0;
+        loop {
+            self.lane.wait_until_resumed().await;
+            let permit = self.lane.limiter.acquire().await;
+            let result = self.client.stream(request, &forward).await;
+            drop(permit);
+
+            let error = match result {
+                Ok(response) => return Ok(response),
+                Err(error) if started.load(Ordering::Relaxed) => return Err(error),
+                Err(error) => error,
+            };
+            let Some(delay) = self.retry_delay(&error, retries) else {
+                return Err(error);
+            };
+            eprintln!(
+                "{} request failed, retrying in {} ms: {}",
+                self.name,
+                delay.as_millis(),
+                error
+            );
+            retries += 1;
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Wait before the next attempt, if the error is worth another one
+    fn retry_delay(&self, error: &anyhow::Error, retries: u32) -> Option<Duration> {
+        let error = error.downcast_ref::<ProviderError>()?;
+        let policy = &self.lane.limits.retry;
+        if !error.is_retryable() || retries >= policy.max_retries {
+            return None;
+        }
+        let delay = policy.delay(retries, error.retry_after_ms)?;
+        if error.kind == ProviderErrorKind::RateLimited {
+            self.lane.pause_for(delay);
+        }
+        Some(delay)
+    }
+}
+
+impl ChatProvider for ScheduledProvider {
+    fn stream<'a>(
+        &'a self,
+        request: &'a ChatRequest,
+        on_chunk: ChunkSink<'a>,
+    ) -> BoxFuture<'a, Result<ChatResponse>> {
+        Box::pin(ScheduledProvider::stream(self, request, on_chunk))
+    }
+
+    fn list_models(&self) -> BoxFuture<'_, Result<Vec<ModelInfo>>> {
+        self.client.list_models()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backs_off_exponentially_and_honors_retry_after() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            base_delay_ms: 1000,
+            max_delay_ms: 10_000,
+        };
+        let millis = |retries, retry_after_ms| {
+            policy
+                .delay(retries, retry_after_ms)
+                .map(|delay| delay.as_millis() as u64)
+        };
+
+        for _ in 0..20 {
+            assert!((500..=1000).contains(&millis(0, None).unwrap()));
+            assert!((2000..=4000).contains(&millis(2, None).unwrap()));
+            assert!((5000..=10_000).contains(&millis(10, None).unwrap()));
+            assert!((3000..=3500).contains(&millis(0, Some(3000)).unwrap()));
+        }
+        assert_eq!(millis(0, Some(20_000)), None);
+    }
+}
@@ -0,0 +1,69 @@
+/// Splits a server-sent events byte stream into the `data` of each event
+///
+/// Bytes can arrive cut anywhere, even inside a UTF-8 character, so only complete
+/// lines are decoded. Comment lines, like OpenRouter's `: OPENROUTER PROCESSING`
+/// keep-alives, and fields other than `data` are skipped.
+#[derive(Default)]
+pub struct SseDecoder {
+    buffer: Vec<u8>,
+    data: Vec<String>,
+}
+
+impl SseDecoder {
+    /// Feed received bytes, returning the data of every event they completed
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(bytes);
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            self.line(line.trim_end_matches(['\n', '\r']), &mut events);
+        }
+        events
+    }
+
+    /// Data of an event left unterminated when the stream ended
+    pub fn finish(&mut self) -> Option<String> {
+        if !self.buffer.is_empty() {
+            let line = String::from_utf8_lossy(&std::mem::take(&mut self.buffer)).into_owned();
+            let mut events = Vec::new();
+            self.line(line.trim_end_matches('\r'), &mut events);
+        }
+        (!self.data.is_empty()).then(|| std::mem::take(&mut self.data).join("\n"))
+    }
+
+    fn line(&mut self, line: &str, events: &mut Vec<String>) {
+        if line.is_empty() {
+            if !self.data.is_empty() {
+                events.push(std::mem::take(&mut self.data).join("\n"));
+            }
+            return;
+        }
+        if let Some(value) = line.strip_prefix("data:") {
+            self.data
+                .push(value.strip_prefix(' ').unwrap_or(value).to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_events_split_across_chunks() {
+        let mut decoder = SseDecoder::default();
+        let stream = ": OPENROUTER PROCESSING\n\ndata: {\"a\":\"é\"}\r\n\r\ndata: x\ndata: y\n\nevent: ping\n\ndata: [DONE]";
+
+        // Cut inside the two-byte 'é' and between the CR and LF
+        let bytes = stream.as_bytes();
+        let cut = stream.find('é').unwrap() + 1;
+        let mut events = decoder.push(&bytes[..cut]);
+        events.extend(decoder.push(&bytes[cut..cut + 3]));
+        events.extend(decoder.push(&bytes[cut + 3..]));
+
+        assert_eq!(events, vec!["{\"a\":\"é\"}", "x\ny"]);
+        assert_eq!(decoder.finish().as_deref(), Some("[DONE]"));
+    }
+}
@@ -0,0 +1,260 @@
+use serde::{Deserialize, Serialize};
+
+use super::error::{ProviderError, ProviderErrorKind};
+use super::types::{ChatMessage, ChatResponse, FunctionCall, Role, ToolCall, Usage};
+
+/// How far past the calls seen so far a tool call fragment's index may point
+const MAX_TOOL_CALL_INDEX_GAP: usize = 64;
+
+/// One piece of a streamed completion, in the order it arrived
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ChatChunk {
+    Content {
+        text: String,
+    },
+    /// Thinking the model shows before answering
+    Reasoning {
+        text: String,
+    },
+    /// Fragment of a tool call; fragments with the same index make up one call
+    ToolCall {
+        index: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        arguments: String,
+    },
+    Usage {
+        usage: Usage,
+    },
+    Finish {
+        reason: String,
+    },
+    /// Model the chunks that follow come from, sent as a fallback chain tries it
+    Model {
+        model: String,
+    },
+}
+
+/// `data` of one chunk of a `chat/completions` stream
+#[derive(Debug, Deserialize)]
+pub struct StreamEvent {
+    #[serde(default)]
+    pub choices: Vec<StreamChoice>,
+    pub usage: Option<Usage>,
+    /// Failure after the stream started, e.g. the upstream provider dropped out
+    pub error: Option<StreamError>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StreamChoice {
+    #[serde(default)]
+    pub delta: Delta,
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Delta {
+    pub content: Option<String>,
+    /// `reasoning_content` on DeepSeek and most OpenAI-compatible servers
+    #[serde(alias = "reasoning_content")]
+    pub reasoning: Option<String>,
+    #[serde(default)]
+    pub tool_calls: Vec<ToolCallDelta>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ToolCallDelta {
+    #[serde(default)]
+    pub index: usize,
+    pub id: Option<String>,
+    pub function: Option<FunctionDelta>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FunctionDelta {
+    pub name: Option<String>,
+    pub arguments: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StreamError {
+    pub code: Option<serde_json::Value>,
+    pub message: String,
+}
+
+/// Builds the complete response out of a stream's deltas
+#[derive(Default)]
+pub struct StreamAccumulator {
+    content: String,
+    reasoning: String,
+    tool_calls: Vec<ToolCall>,
+    usage: Option<Usage>,
+    finish_reason: Option<String>,
+}
+
+impl StreamAccumulator {
+    /// Apply one stream event, returning it as chunks for the UI
+    ///
+    /// Fails on a tool call index far beyond the calls seen so far, which would
+    /// otherwise make room for that many empty calls.
+    pub fn apply(&mut self, event: StreamEvent) -> Result<Vec<ChatChunk>, ProviderError> {
+        let mut chunks = Vec::new();
+
+        for choice in event.choices.into_iter().take(1) {
+            let delta = choice.delta;
+            if let Some(text) = delta.reasoning.filter(|text| !text.is_empty()) {
+                self.reasoning.push_str(&text);
+                chunks.push(ChatChunk::Reasoning { text });
+            }
+            if let Some(text) = delta.content.filter(|text| !text.is_empty()) {
+                self.content.push_str(&text);
+                chunks.push(ChatChunk::Content { text });
+            }
+            for fragment in delta.tool_calls {
+                let (name, arguments) = fragment
+                    .function
+                    .map(|function| (function.name, function.arguments.unwrap_or_default()))
+                    .unwrap_or_default();
+                self.add_tool_call_fragment(
+                    fragment.index,
+                    fragment.id.as_deref(),
+                    name.as_deref(),
+                    &arguments,
+                )?;
+                chunks.push(ChatChunk::ToolCall {
+                    index: fragment.index,
+                    id: fragment.id,
+                    name,
+                    arguments,
+                });
+            }
+            if let Some(reason) = choice.finish_reason {
+                self.finish_reason = Some(reason.clone());
+                chunks.push(ChatChunk::Finish { reason });
+            }
+        }
+
+        if let Some(usage) = event.usage {
+            self.usage = Some(usage.clone());
+            chunks.push(ChatChunk::Usage { usage });
+        }
+        Ok(chunks)
+    }
+
+    /// Providers send a call's ID and name once, then its arguments in pieces
+    fn add_tool_call_fragment(
+        &mut self,
+        index: usize,
+        id: Option<&str>,
+        name: Option<&str>,
+        arguments: &str,
+    ) -> Result<(), ProviderError> {
+        if index > self.tool_calls.len() + MAX_TOOL_CALL_INDEX_GAP {
+            return Err(ProviderError {
+                kind: ProviderErrorKind::Server,
+                message: format!(
+                    "The stream sent tool call {} after only {} calls",
+                    index,
+                    self.tool_calls.len()
+                ),
+                status: None,
+                retry_after_ms: None,
+            });
+        }
+        while self.tool_calls.len() <= index {
+            self.tool_calls.push(ToolCall {
+                id: String::new(),
+                call_type: "function".to_string(),
+                function: FunctionCall {
+                    name: String::new(),
+                    arguments: String::new(),
+                },
+            });
+        }
+        let call = &mut self.tool_calls[index];
+        if let Some(id) = id {
+            call.id = id.to_string();
+        }
+        if let Some(name) = name {
+            call.function.name.push_str(name);
+        }
+        call.function.arguments.push_str(arguments);
+        Ok(())
+    }
+
+    pub fn finish(self) -> ChatResponse {
+        let tool_calls = self
+            .tool_calls
+            .into_iter()
+            .filter(|call| !call.function.name.is_empty())
+            .collect();
+
+        ChatResponse {
+            message: ChatMessage {
+                role: Role::Assistant,
+                content: (!self.content.is_empty()).then_some(self.content),
+                tool_calls,
+                tool_call_id: None,
+                reasoning: (!self.reasoning.is_empty()).then_some(self.reasoning),
+            },
+            usage: self.usage,
+            finish_reason: self.finish_reason,
+            model: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(data: &str) -> StreamEvent {
+        serde_json::from_str(data).unwrap()
+    }
+
+    #[test]
+    fn assembles_content_reasoning_and_tool_call_fragments() {
+        let mut stream = StreamAccumulator::default();
+        let data = [
+            r#"{"choices":[{"delta":{"role":"assistant","reasoning":"Let me "}}]}"#,
+            r#"{"choices":[{"delta":{"reasoning":"look.","content":"Checking"}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"fs__read","arguments":""}}]}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"path\":"}}]}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":1,"id":"call_2","function":{"name":"fs__list","arguments":"{}"}}]}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"/a\"}"}}]}}]}"#,
+            r#"{"choices":[{"delta":{},"finish_reason":"tool_calls"}]}"#,
+            r#"{"choices":[],"usage":{"prompt_tokens":10,"completion_tokens":5,"total_tokens":15,"cost":0.002}}"#,
+        ];
+
+        let mut chunks = Vec::new();
+        for data in data {
+            chunks.extend(stream.apply(event(data)).unwrap());
+        }
+        assert!(matches!(&chunks[0], ChatChunk::Reasoning { text } if text == "Let me "));
+        assert!(matches!(chunks.last(), Some(ChatChunk::Usage { .. })));
+
+        let response = stream.finish();
+        let message = response.message;
+        assert_eq!(message.content.as_deref(), Some("Checking"));
+        assert_eq!(message.reasoning.as_deref(), Some("Let me look."));
+        assert_eq!(message.tool_calls.len(), 2);
+        assert_eq!(message.tool_calls[0].id, "call_1");
+        assert_eq!(message.tool_calls[0].function.arguments, r#"{"path":"/a"}"#);
+        assert_eq!(message.tool_calls[1].function.name, "fs__list");
+        assert_eq!(response.finish_reason.as_deref(), Some("tool_calls"));
+        assert_eq!(response.usage.unwrap().cost, Some(0.002));
+    }
+
+    #[test]
+    fn rejects_tool_call_indices_far_past_the_last_call() {
+        let mut stream = StreamAccumulator::default();
+        let data = r#"{"choices":[{"delta":{"tool_calls":[{"index":4000000000,"id":"call_1","function":{"name":"fs__read","arguments":"{}"}}]}}]}"#;
+
+        let error = stream.apply(event(data)).unwrap_err();
+        assert_eq!(error.kind, ProviderErrorKind::Server);
+        assert!(stream.finish().message.tool_calls.is_empty());
+    }
+}
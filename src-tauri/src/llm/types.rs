@@ -0,0 +1,142 @@
+use anyhow::Result;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+
+use super::stream::ChatChunk;
+use crate::mcp::OpenAITool;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+    Tool,
+}
+
+/// One message of a conversation in the OpenAI chat completions format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: Role,
+    /// Empty for assistant messages that only call tools
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// Call a `tool` message answers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    /// Thinking the model showed before answering, sent back so it can pick up where it left off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
+}
+
+impl ChatMessage {
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::User,
+            content: Some(content.into()),
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+            reasoning: None,
+        }
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::Assistant,
+            ..Self::user(content)
+        }
+    }
+
+    /// Result of a tool call, sent back to the model
+    pub fn tool(tool_call_id: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            role: Role::Tool,
+            tool_call_id: Some(tool_call_id.into()),
+            ..Self::user(content)
+        }
+    }
+}
+
+/// Tool call requested by the model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    #[serde(rename = "type", default = "function_type")]
+    pub call_type: String,
+    pub function: FunctionCall,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionCall {
+    /// Provider-safe tool name, as listed by `list_provider_tools`
+    pub name: String,
+    /// Arguments as a JSON string, which models don't always get right
+    #[serde(default)]
+    pub arguments: String,
+}
+
+fn function_type() -> String {
+    "function".to_string()
+}
+
+/// Token counts of one completion
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Usage {
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
+    #[serde(default)]
+    pub total_tokens: u64,
+    /// Price of the completion in USD, when the provider reports it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChatRequest {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    #[serde(default)]
+    pub tools: Vec<OpenAITool>,
+}
+
+/// The model's answer to a [`ChatRequest`]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatResponse {
+    pub message: ChatMessage,
+    pub usage: Option<Usage>,
+    pub finish_reason: Option<String>,
+    /// Model that answered, when a [`FallbackChain`](super::FallbackChain) picked it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+/// Receives the chunks of a streamed completion as they arrive
+pub type ChunkSink<'a> = &'a (dyn Fn(ChatChunk) + Send + Sync);
+
+/// Model offered by a provider
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelInfo {
+    pub id: String,
+    /// Display name, when the provider has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// A model provider's API, like OpenRouter or Anthropic
+pub trait ChatProvider: Send + Sync {
+    /// Stream the answer to `on_chunk` and return it once complete
+    fn stream<'a>(
+        &'a self,
+        request: &'a ChatRequest,
+        on_chunk: ChunkSink<'a>,
+    ) -> BoxFuture<'a, Result<ChatResponse>>;
+
+    /// Models the provider currently offers
+    fn list_models(&self) -> BoxFuture<'_, Result<Vec<ModelInfo>>>;
+}
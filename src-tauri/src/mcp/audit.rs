@@ -0,0 +1,521 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::protocol::CallToolResult;
+use super::trace::redact;
+
+/// Directory under the app data directory holding the audit log
+pub const AUDIT_DIR_NAME: &str = "audit";
+
+const RETENTION_FILE_NAME: &str = "retention.json";
+
+/// Directory under the audit directory that exports are written to
+const EXPORT_DIR_NAME: &str = "exports";
+
+/// Longest result summary kept per entry, in characters
+const SUMMARY_LEN: usize = 1000;
+
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// How a tool call was authorized
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ApprovalDecision {
+    /// Ran without asking, e.g. an auto-approved tool or a gateway client
+    #[default]
+    Auto,
+    /// The user approved the call
+    Approved,
+    /// The user denied the call, so it never reached the server
+    Denied,
+}
+
+/// One tool call in the audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub timestamp_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<String>,
+    pub server_id: String,
+    pub tool_name: String,
+    /// Arguments with values of sensitive keys redacted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<Value>,
+    /// Start of the text the tool returned
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_summary: Option<String>,
+    /// The tool reported an error result
+    #[serde(default)]
+    pub is_error: bool,
+    pub duration_ms: u64,
+    pub approval: ApprovalDecision,
+    /// The call failed before producing a result
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl AuditEntry {
+    /// Entry for a finished or failed call
+    pub fn new(
+        server_id: &str,
+        tool_name: &str,
+        arguments: Option<&Value>,
+        conversation_id: Option<String>,
+        approval: ApprovalDecision,
+    ) -> Self {
+        let arguments = arguments.map(|arguments| {
+            let mut arguments = arguments.clone();
+            redact(&mut arguments, &[]);
+            arguments
+        });
+
+        Self {
+            timestamp_ms: now_ms(),
+            conversation_id,
+            server_id: server_id.to_string(),
+            tool_name: tool_name.to_string(),
+            arguments,
+            result_summary: None,
+            is_error: false,
+            duration_ms: 0,
+            approval,
+            error: None,
+        }
+    }
+
+    /// Fill in the outcome of the call
+    pub fn finish(mut self, outcome: &Result<CallToolResult>, duration_ms: u64) -> Self {
+        self.duration_ms = duration_ms;
+        match outcome {
+            Ok(result) => {
+                self.is_error = result.is_error.unwrap_or(false);
+                self.result_summary = Some(summarize(result));
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+        self
+    }
+}
+
+fn summarize(result: &CallToolResult) -> String {
+    let text = result
+        .content
+        .iter()
+        .filter_map(|content| content.text.as_deref())
+        .collect::<Vec<_>>()
+        .join("\n");
+    match text.char_indices().nth(SUMMARY_LEN) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text,
+    }
+}
+
+/// How long audit entries are kept
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRetention {
+    /// Days of entries to keep; `None` keeps them forever
+    pub max_age_days: Option<u32>,
+}
+
+impl Default for AuditRetention {
+    fn default() -> Self {
+        Self {
+            max_age_days: Some(90),
+        }
+    }
+}
+
+/// Filter for reading the audit log; every field is optional
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AuditQuery {
+    pub conversation_id: Option<String>,
+    pub server_id: Option<String>,
+    pub tool_name: Option<String>,
+    pub since_ms: Option<u64>,
+    pub until_ms: Option<u64>,
+    /// Only failed calls, error results and denials
+    pub errors_only: bool,
+    pub limit: Option<usize>,
+    pub offset: usize,
+}
+
+impl AuditQuery {
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        let equals = |filter: &Option<String>, value: &str| {
+            filter.as_deref().is_none_or(|filter| filter == value)
+        };
+
+        equals(&self.server_id, &entry.server_id)
+            && equals(&self.tool_name, &entry.tool_name)
+            && self
+                .conversation_id
+                .as_ref()
+                .is_none_or(|id| entry.conversation_id.as_ref() == Some(id))
+            && self
+                .since_ms
+                .is_none_or(|since| entry.timestamp_ms >= since)
+            && self
+                .until_ms
+                .is_none_or(|until| entry.timestamp_ms <= until)
+            && (!self.errors_only
+                || entry.is_error
+                || entry.error.is_some()
+                || entry.approval == ApprovalDecision::Denied)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportFormat {
+    /// One JSON object per line
+    Jsonl,
+    Csv,
+}
+
+/// Append-only log of tool calls, one JSONL file per UTC day
+///
+/// Entries are never rewritten; retention only deletes whole day files.
+pub struct McpAuditLog {
+    dir: PathBuf,
+    retention: Mutex<AuditRetention>,
+    /// Day of the last write, to prune once per day
+    last_day: Mutex<Option<u64>>,
+}
+
+impl McpAuditLog {
+    /// Open the log in `dir` and apply its retention settings
+    pub fn open(dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        let retention_path = dir.join(RETENTION_FILE_NAME);
+        let retention = match std::fs::read_to_string(&retention_path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                // Refusing to open would leave tool calls unaudited
+                eprintln!(
+                    "Ignoring {}, keeping the default retention: {}",
+                    retention_path.display(),
+                    e
+                );
+                AuditRetention::default()
+            }),
+            Err(_) => AuditRetention::default(),
+        };
+
+        let log = Self {
+            dir: dir.to_path_buf(),
+            retention: Mutex::new(retention),
+            last_day: Mutex::new(None),
+        };
+        log.prune()?;
+        Ok(log)
+    }
+
+    pub fn retention(&self) -> Result<AuditRetention> {
+        Ok(self
+            .retention
+            .lock()
+            .map_err(|_| anyhow!("Audit log lock poisoned"))?
+            .clone())
+    }
+
+    /// Save new retention settings and prune right away
+    pub fn set_retention(&self, retention: AuditRetention) -> Result<()> {
+        let path = self.dir.join(RETENTION_FILE_NAME);
+        std::fs::write(&path, serde_json::to_string_pretty(&retention)?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        *self
+            .retention
+            .lock()
+            .map_err(|_| anyhow!("Audit log lock poisoned"))? = retention;
+        self.prune()
+    }
+
+    /// Append an entry; failures are reported on stderr so they never fail a call
+    pub fn record(&self, entry: &AuditEntry) {
+        if let Err(e) = self.append(entry) {
+            eprintln!("Failed to write MCP audit log: {}", e);
+        }
+    }
+
+    fn append(&self, entry: &AuditEntry) -> Result<()> {
+        let day = entry.timestamp_ms / DAY_MS;
+        let mut last_day = self
+            .last_day
+            .lock()
+            .map_err(|_| anyhow!("Audit log lock poisoned"))?;
+        if *last_day != Some(day) {
+            *last_day = Some(day);
+            self.prune()?;
+        }
+
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        let path = self.day_path(day);
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .with_context(|| format!("Failed to append to {}", path.display()))
+    }
+
+    /// Entries matching the query, newest first
+    pub fn query(&self, query: &AuditQuery) -> Result<Vec<AuditEntry>> {
+        let first_day = query.since_ms.map(|since| since / DAY_MS);
+        let last_day = query.until_ms.map(|until| until / DAY_MS);
+        let limit = query.limit.unwrap_or(usize::MAX);
+
+        let mut entries = Vec::new();
+        let mut skipped = 0;
+        for (day, path) in self.day_files()?.into_iter().rev() {
+            if first_day.is_some_and(|first| day < first) {
+                break;
+            }
+            if last_day.is_some_and(|last| day > last) {
+                continue;
+            }
+
+            let contents = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            for entry in contents
+                .lines()
+                .rev()
+                .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok())
+                .filter(|entry| query.matches(entry))
+            {
+                if skipped < query.offset {
+                    skipped += 1;
+                    continue;
+                }
+                entries.push(entry);
+                if entries.len() >= limit {
+                    return Ok(entries);
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Write entries matching the query to `file_name` in the export directory, oldest first
+    ///
+    /// Only a plain file name is accepted, so an export can't overwrite files
+    /// elsewhere. Returns the path written and the number of entries exported.
+    pub fn export(
+        &self,
+        query: &AuditQuery,
+        format: ExportFormat,
+        file_name: &str,
+    ) -> Result<(PathBuf, usize)> {
+        if Path::new(file_name).file_name() != Some(file_name.as_ref()) {
+            return Err(anyhow!("'{}' is not a plain file name", file_name));
+        }
+        let dir = self.dir.join(EXPORT_DIR_NAME);
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        let path = dir.join(file_name);
+
+        let mut entries = self.query(query)?;
+        entries.reverse();
+
+        let contents = match format {
+            ExportFormat::Jsonl => entries
+                .iter()
+                .map(|entry| serde_json::to_string(entry).map(|line| line + "\n"))
+                .collect::<Result<String, _>>()?,
+            ExportFormat::Csv => to_csv(&entries)?,
+        };
+        std::fs::write(&path, contents)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+
+        Ok((path, entries.len()))
+    }
+
+    /// Delete day files older than the retention period
+    fn prune(&self) -> Result<()> {
+        let Some(max_age_days) = self.retention()?.max_age_days else {
+            return Ok(());
+        };
+        let oldest_kept = (now_ms() / DAY_MS).saturating_sub(max_age_days as u64);
+
+        for (day, path) in self.day_files()? {
+            if day >= oldest_kept {
+                break;
+            }
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+        Ok(())
+    }
+
+    fn day_path(&self, day: u64) -> PathBuf {
+        let (year, month, date) = civil_from_days(day);
+        self.dir
+            .join(format!("audit-{:04}-{:02}-{:02}.jsonl", year, month, date))
+    }
+
+    /// Day files sorted oldest first, with their day number
+    fn day_files(&self) -> Result<Vec<(u64, PathBuf)>> {
+        let mut files: Vec<(u64, PathBuf)> = std::fs::read_dir(&self.dir)
+            .with_context(|| format!("Failed to read {}", self.dir.display()))?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                let date = name.strip_prefix("audit-")?.strip_suffix(".jsonl")?;
+                Some((days_from_civil(date)?, entry.path()))
+            })
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+}
+
+fn to_csv(entries: &[AuditEntry]) -> Result<String> {
+    let mut csv = String::from(
+        "timestamp_ms,conversation_id,server_id,tool_name,arguments,result_summary,is_error,duration_ms,approval,error\n",
+    );
+    for entry in entries {
+        let arguments = entry
+            .arguments
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+        let approval = serde_json::to_value(entry.approval)?;
+        let fields = [
+            entry.timestamp_ms.to_string(),
+            entry.conversation_id.clone().unwrap_or_default(),
+            entry.server_id.clone(),
+            entry.tool_name.clone(),
+            arguments.unwrap_or_default(),
+            entry.result_summary.clone().unwrap_or_default(),
+            entry.is_error.to_string(),
+            entry.duration_ms.to_string(),
+            approval.as_str().unwrap_or_default().to_string(),
+            entry.error.clone().unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    Ok(csv)
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Gregorian date of a day number counted from 1970-01-01
+fn civil_from_days(day: u64) -> (i64, u32, u32) {
+    let z = day as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let date = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, date)
+}
+
+/// Day number of a `YYYY-MM-DD` date counted from 1970-01-01
+fn days_from_civil(date: &str) -> Option<u64> {
+    let mut parts = date.splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: i64 = parts.next()?.parse().ok()?;
+    let day: i64 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    u64::try_from(era * 146_097 + doe - 719_468).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_day_numbers_to_dates_and_back() {
+        for (day, date) in [
+            (0, "1970-01-01"),
+            (59, "1970-03-01"),
+            (11_016, "2000-02-29"),
+            (20_543, "2026-03-31"),
+        ] {
+            let (year, month, dom) = civil_from_days(day);
+            assert_eq!(format!("{:04}-{:02}-{:02}", year, month, dom), date);
+            assert_eq!(days_from_civil(date), Some(day));
+        }
+        assert_eq!(days_from_civil("2026-13-01"), None);
+    }
+
+    #[test]
+    fn quotes_csv_fields() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn survives_bad_retention_and_exports_only_to_its_directory() {
+        let dir = std::env::temp_dir().join(format!("orion-audit-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(RETENTION_FILE_NAME), "{ not json").unwrap();
+
+        let log = McpAuditLog::open(&dir).unwrap();
+        assert_eq!(log.retention().unwrap().max_age_days, Some(90));
+
+        log.record(&AuditEntry {
+            timestamp_ms: now_ms(),
+            conversation_id: None,
+            server_id: "fake".to_string(),
+            tool_name: "echo".to_string(),
+            arguments: None,
+            result_summary: Some("done".to_string()),
+            is_error: false,
+            duration_ms: 1,
+            approval: ApprovalDecision::Auto,
+            error: None,
+        });
+        let query = AuditQuery::default();
+        let (path, count) = log
+            .export(&query, ExportFormat::Jsonl, "calls.jsonl")
+            .unwrap();
+        assert_eq!(path, dir.join(EXPORT_DIR_NAME).join("calls.jsonl"));
+        assert_eq!(count, 1);
+
+        for name in ["../calls.jsonl", "/tmp/calls.jsonl", "..", ""] {
+            assert!(log.export(&query, ExportFormat::Csv, name).is_err());
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
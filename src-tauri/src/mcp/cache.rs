@@ -0,0 +1,175 @@
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::protocol::CallToolResult;
+
+/// Most results kept at once; the oldest are dropped first
+const MAX_ENTRIES: usize = 500;
+
+/// Key in `_meta` marking a result that was served from the cache
+pub const CACHE_META_KEY: &str = "orion/cache";
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    server_id: String,
+    tool_name: String,
+    /// Arguments serialized with object keys sorted, so key order doesn't matter
+    arguments: String,
+}
+
+impl CacheKey {
+    pub fn new(server_id: &str, tool_name: &str, arguments: Option<&Value>) -> Self {
+        Self {
+            server_id: server_id.to_string(),
+            tool_name: tool_name.to_string(),
+            arguments: arguments.map(canonicalize).unwrap_or_default(),
+        }
+    }
+}
+
+fn canonicalize(value: &Value) -> String {
+    match value {
+        Value::Object(object) => {
+            let mut keys: Vec<&String> = object.keys().collect();
+            keys.sort();
+            let fields: Vec<String> = keys
+                .into_iter()
+                .map(|key| {
+                    format!(
+                        "{}:{}",
+                        Value::String(key.clone()),
+                        canonicalize(&object[key])
+                    )
+                })
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonicalize).collect();
+            format!("[{}]", items.join(","))
+        }
+        _ => value.to_string(),
+    }
+}
+
+struct CachedResult {
+    result: CallToolResult,
+    stored_at: Instant,
+    /// Change count of the server when the result was stored
+    generation: u64,
+}
+
+/// Results of read-only and idempotent tool calls, reused until they expire
+///
+/// Entries are dropped when they outlive their TTL, when the server reports that
+/// its tools or resources changed, and when the server stops.
+#[derive(Default)]
+pub struct ResultCache {
+    entries: Mutex<HashMap<CacheKey, CachedResult>>,
+}
+
+impl ResultCache {
+    /// Cached result for the key, marked as a cache hit in its `_meta`
+    ///
+    /// `generation` is the server's current change count; results stored before a
+    /// change are stale.
+    pub fn get(&self, key: &CacheKey, generation: u64, ttl: Duration) -> Option<CallToolResult> {
+        let mut entries = self.lock();
+        let cached = entries.get(key)?;
+        let age = cached.stored_at.elapsed();
+        if age >= ttl || cached.generation != generation {
+            entries.remove(key);
+            return None;
+        }
+
+        let mut result = cached.result.clone();
+        let meta = result.meta.get_or_insert_with(|| json!({}));
+        if let Some(meta) = meta.as_object_mut() {
+            meta.insert(
+                CACHE_META_KEY.to_string(),
+                json!({ "hit": true, "ageMs": age.as_millis() as u64 }),
+            );
+        }
+        Some(result)
+    }
+
+    /// Remember a result; error results are never cached
+    pub fn insert(&self, key: CacheKey, result: &CallToolResult, generation: u64) {
+        if result.is_error == Some(true) {
+            return;
+        }
+
+        let mut entries = self.lock();
+        if entries.len() >= MAX_ENTRIES && !entries.contains_key(&key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, cached)| cached.stored_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(
+            key,
+            CachedResult {
+                result: result.clone(),
+                stored_at: Instant::now(),
+                generation,
+            },
+        );
+    }
+
+    /// Drop every result of a server
+    pub fn invalidate_server(&self, server_id: &str) {
+        self.lock().retain(|key, _| key.server_id != server_id);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<CacheKey, CachedResult>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignores_argument_key_order() {
+        let a = CacheKey::new(
+            "fs",
+            "list",
+            Some(&json!({ "path": "/", "opts": { "a": 1, "b": [2] } })),
+        );
+        let b = CacheKey::new(
+            "fs",
+            "list",
+            Some(&json!({ "opts": { "b": [2], "a": 1 }, "path": "/" })),
+        );
+        assert_eq!(a, b);
+        assert_ne!(
+            a,
+            CacheKey::new("fs", "list", Some(&json!({ "path": "/tmp" })))
+        );
+    }
+
+    #[test]
+    fn expires_on_ttl_and_changes() {
+        let cache = ResultCache::default();
+        let key = CacheKey::new("fs", "list", None);
+        cache.insert(key.clone(), &CallToolResult::text("a"), 0);
+
+        let hit = cache.get(&key, 0, Duration::from_secs(60)).unwrap();
+        assert_eq!(hit.meta.unwrap()[CACHE_META_KEY]["hit"], true);
+
+        assert!(cache.get(&key, 1, Duration::from_secs(60)).is_none());
+        cache.insert(key.clone(), &CallToolResult::text("a"), 1);
+        assert!(cache.get(&key, 1, Duration::ZERO).is_none());
+
+        cache.insert(key.clone(), &CallToolResult::error("no"), 1);
+        assert!(cache.get(&key, 1, Duration::from_secs(60)).is_none());
+    }
+}
@@ -0,0 +1,39 @@
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Shared flag for abandoning work in flight, such as a generation the user stopped
+///
+/// Clones observe the same flag; once cancelled it stays cancelled.
+#[derive(Debug, Clone)]
+pub struct CancelToken {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl Default for CancelToken {
+    fn default() -> Self {
+        Self {
+            sender: Arc::new(watch::channel(false).0),
+        }
+    }
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.sender.send_replace(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.sender.borrow()
+    }
+
+    /// Wait until the token is cancelled
+    pub async fn cancelled(&self) {
+        let mut receiver = self.sender.subscribe();
+        // The sender lives as long as `self`, so this only returns once cancelled
+        let _ = receiver.wait_for(|cancelled| *cancelled).await;
+    }
+}
@@ -0,0 +1,129 @@
+use anyhow::{anyhow, Result};
+use std::path::Path;
+
+use super::protocol::McpServerConfig;
+use super::results::BUILTIN_SERVER_ID;
+use crate::store::{ConfigStore, StoredConfig};
+
+/// File name of the MCP server configuration in the app config dir
+pub const CONFIG_FILE_NAME: &str = "mcp_servers.json";
+
+/// Current version of the `mcp_servers.json` format
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Validate a server configuration before it is saved
+pub fn validate_config(config: &McpServerConfig) -> Result<()> {
+    if config.id.trim().is_empty() {
+        return Err(anyhow!("Server ID must not be empty"));
+    }
+    if config.id == BUILTIN_SERVER_ID {
+        return Err(anyhow!(
+            "Server ID '{}' is reserved for Orion's built-in tools",
+            config.id
+        ));
+    }
+    if config.name.trim().is_empty() {
+        return Err(anyhow!("Server '{}' must have a name", config.id));
+    }
+    if config.command.trim().is_empty() {
+        return Err(anyhow!("Server '{}' must have a command", config.name));
+    }
+    if config.args.iter().any(|arg| arg.contains('\0')) {
+        return Err(anyhow!(
+            "Server '{}' has an argument containing a NUL byte",
+            config.name
+        ));
+    }
+
+    if let Some(cwd) = &config.cwd {
+        if !Path::new(cwd).is_absolute() {
+            return Err(anyhow!(
+                "Server '{}' working directory must be an absolute path",
+                config.name
+            ));
+        }
+    }
+
+    if config.max_concurrent_calls == Some(0) {
+        return Err(anyhow!(
+            "Server '{}' must allow at least one concurrent call",
+            config.name
+        ));
+    }
+    if let Some(rate_limit) = &config.rate_limit {
+        if rate_limit.burst == 0
+            || !rate_limit.per_second.is_finite()
+            || rate_limit.per_second <= 0.0
+        {
+            return Err(anyhow!(
+                "Server '{}' rate limit needs a burst of at least 1 and a positive rate",
+                config.name
+            ));
+        }
+    }
+
+    if let Some(env) = &config.env {
+        for key in env.keys() {
+            if key.is_empty() || key.contains('=') || key.contains('\0') {
+                return Err(anyhow!(
+                    "Server '{}' has an invalid environment variable name '{}'",
+                    config.name,
+                    key
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl StoredConfig for McpServerConfig {
+    const LIST_KEY: &'static str = "servers";
+    const ENTRY_NAME: &'static str = "Server config";
+    const VERSION: u32 = CONFIG_VERSION;
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn validate(&self) -> Result<()> {
+        validate_config(self)
+    }
+}
+
+/// Persistent store for MCP server configurations in `mcp_servers.json`
+pub type McpConfigStore = ConfigStore<McpServerConfig>;
+
+impl McpConfigStore {
+    /// Configurations that should be started with the app
+    pub fn autostart_configs(&self) -> Result<Vec<McpServerConfig>> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter(|config| config.autostart)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sets_unreadable_configs_aside() {
+        let dir = std::env::temp_dir().join(format!("orion-config-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(CONFIG_FILE_NAME);
+        std::fs::write(&path, "{ not json").unwrap();
+
+        let store = McpConfigStore::load_or_empty(path.clone());
+        assert!(store.list().unwrap().is_empty());
+        assert!(!path.exists());
+        assert_eq!(
+            std::fs::read_to_string(dir.join("mcp_servers.json.invalid")).unwrap(),
+            "{ not json"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,78 @@
+use std::fmt;
+
+/// An MCP request did not get a response within its time limit
+#[derive(Debug, Clone)]
+pub struct TimeoutError {
+    pub method: String,
+    pub timeout_ms: u64,
+}
+
+impl fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Timeout waiting for '{}' response after {} ms",
+            self.method, self.timeout_ms
+        )
+    }
+}
+
+impl std::error::Error for TimeoutError {}
+
+/// An MCP request was abandoned through its cancel token
+#[derive(Debug, Clone)]
+pub struct CancelledError {
+    pub method: String,
+}
+
+impl fmt::Display for CancelledError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}' was cancelled", self.method)
+    }
+}
+
+impl std::error::Error for CancelledError {}
+
+/// A server sent a message larger than its configured frame size limit
+#[derive(Debug, Clone)]
+pub struct FrameTooLargeError {
+    pub size: usize,
+    pub limit: usize,
+}
+
+impl fmt::Display for FrameTooLargeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Server sent a {} byte message, over the {} byte limit (raise maxFrameBytes to allow it)",
+            self.size, self.limit
+        )
+    }
+}
+
+impl std::error::Error for FrameTooLargeError {}
+
+/// A sandboxed server failed, most likely because the sandbox blocked something
+#[derive(Debug, Clone)]
+pub struct SandboxError {
+    pub server_name: String,
+    pub cause: String,
+    /// Stderr lines that look like denied filesystem access or syscalls
+    pub denials: Vec<String>,
+}
+
+impl fmt::Display for SandboxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Server '{}' failed while sandboxed ({}); the sandbox may have blocked something it needs",
+            self.server_name, self.cause
+        )?;
+        if !self.denials.is_empty() {
+            write!(f, ": {}", self.denials.join("; "))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SandboxError {}
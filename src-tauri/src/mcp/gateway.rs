@@ -0,0 +1,324 @@
+use anyhow::{anyhow, Context, Result};
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::{oneshot, Mutex};
+
+use super::protocol::{
+    CallToolResult, JsonRpcResponse, McpTool, ServerCapabilities, ServerInfo, INTERNAL_ERROR,
+};
+use super::serve::{handle_message, serve_with, McpHandler};
+use super::server::McpServerManager;
+
+/// File in the app data directory holding the gateway's bearer token
+const TOKEN_FILE_NAME: &str = "gateway.token";
+
+/// File in the app data directory holding the running gateway's URL
+const URL_FILE_NAME: &str = "gateway.url";
+
+/// Path of the MCP endpoint on the HTTP gateway
+const ENDPOINT_PATH: &str = "/mcp";
+
+/// Path of the Prometheus metrics endpoint on the HTTP gateway
+const METRICS_PATH: &str = "/metrics";
+
+/// Proxies MCP clients to every running server of a manager
+///
+/// Tools are namespaced as `<server>__<tool>` like they are for models, and the
+/// servers' tool policies apply. Servers with `exposeViaGateway` off are hidden.
+pub struct GatewayHandler {
+    manager: McpServerManager,
+}
+
+impl GatewayHandler {
+    pub fn new(manager: McpServerManager) -> Self {
+        Self { manager }
+    }
+}
+
+impl McpHandler for GatewayHandler {
+    fn server_info(&self) -> ServerInfo {
+        ServerInfo {
+            name: "orion-gateway".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+
+    fn capabilities(&self) -> ServerCapabilities {
+        ServerCapabilities {
+            experimental: None,
+            logging: None,
+            prompts: None,
+            resources: None,
+            tools: Some(json!({})),
+        }
+    }
+
+    async fn list_tools(&self) -> Result<Vec<McpTool>> {
+        Ok(self.manager.list_gateway_tools().await)
+    }
+
+    async fn call_tool(&self, name: &str, arguments: Option<Value>) -> Result<CallToolResult> {
+        self.manager.call_gateway_tool(name, arguments).await
+    }
+}
+
+/// Where a running HTTP gateway can be reached
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GatewayStatus {
+    pub url: String,
+    /// Sent by clients as `Authorization: Bearer <token>`
+    pub token: String,
+}
+
+struct RunningGateway {
+    status: GatewayStatus,
+    shutdown: oneshot::Sender<()>,
+}
+
+/// HTTP MCP endpoint on localhost sharing the app's running servers
+///
+/// Accepts JSON-RPC messages POSTed to `/mcp` and answers with JSON, and serves
+/// the manager's metrics for Prometheus at `/metrics`. The bearer token is kept in
+/// the app data directory so client configs stay valid across restarts, next to
+/// the URL while the gateway runs so `orion-mcp --gateway` can find it.
+pub struct McpGateway {
+    token_path: PathBuf,
+    url_path: PathBuf,
+    running: Mutex<Option<RunningGateway>>,
+}
+
+struct HttpState {
+    handler: GatewayHandler,
+    token: String,
+}
+
+impl HttpState {
+    /// Reject requests without the bearer token, and any coming from a browser
+    fn check(&self, headers: &HeaderMap) -> Result<(), StatusCode> {
+        let authorized = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|token| token == self.token);
+        if !authorized {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+
+        // Browsers always send Origin; refuse web pages even if they got hold of the token
+        if headers.contains_key(header::ORIGIN) {
+            return Err(StatusCode::FORBIDDEN);
+        }
+        Ok(())
+    }
+}
+
+impl McpGateway {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            token_path: data_dir.join(TOKEN_FILE_NAME),
+            url_path: data_dir.join(URL_FILE_NAME),
+            running: Mutex::new(None),
+        }
+    }
+
+    /// Start serving on `127.0.0.1:port`; port 0 picks a free port
+    pub async fn start(&self, manager: McpServerManager, port: u16) -> Result<GatewayStatus> {
+        let mut running = self.running.lock().await;
+        if running.is_some() {
+            return Err(anyhow!("MCP gateway is already running"));
+        }
+
+        let token = self.load_or_create_token()?;
+        let listener = tokio::net::TcpListener::bind(("127.0.0.1", port))
+            .await
+            .with_context(|| format!("Failed to listen on port {}", port))?;
+        let address = listener.local_addr()?;
+        let url = format!("http://{}{}", address, ENDPOINT_PATH);
+        std::fs::write(&self.url_path, &url)
+            .with_context(|| format!("Failed to write {}", self.url_path.display()))?;
+
+        let state = Arc::new(HttpState {
+            handler: GatewayHandler::new(manager),
+            token: token.clone(),
+        });
+        let router = Router::new()
+            .route(ENDPOINT_PATH, post(handle_post))
+            .route(METRICS_PATH, get(handle_metrics))
+            .with_state(state);
+
+        let (shutdown, shutdown_rx) = oneshot::channel::<()>();
+        tokio::spawn(async move {
+            let server = axum::serve(listener, router).with_graceful_shutdown(async {
+                let _ = shutdown_rx.await;
+            });
+            if let Err(e) = server.await {
+                eprintln!("MCP gateway stopped: {}", e);
+            }
+        });
+
+        let status = GatewayStatus { url, token };
+        *running = Some(RunningGateway {
+            status: status.clone(),
+            shutdown,
+        });
+        Ok(status)
+    }
+
+    /// Stop serving; the proxied servers keep running
+    pub async fn stop(&self) -> Result<()> {
+        let running = self
+            .running
+            .lock()
+            .await
+            .take()
+            .ok_or_else(|| anyhow!("MCP gateway is not running"))?;
+        let _ = running.shutdown.send(());
+        let _ = std::fs::remove_file(&self.url_path);
+        Ok(())
+    }
+
+    pub async fn status(&self) -> Option<GatewayStatus> {
+        self.running
+            .lock()
+            .await
+            .as_ref()
+            .map(|running| running.status.clone())
+    }
+
+    fn load_or_create_token(&self) -> Result<String> {
+        if let Ok(token) = std::fs::read_to_string(&self.token_path) {
+            let token = token.trim();
+            if !token.is_empty() {
+                return Ok(token.to_string());
+            }
+        }
+
+        let bytes: [u8; 32] = rand::random();
+        let token: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+
+        if let Some(parent) = self.token_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.token_path, &token)
+            .with_context(|| format!("Failed to write {}", self.token_path.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&self.token_path, std::fs::Permissions::from_mode(0o600))?;
+        }
+
+        Ok(token)
+    }
+}
+
+/// Forwards MCP messages to the HTTP gateway of the running app
+///
+/// Lets stdio-only clients share the app's running servers instead of starting
+/// their own copies.
+pub struct GatewayClient {
+    http: reqwest::Client,
+    url: String,
+    token: String,
+}
+
+impl GatewayClient {
+    /// Client for the gateway of the app using `data_dir`
+    pub fn connect(data_dir: &Path) -> Result<Self> {
+        let read = |file_name: &str| {
+            std::fs::read_to_string(data_dir.join(file_name))
+                .ok()
+                .map(|contents| contents.trim().to_string())
+                .filter(|contents| !contents.is_empty())
+        };
+        let (Some(url), Some(token)) = (read(URL_FILE_NAME), read(TOKEN_FILE_NAME)) else {
+            return Err(anyhow!(
+                "Orion's MCP gateway is not running; start it from the app first"
+            ));
+        };
+        Ok(Self {
+            http: reqwest::Client::new(),
+            url,
+            token,
+        })
+    }
+
+    /// Forward one JSON-RPC message; `None` for notifications
+    ///
+    /// Failing to reach the gateway is answered as an error to the request.
+    pub async fn forward(&self, message: String) -> Option<JsonRpcResponse> {
+        let id = serde_json::from_str::<Value>(&message)
+            .ok()
+            .and_then(|message| message.get("id").cloned())
+            .unwrap_or(Value::Null);
+        match self.post(message).await {
+            Ok(response) => response,
+            Err(e) if id.is_null() => {
+                eprintln!("Failed to forward notification: {}", e);
+                None
+            }
+            Err(e) => Some(JsonRpcResponse::failure(id, INTERNAL_ERROR, e.to_string())),
+        }
+    }
+
+    async fn post(&self, message: String) -> Result<Option<JsonRpcResponse>> {
+        let response = self
+            .http
+            .post(&self.url)
+            .bearer_auth(&self.token)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(message)
+            .send()
+            .await
+            .context("Failed to reach Orion's MCP gateway; is the app still running?")?;
+        match response.status() {
+            StatusCode::ACCEPTED => Ok(None),
+            status if status.is_success() => Ok(Some(response.json().await?)),
+            status => Err(anyhow!("Orion's MCP gateway answered {}", status)),
+        }
+    }
+
+    /// Forward MCP on the process's stdin and stdout until the client disconnects
+    pub async fn proxy_stdio(self) -> Result<()> {
+        let client = Arc::new(self);
+        serve_with(tokio::io::stdin(), tokio::io::stdout(), move |message| {
+            let client = client.clone();
+            async move { client.forward(message).await }
+        })
+        .await
+    }
+}
+
+async fn handle_post(
+    State(state): State<Arc<HttpState>>,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    if let Err(status) = state.check(&headers) {
+        return status.into_response();
+    }
+
+    match handle_message(&state.handler, &body).await {
+        Some(response) => Json(response).into_response(),
+        None => StatusCode::ACCEPTED.into_response(),
+    }
+}
+
+async fn handle_metrics(State(state): State<Arc<HttpState>>, headers: HeaderMap) -> Response {
+    if let Err(status) = state.check(&headers) {
+        return status.into_response();
+    }
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.handler.manager.metrics().to_prometheus(),
+    )
+        .into_response()
+}
@@ -0,0 +1,454 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use super::protocol::{
+    CallToolResult, GetPromptResult, McpTool, Prompt, PromptArgument, PromptMessage,
+    ReadResourceResult, Resource, ResourceContents, ServerCapabilities, ServerInfo,
+    ToolAnnotations, ToolResultContent,
+};
+use super::serve::McpHandler;
+use crate::commands::storage::{
+    read_json, Conversation, SavedPrompt, Settings, CONVERSATIONS_FILE_NAME, PROMPTS_FILE_NAME,
+    SETTINGS_FILE_NAME,
+};
+
+const CONVERSATION_URI_PREFIX: &str = "orion://conversations/";
+const SETTINGS_URI: &str = "orion://settings";
+
+/// Characters of context shown on each side of a search match
+const SNIPPET_CONTEXT: usize = 80;
+const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+/// Serves Orion's conversation history, settings and saved prompts over MCP
+///
+/// Storage files are re-read on every request, so changes made by the running app
+/// are visible without restarting the sidecar.
+pub struct HistoryHandler {
+    data_dir: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct SearchArgs {
+    query: String,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+impl HistoryHandler {
+    pub fn new(data_dir: PathBuf) -> Self {
+        Self { data_dir }
+    }
+
+    fn conversations(&self) -> Result<Vec<Conversation>> {
+        Ok(read_json(&self.data_dir.join(CONVERSATIONS_FILE_NAME))?.unwrap_or_default())
+    }
+
+    fn prompts(&self) -> Result<Vec<SavedPrompt>> {
+        Ok(read_json(&self.data_dir.join(PROMPTS_FILE_NAME))?.unwrap_or_default())
+    }
+
+    fn search(&self, args: SearchArgs) -> Result<CallToolResult> {
+        let query = args.query.trim().to_lowercase();
+        if query.is_empty() {
+            return Ok(CallToolResult::error("Query must not be empty"));
+        }
+        let limit = args.limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+
+        let mut conversations = self.conversations()?;
+        conversations.sort_by_key(|conversation| std::cmp::Reverse(conversation.updated_at));
+
+        let mut hits = Vec::new();
+        for conversation in &conversations {
+            let title_match = conversation.title.to_lowercase().contains(&query);
+            let snippets: Vec<Value> = conversation
+                .messages
+                .iter()
+                .filter_map(|message| {
+                    let text = snippet(&message.content, &query)?;
+                    Some(json!({
+                        "messageId": message.id,
+                        "role": message.role,
+                        "snippet": text,
+                    }))
+                })
+                .take(3)
+                .collect();
+
+            if title_match || !snippets.is_empty() {
+                hits.push(json!({
+                    "uri": format!("{}{}", CONVERSATION_URI_PREFIX, conversation.id),
+                    "title": conversation.title,
+                    "updatedAt": conversation.updated_at,
+                    "matches": snippets,
+                }));
+            }
+            if hits.len() >= limit {
+                break;
+            }
+        }
+
+        Ok(CallToolResult::text(serde_json::to_string_pretty(&hits)?))
+    }
+}
+
+impl McpHandler for HistoryHandler {
+    fn server_info(&self) -> ServerInfo {
+        ServerInfo {
+            name: "orion".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+
+    fn capabilities(&self) -> ServerCapabilities {
+        ServerCapabilities {
+            experimental: None,
+            logging: None,
+            prompts: Some(json!({})),
+            resources: Some(json!({})),
+            tools: Some(json!({})),
+        }
+    }
+
+    async fn list_tools(&self) -> Result<Vec<McpTool>> {
+        Ok(vec![McpTool {
+            name: "search_conversations".to_string(),
+            description: "Search Orion conversation titles and messages. Returns matching conversations with their resource URIs and message snippets, most recent first.".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "Case-insensitive text to search for" },
+                    "limit": { "type": "integer", "minimum": 1, "description": "Maximum number of conversations to return" }
+                },
+                "required": ["query"]
+            }),
+            annotations: Some(ToolAnnotations {
+                read_only_hint: Some(true),
+                ..ToolAnnotations::default()
+            }),
+        }])
+    }
+
+    async fn call_tool(&self, name: &str, arguments: Option<Value>) -> Result<CallToolResult> {
+        match name {
+            "search_conversations" => {
+                let args: SearchArgs = serde_json::from_value(arguments.unwrap_or(Value::Null))
+                    .map_err(|e| anyhow!("Invalid arguments: {}", e))?;
+                self.search(args)
+            }
+            _ => Err(anyhow!("Unknown tool '{}'", name)),
+        }
+    }
+
+    async fn list_resources(&self) -> Result<Vec<Resource>> {
+        let mut resources = vec![Resource {
+            uri: SETTINGS_URI.to_string(),
+            name: "Settings".to_string(),
+            description: Some("Orion settings; the API key is never included".to_string()),
+            mime_type: Some("application/json".to_string()),
+        }];
+        resources.extend(
+            self.conversations()?
+                .into_iter()
+                .map(|conversation| Resource {
+                    uri: format!("{}{}", CONVERSATION_URI_PREFIX, conversation.id),
+                    name: conversation.title,
+                    description: Some(format!(
+                        "{} messages with {}",
+                        conversation.messages.len(),
+                        conversation.model
+                    )),
+                    mime_type: Some("text/markdown".to_string()),
+                }),
+        );
+        Ok(resources)
+    }
+
+    async fn read_resource(&self, uri: &str) -> Result<ReadResourceResult> {
+        let (mime_type, text) = if uri == SETTINGS_URI {
+            let settings: Option<Settings> = read_json(&self.data_dir.join(SETTINGS_FILE_NAME))?;
+            let settings = settings.map(|settings| {
+                json!({
+                    "selectedModel": settings.selected_model,
+                })
+            });
+            ("application/json", serde_json::to_string_pretty(&settings)?)
+        } else if let Some(id) = uri.strip_prefix(CONVERSATION_URI_PREFIX) {
+            let conversation = self
+                .conversations()?
+                .into_iter()
+                .find(|conversation| conversation.id == id)
+                .ok_or_else(|| anyhow!("Conversation '{}' not found", id))?;
+            ("text/markdown", transcript(&conversation))
+        } else {
+            return Err(anyhow!("Unknown resource '{}'", uri));
+        };
+
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents {
+                uri: uri.to_string(),
+                mime_type: Some(mime_type.to_string()),
+                text,
+            }],
+        })
+    }
+
+    async fn list_prompts(&self) -> Result<Vec<Prompt>> {
+        Ok(self
+            .prompts()?
+            .into_iter()
+            .map(|prompt| Prompt {
+                arguments: placeholders(&prompt.content)
+                    .into_iter()
+                    .map(|name| PromptArgument {
+                        name,
+                        description: None,
+                        required: Some(true),
+                    })
+                    .collect(),
+                name: prompt.name,
+                description: prompt.description,
+            })
+            .collect())
+    }
+
+    async fn get_prompt(
+        &self,
+        name: &str,
+        arguments: HashMap<String, String>,
+    ) -> Result<GetPromptResult> {
+        let prompt = self
+            .prompts()?
+            .into_iter()
+            .find(|prompt| prompt.name == name)
+            .ok_or_else(|| anyhow!("Unknown prompt '{}'", name))?;
+
+        let mut text = prompt.content;
+        for argument in placeholders(&text) {
+            let value = arguments
+                .get(&argument)
+                .ok_or_else(|| anyhow!("Missing argument '{}'", argument))?;
+            text = text.replace(&format!("{{{{{}}}}}", argument), value);
+        }
+
+        Ok(GetPromptResult {
+            description: prompt.description,
+            messages: vec![PromptMessage {
+                role: "user".to_string(),
+                content: ToolResultContent::text(text),
+            }],
+        })
+    }
+}
+
+/// Render a conversation as a markdown transcript
+fn transcript(conversation: &Conversation) -> String {
+    let mut text = format!(
+        "# {}\n\nModel: {}\n",
+        conversation.title, conversation.model
+    );
+    for message in &conversation.messages {
+        text.push_str(&format!("\n## {}\n\n{}\n", message.role, message.content));
+    }
+    text
+}
+
+/// Excerpt around the first case-insensitive match of `query` (already lowercased)
+fn snippet(content: &str, query: &str) -> Option<String> {
+    // Lowercasing can change byte lengths, so search per character
+    let chars: Vec<char> = content.chars().collect();
+    let lower: Vec<char> = chars.iter().flat_map(|c| c.to_lowercase()).collect();
+    let query: Vec<char> = query.chars().collect();
+    if lower.len() != chars.len() {
+        // Rare multi-character lowercase forms; fall back to the start of the message
+        return content
+            .to_lowercase()
+            .contains(&query.iter().collect::<String>())
+            .then(|| chars.iter().take(SNIPPET_CONTEXT * 2).collect());
+    }
+
+    let start = lower
+        .windows(query.len())
+        .position(|window| window == query)?;
+    let from = start.saturating_sub(SNIPPET_CONTEXT);
+    let to = (start + query.len() + SNIPPET_CONTEXT).min(chars.len());
+
+    let mut text: String = chars[from..to].iter().collect();
+    if from > 0 {
+        text.insert(0, '…');
+    }
+    if to < chars.len() {
+        text.push('…');
+    }
+    Some(text)
+}
+
+/// Unique `{{name}}` placeholders in a prompt template, in order of appearance
+fn placeholders(template: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find("}}") else {
+            break;
+        };
+        let name = &rest[..end];
+        if !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+            && !names.iter().any(|existing| existing == name)
+        {
+            names.push(name.to_string());
+        }
+        rest = &rest[end + 2..];
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: &str, content: &str) -> Value {
+        json!({ "id": id, "role": "user", "content": content, "timestamp": 1 })
+    }
+
+    fn handler(name: &str) -> HistoryHandler {
+        let dir = std::env::temp_dir().join(format!(
+            "orion-history-test-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let conversations = json!([
+            {
+                "id": "old", "title": "Rust lifetimes", "createdAt": 1, "updatedAt": 1,
+                "model": "m", "messages": [message("a", "Why does the borrow checker complain?")]
+            },
+            {
+                "id": "new", "title": "Dinner", "createdAt": 2, "updatedAt": 2,
+                "model": "m", "messages": [message("b", "A recipe that goes with the Borrow wine")]
+            }
+        ]);
+        std::fs::write(dir.join(CONVERSATIONS_FILE_NAME), conversations.to_string()).unwrap();
+        let settings = json!({ "selected_model": "m", "api_key": "sk-secret" });
+        std::fs::write(dir.join(SETTINGS_FILE_NAME), settings.to_string()).unwrap();
+        let prompts = json!([
+            { "id": "1", "name": "translate", "content": "Translate {{text}} to {{language}}: {{text}}" }
+        ]);
+        std::fs::write(dir.join(PROMPTS_FILE_NAME), prompts.to_string()).unwrap();
+
+        HistoryHandler::new(dir)
+    }
+
+    fn text(result: &CallToolResult) -> &str {
+        result.content[0].text.as_deref().unwrap()
+    }
+
+    #[tokio::test]
+    async fn searches_recent_conversations_first() {
+        let handler = handler("search");
+
+        let result = handler
+            .call_tool("search_conversations", Some(json!({ "query": "BORROW" })))
+            .await
+            .unwrap();
+        let hits: Vec<Value> = serde_json::from_str(text(&result)).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0]["uri"], "orion://conversations/new");
+        assert_eq!(
+            hits[0]["matches"][0]["snippet"],
+            "A recipe that goes with the Borrow wine"
+        );
+
+        let result = handler
+            .call_tool(
+                "search_conversations",
+                Some(json!({ "query": "lifetimes", "limit": 1 })),
+            )
+            .await
+            .unwrap();
+        let hits: Vec<Value> = serde_json::from_str(text(&result)).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0]["uri"], "orion://conversations/old");
+        assert_eq!(hits[0]["matches"], json!([]));
+
+        let result = handler
+            .call_tool("search_conversations", Some(json!({ "query": " " })))
+            .await
+            .unwrap();
+        assert_eq!(result.is_error, Some(true));
+    }
+
+    #[tokio::test]
+    async fn reads_settings_without_the_key_and_transcripts() {
+        let handler = handler("resources");
+
+        let uris: Vec<String> = handler
+            .list_resources()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|resource| resource.uri)
+            .collect();
+        assert_eq!(
+            uris,
+            [
+                SETTINGS_URI,
+                "orion://conversations/old",
+                "orion://conversations/new"
+            ]
+        );
+
+        let settings = handler.read_resource(SETTINGS_URI).await.unwrap();
+        assert!(!settings.contents[0].text.contains("sk-secret"));
+        assert!(settings.contents[0].text.contains("selectedModel"));
+
+        let transcript = handler
+            .read_resource("orion://conversations/old")
+            .await
+            .unwrap();
+        assert_eq!(
+            transcript.contents[0].text,
+            "# Rust lifetimes\n\nModel: m\n\n## user\n\nWhy does the borrow checker complain?\n"
+        );
+
+        assert!(handler
+            .read_resource("orion://conversations/gone")
+            .await
+            .is_err());
+        assert!(handler.read_resource("orion://elsewhere").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn fills_prompt_placeholders() {
+        let handler = handler("prompts");
+
+        let prompts = handler.list_prompts().await.unwrap();
+        let arguments: Vec<&str> = prompts[0]
+            .arguments
+            .iter()
+            .map(|argument| argument.name.as_str())
+            .collect();
+        assert_eq!(arguments, ["text", "language"]);
+
+        let arguments = HashMap::from([
+            ("text".to_string(), "hello".to_string()),
+            ("language".to_string(), "French".to_string()),
+        ]);
+        let prompt = handler.get_prompt("translate", arguments).await.unwrap();
+        assert_eq!(
+            prompt.messages[0].content.text.as_deref(),
+            Some("Translate hello to French: hello")
+        );
+
+        let missing = HashMap::from([("text".to_string(), "hello".to_string())]);
+        assert!(handler.get_prompt("translate", missing).await.is_err());
+        assert!(handler.get_prompt("unknown", HashMap::new()).await.is_err());
+    }
+}
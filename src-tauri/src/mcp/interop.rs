@@ -0,0 +1,612 @@
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::protocol::{
+    default_max_frame_bytes, CacheConfig, InheritEnv, McpServerConfig, McpTimeouts, ResultLimits,
+};
+use super::secrets::{escape_literal, LITERAL_PREFIX};
+
+/// Config file formats that can be imported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigFormat {
+    /// `claude_desktop_config.json`
+    ClaudeDesktop,
+    /// `.cursor/mcp.json`
+    Cursor,
+    /// VS Code `mcp.json` or the `mcp` section of `settings.json`
+    VsCode,
+}
+
+/// A problem with a single server entry
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportIssue {
+    pub name: String,
+    pub message: String,
+}
+
+/// Result of importing a config file
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportReport {
+    pub format: ConfigFormat,
+    pub servers: Vec<McpServerConfig>,
+    /// Entries that were converted but need attention (e.g. unresolved inputs)
+    pub warnings: Vec<ImportIssue>,
+    /// Entries that could not be converted
+    pub skipped: Vec<ImportIssue>,
+}
+
+/// Context for resolving `${...}` placeholders
+struct ImportContext {
+    workspace_folder: Option<PathBuf>,
+    inputs: HashMap<String, String>,
+    warnings: Vec<ImportIssue>,
+}
+
+impl ImportContext {
+    fn warn(&mut self, name: &str, message: String) {
+        self.warnings.push(ImportIssue {
+            name: name.to_string(),
+            message,
+        });
+    }
+
+    /// Replace `${env:..}`, `${workspaceFolder}` and `${userHome}` placeholders
+    ///
+    /// Fails on `${input:..}`, which can only be kept as a vault reference when it
+    /// is a whole environment value.
+    fn substitute(&mut self, name: &str, value: &str) -> std::result::Result<String, String> {
+        let mut result = String::with_capacity(value.len());
+        let mut rest = value;
+
+        while let Some(start) = rest.find("${") {
+            let Some(len) = rest[start..].find('}') else {
+                break;
+            };
+            result.push_str(&rest[..start]);
+            let placeholder = &rest[start + 2..start + len];
+            result.push_str(&self.resolve_placeholder(name, placeholder)?);
+            rest = &rest[start + len + 1..];
+        }
+
+        result.push_str(rest);
+        Ok(result)
+    }
+
+    /// Convert an environment value, keeping inputs and variables as vault references
+    ///
+    /// A value that is exactly `${input:ID}` becomes `secret:ID` and `${env:VAR}`
+    /// becomes `env:VAR`, so credentials are resolved at spawn time instead of being
+    /// copied into the imported config. Other values that look like references are
+    /// escaped so they stay plain values.
+    fn env_value(&mut self, name: &str, value: &str) -> std::result::Result<String, String> {
+        let placeholder = value
+            .strip_prefix("${")
+            .and_then(|rest| rest.strip_suffix('}'))
+            .filter(|placeholder| !placeholder.contains('}'));
+
+        match placeholder {
+            Some(placeholder) if placeholder.starts_with("input:") => {
+                let input_id = &placeholder["input:".len()..];
+                self.warn(
+                    name,
+                    format!(
+                        "Store secret '{}' in the vault before starting the server",
+                        input_id
+                    ),
+                );
+                Ok(format!("secret:{}", input_id))
+            }
+            Some(placeholder) if placeholder.starts_with("env:") => Ok(placeholder.to_string()),
+            _ => Ok(escape_literal(&self.substitute(name, value)?)),
+        }
+    }
+
+    fn resolve_placeholder(
+        &mut self,
+        name: &str,
+        placeholder: &str,
+    ) -> std::result::Result<String, String> {
+        if let Some(input_id) = placeholder.strip_prefix("input:") {
+            let description = self
+                .inputs
+                .get(input_id)
+                .map(|description| format!(" ({})", description))
+                .unwrap_or_default();
+            return Err(format!(
+                "Input '{}'{} can only be imported as a whole environment value",
+                input_id, description
+            ));
+        }
+
+        let var = placeholder.strip_prefix("env:").unwrap_or(placeholder);
+        Ok(match var {
+            "workspaceFolder" => match &self.workspace_folder {
+                Some(folder) => folder.display().to_string(),
+                None => {
+                    self.warn(name, "Could not resolve ${workspaceFolder}".to_string());
+                    String::new()
+                }
+            },
+            "userHome" => home_dir().unwrap_or_default(),
+            "pathSeparator" => std::path::MAIN_SEPARATOR.to_string(),
+            _ => match std::env::var(var) {
+                Ok(value) => value,
+                Err(_) => {
+                    self.warn(
+                        name,
+                        format!("Environment variable '{}' is not set", var),
+                    );
+                    String::new()
+                }
+            },
+        })
+    }
+}
+
+fn home_dir() -> Option<String> {
+    std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok()
+}
+
+/// Import MCP server definitions from a Claude Desktop, Cursor or VS Code config file
+pub fn import_file(path: &Path) -> Result<ImportReport> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    import_str(&contents, path)
+}
+
+/// Import MCP server definitions from config file contents
+///
+/// `path` is used to tell Cursor configs apart from Claude Desktop ones and to
+/// resolve `${workspaceFolder}`.
+pub fn import_str(contents: &str, path: &Path) -> Result<ImportReport> {
+    let root: Value = serde_json::from_str(&strip_jsonc(contents))
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    let in_dot_dir = |dir: &str| {
+        path.parent()
+            .and_then(Path::file_name)
+            .is_some_and(|name| name == dir)
+    };
+
+    let (format, entries) = if let Some(entries) = root.get("mcpServers") {
+        let format = if in_dot_dir(".cursor") {
+            ConfigFormat::Cursor
+        } else {
+            ConfigFormat::ClaudeDesktop
+        };
+        (format, entries)
+    } else if let Some(entries) = root.get("servers") {
+        (ConfigFormat::VsCode, entries)
+    } else if let Some(entries) = root.get("mcp").and_then(|mcp| mcp.get("servers")) {
+        (ConfigFormat::VsCode, entries)
+    } else {
+        return Err(anyhow!(
+            "{} does not contain an 'mcpServers' or 'servers' section",
+            path.display()
+        ));
+    };
+
+    let entries = entries
+        .as_object()
+        .ok_or_else(|| anyhow!("Server list in {} is not an object", path.display()))?;
+
+    // Workspace configs live in `<workspace>/.vscode` or `<workspace>/.cursor`
+    let workspace_folder = if in_dot_dir(".vscode") || in_dot_dir(".cursor") {
+        path.parent().and_then(Path::parent).map(Path::to_path_buf)
+    } else {
+        None
+    };
+
+    let inputs_value = root
+        .get("inputs")
+        .or_else(|| root.get("mcp").and_then(|mcp| mcp.get("inputs")));
+    let inputs = inputs_value
+        .and_then(Value::as_array)
+        .map(|inputs| {
+            inputs
+                .iter()
+                .filter_map(|input| {
+                    let id = input.get("id")?.as_str()?;
+                    let description = input
+                        .get("description")
+                        .and_then(Value::as_str)
+                        .unwrap_or(id);
+                    Some((id.to_string(), description.to_string()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut ctx = ImportContext {
+        workspace_folder,
+        inputs,
+        warnings: Vec::new(),
+    };
+    let mut servers = Vec::new();
+    let mut skipped = Vec::new();
+    let mut used_ids = HashSet::new();
+
+    for (name, entry) in entries {
+        match convert_entry(name, entry, &mut ctx) {
+            Ok(mut config) => {
+                config.id = unique_id(name, &mut used_ids);
+                servers.push(config);
+            }
+            Err(message) => skipped.push(ImportIssue {
+                name: name.clone(),
+                message,
+            }),
+        }
+    }
+
+    Ok(ImportReport {
+        format,
+        servers,
+        warnings: ctx.warnings,
+        skipped,
+    })
+}
+
+/// Convert a single server entry, returning a reason when it cannot be converted
+fn convert_entry(
+    name: &str,
+    entry: &Value,
+    ctx: &mut ImportContext,
+) -> std::result::Result<McpServerConfig, String> {
+    let entry = entry
+        .as_object()
+        .ok_or_else(|| "Entry is not an object".to_string())?;
+
+    let server_type = entry.get("type").and_then(Value::as_str);
+    let env = string_map(entry.get("env"), "env")?
+        .into_iter()
+        .map(|(key, value)| Ok((key, ctx.env_value(name, &value)?)))
+        .collect::<std::result::Result<HashMap<_, _>, String>>()?;
+
+    if entry.contains_key("envFile") {
+        ctx.warn(
+            name,
+            "'envFile' is not supported; add those variables manually".to_string(),
+        );
+    }
+
+    let (command, args) = if let Some(command) = entry.get("command") {
+        if matches!(server_type, Some(t) if t != "stdio") {
+            return Err(format!("Unsupported server type '{}'", server_type.unwrap_or_default()));
+        }
+
+        let command = command
+            .as_str()
+            .ok_or_else(|| "'command' is not a string".to_string())?;
+        let args = match entry.get("args") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Array(args)) => args
+                .iter()
+                .map(|arg| {
+                    arg.as_str()
+                        .ok_or_else(|| "'args' contains a non-string value".to_string())
+                        .and_then(|arg| ctx.substitute(name, arg))
+                })
+                .collect::<std::result::Result<Vec<_>, _>>()?,
+            Some(_) => return Err("'args' is not an array".to_string()),
+        };
+
+        (ctx.substitute(name, command)?, args)
+    } else if let Some(url) = entry.get("url").or_else(|| entry.get("serverUrl")) {
+        let url = url
+            .as_str()
+            .ok_or_else(|| "'url' is not a string".to_string())?;
+
+        // Remote servers are bridged to stdio through mcp-remote
+        let mut args = vec![
+            "-y".to_string(),
+            "mcp-remote".to_string(),
+            ctx.substitute(name, url)?,
+        ];
+        for (header, value) in string_map(entry.get("headers"), "headers")? {
+            args.push("--header".to_string());
+            args.push(format!("{}: {}", header, ctx.substitute(name, &value)?));
+        }
+        ctx.warn(
+            name,
+            "Remote server is bridged through 'npx mcp-remote'".to_string(),
+        );
+
+        ("npx".to_string(), args)
+    } else {
+        return Err("Entry has neither 'command' nor 'url'".to_string());
+    };
+
+    Ok(McpServerConfig {
+        id: String::new(),
+        name: name.to_string(),
+        command,
+        args,
+        env: (!env.is_empty()).then_some(env),
+        timeouts: McpTimeouts::default(),
+        autostart: false,
+        cwd: None,
+        inherit_env: InheritEnv::default(),
+        extra_path: Vec::new(),
+        sandbox: None,
+        max_frame_bytes: default_max_frame_bytes(),
+        disabled_tools: Vec::new(),
+        expose_via_gateway: true,
+        record_traffic: false,
+        max_concurrent_calls: None,
+        rate_limit: None,
+        result_limits: ResultLimits::default(),
+        cache: CacheConfig::default(),
+    })
+}
+
+/// Read an object of scalar values as strings
+fn string_map(
+    value: Option<&Value>,
+    field: &str,
+) -> std::result::Result<Vec<(String, String)>, String> {
+    match value {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(Value::Object(map)) => map
+            .iter()
+            .map(|(key, value)| match value {
+                Value::String(s) => Ok((key.clone(), s.clone())),
+                Value::Number(_) | Value::Bool(_) => Ok((key.clone(), value.to_string())),
+                _ => Err(format!("'{}.{}' is not a string", field, key)),
+            })
+            .collect(),
+        Some(_) => Err(format!("'{}' is not an object", field)),
+    }
+}
+
+/// Derive a server ID from its name, unique within one import
+fn unique_id(name: &str, used: &mut HashSet<String>) -> String {
+    let slug: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let slug = slug.trim_matches('-');
+    let base = if slug.is_empty() { "server" } else { slug };
+
+    let mut id = format!("imported-{}", base);
+    let mut n = 2;
+    while used.contains(&id) {
+        id = format!("imported-{}-{}", base, n);
+        n += 1;
+    }
+    used.insert(id.clone());
+    id
+}
+
+/// Export server configurations in the Claude Desktop `mcpServers` format
+///
+/// Environment values are written as saved, except for escaped plain values:
+/// `secret:` and `env:` references stay references, but plain values,
+/// credentials included, end up in plain text.
+pub fn export_claude_desktop(servers: &[McpServerConfig]) -> Value {
+    let mut entries = Map::new();
+
+    for server in servers {
+        let mut name = server.name.clone();
+        let mut n = 2;
+        while entries.contains_key(&name) {
+            name = format!("{} ({})", server.name, n);
+            n += 1;
+        }
+
+        let mut entry = json!({
+            "command": server.command,
+            "args": server.args,
+        });
+        if let Some(env) = server.env.as_ref().filter(|env| !env.is_empty()) {
+            let env: Map<String, Value> = env
+                .iter()
+                .map(|(key, value)| {
+                    let value = value.strip_prefix(LITERAL_PREFIX).unwrap_or(value);
+                    (key.clone(), json!(value))
+                })
+                .collect();
+            entry["env"] = Value::Object(env);
+        }
+        entries.insert(name, entry);
+    }
+
+    json!({ "mcpServers": entries })
+}
+
+/// Strip `//` and `/* */` comments and trailing commas, as allowed in VS Code config files
+fn strip_jsonc(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            output.push(c);
+            if c == '\\' {
+                if let Some(escaped) = chars.next() {
+                    output.push(escaped);
+                }
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                output.push(c);
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        output.push('\n');
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = '\0';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            '}' | ']' => {
+                let trimmed = output.trim_end().len();
+                if output[..trimmed].ends_with(',') {
+                    output.truncate(trimmed - 1);
+                }
+                output.push(c);
+            }
+            _ => output.push(c),
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn import(contents: &str, path: &str) -> ImportReport {
+        import_str(contents, Path::new(path)).unwrap()
+    }
+
+    #[test]
+    fn strips_comments_and_trailing_commas_outside_strings() {
+        let stripped = strip_jsonc(
+            r#"{
+                // a comment
+                "url": "http://example.com/*not a comment*/", /* a comment */
+                "quote": "say \"//hi\"",
+                "list": [1, 2,],
+            }"#,
+        );
+        let value: Value = serde_json::from_str(&stripped).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "url": "http://example.com/*not a comment*/",
+                "quote": "say \"//hi\"",
+                "list": [1, 2],
+            })
+        );
+    }
+
+    #[test]
+    fn imports_claude_desktop_configs() {
+        let report = import(
+            r#"{"mcpServers": {
+                "Files": {"command": "npx", "args": ["-y", "server-fs"], "env": {"DEBUG": 1}},
+                "Remote": {"url": "https://example.com/mcp", "headers": {"X-Team": "orion"}},
+                "Broken": {"args": []}
+            }}"#,
+            "/home/me/claude_desktop_config.json",
+        );
+        assert_eq!(report.format, ConfigFormat::ClaudeDesktop);
+
+        let files = &report.servers[0];
+        assert_eq!(files.id, "imported-files");
+        assert_eq!(files.command, "npx");
+        assert_eq!(files.args, ["-y", "server-fs"]);
+        assert_eq!(files.env.as_ref().unwrap()["DEBUG"], "1");
+
+        let remote = &report.servers[1];
+        assert_eq!(
+            remote.args,
+            [
+                "-y",
+                "mcp-remote",
+                "https://example.com/mcp",
+                "--header",
+                "X-Team: orion"
+            ]
+        );
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].name, "Broken");
+    }
+
+    #[test]
+    fn imports_cursor_and_vs_code_configs() {
+        let cursor = import(
+            r#"{"mcpServers": {"Docs": {"command": "docs", "args": ["${workspaceFolder}/docs"]}}}"#,
+            "/work/app/.cursor/mcp.json",
+        );
+        assert_eq!(cursor.format, ConfigFormat::Cursor);
+        assert_eq!(cursor.servers[0].args, ["/work/app/docs"]);
+
+        let vs_code = import(
+            r#"{"servers": {"Db": {"type": "stdio", "command": "db"}, "Web": {"type": "sse", "command": "web"}}}"#,
+            "/work/app/.vscode/mcp.json",
+        );
+        assert_eq!(vs_code.format, ConfigFormat::VsCode);
+        assert_eq!(vs_code.servers.len(), 1);
+        assert_eq!(vs_code.skipped[0].name, "Web");
+
+        let settings = import(
+            r#"{"editor.tabSize": 2, "mcp": {"servers": {"Db": {"command": "db"}, "db": {"command": "db"}}}}"#,
+            "/home/me/.config/Code/User/settings.json",
+        );
+        assert_eq!(settings.format, ConfigFormat::VsCode);
+        let ids: Vec<&str> = settings.servers.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["imported-db", "imported-db-2"]);
+
+        assert!(import_str(r#"{"other": {}}"#, Path::new("x.json")).is_err());
+    }
+
+    #[test]
+    fn keeps_inputs_and_variables_as_references() {
+        let report = import(
+            r#"{
+                "inputs": [{"id": "token", "description": "API token"}],
+                "servers": {
+                    "Api": {"command": "api", "env": {"TOKEN": "${input:token}", "HOME_DIR": "${env:HOME}"}},
+                    "Mixed": {"command": "api", "env": {"AUTH": "Bearer ${input:token}"}},
+                    "Arg": {"command": "api", "args": ["--token=${input:token}"]}
+                }
+            }"#,
+            "/work/app/.vscode/mcp.json",
+        );
+
+        let env = report.servers[0].env.as_ref().unwrap();
+        assert_eq!(env["TOKEN"], "secret:token");
+        assert_eq!(env["HOME_DIR"], "env:HOME");
+        assert_eq!(report.warnings.len(), 1);
+
+        // An input inside a longer value can't become a vault reference
+        let skipped: Vec<&str> = report.skipped.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(skipped, ["Arg", "Mixed"]);
+        assert!(report.skipped[0].message.contains("API token"));
+    }
+
+    #[test]
+    fn exports_claude_desktop_configs() {
+        let report = import(
+            r#"{"mcpServers": {"Files": {"command": "fs", "env": {"KEY": "${env:KEY}", "MODE": "file:ro"}}}}"#,
+            "claude_desktop_config.json",
+        );
+        let env = report.servers[0].env.as_ref().unwrap();
+        assert_eq!(env["MODE"], "literal:file:ro");
+        let servers = [report.servers[0].clone(), report.servers[0].clone()];
+        assert_eq!(
+            export_claude_desktop(&servers),
+            json!({"mcpServers": {
+                "Files": {"command": "fs", "args": [], "env": {"KEY": "env:KEY", "MODE": "file:ro"}},
+                "Files (2)": {"command": "fs", "args": [], "env": {"KEY": "env:KEY", "MODE": "file:ro"}},
+            }})
+        );
+    }
+}
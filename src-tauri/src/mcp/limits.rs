@@ -0,0 +1,209 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::{Mutex, Semaphore, SemaphorePermit};
+use tokio::time::{Duration, Instant};
+
+use super::protocol::{McpServerConfig, RateLimit};
+
+/// Queue of tool calls waiting for a server's concurrency and rate limits
+///
+/// Also queues the completion requests to a chat provider, see `RequestScheduler`.
+///
+/// Calls are admitted in the order they arrive: both the semaphore and the
+/// bucket's lock hand out turns first come, first served, so a burst of calls
+/// from one conversation can't starve a call that was made before it.
+pub struct CallLimiter {
+    max_concurrent_calls: Option<usize>,
+    rate_limit: Option<RateLimit>,
+    concurrency: Option<Semaphore>,
+    bucket: Option<Mutex<TokenBucket>>,
+    queued: AtomicUsize,
+    in_flight: AtomicUsize,
+}
+
+/// Current load on a server's call queue
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallQueueStatus {
+    /// Calls sent to the server and not answered yet
+    pub in_flight: usize,
+    /// Calls waiting for a free slot or a rate limit token
+    pub queued: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_calls: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimit>,
+}
+
+struct TokenBucket {
+    tokens: f64,
+    capacity: f64,
+    per_second: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: &RateLimit) -> Self {
+        let capacity = f64::from(limit.burst.max(1));
+        Self {
+            tokens: capacity,
+            capacity,
+            per_second: limit.per_second,
+            refilled_at: Instant::now(),
+        }
+    }
+
+    /// Take a token, or say how long until one is available
+    fn try_take(&mut self) -> Result<(), Duration> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.capacity);
+        self.refilled_at = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.per_second,
+            ))
+        }
+    }
+}
+
+/// Slot for one call; frees it when dropped
+pub struct CallPermit<'a> {
+    limiter: &'a CallLimiter,
+    _slot: Option<SemaphorePermit<'a>>,
+}
+
+impl Drop for CallPermit<'_> {
+    fn drop(&mut self) {
+        self.limiter.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Counts a caller as queued until it is admitted or gives up
+struct QueuedGuard<'a>(&'a AtomicUsize);
+
+impl Drop for QueuedGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl CallLimiter {
+    pub fn new(config: &McpServerConfig) -> Self {
+        Self::with_limits(config.max_concurrent_calls, config.rate_limit.clone())
+    }
+
+    pub fn with_limits(max_concurrent_calls: Option<usize>, rate_limit: Option<RateLimit>) -> Self {
+        // A rate of zero or less would never refill, so it is treated as no limit
+        let rate_limit = rate_limit.filter(|limit| limit.per_second > 0.0);
+
+        Self {
+            max_concurrent_calls,
+            concurrency: max_concurrent_calls.map(|max| Semaphore::new(max.max(1))),
+            bucket: rate_limit
+                .as_ref()
+                .map(|limit| Mutex::new(TokenBucket::new(limit))),
+            rate_limit,
+            queued: AtomicUsize::new(0),
+            in_flight: AtomicUsize::new(0),
+        }
+    }
+
+    /// Wait for a free slot and a rate limit token
+    ///
+    /// The token is taken only once a slot is free, so calls stuck behind the
+    /// concurrency limit don't use up the rate limit.
+    pub async fn acquire(&self) -> CallPermit<'_> {
+        self.queued.fetch_add(1, Ordering::SeqCst);
+        let queued = QueuedGuard(&self.queued);
+
+        let slot = match &self.concurrency {
+            Some(semaphore) => Some(
+                semaphore
+                    .acquire()
+                    .await
+                    .expect("call semaphore is never closed"),
+            ),
+            None => None,
+        };
+
+        if let Some(bucket) = &self.bucket {
+            // Holding the lock while sleeping keeps later callers in line behind this one
+            let mut bucket = bucket.lock().await;
+            while let Err(wait) = bucket.try_take() {
+                tokio::time::sleep(wait).await;
+            }
+        }
+
+        drop(queued);
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        CallPermit {
+            limiter: self,
+            _slot: slot,
+        }
+    }
+
+    pub fn status(&self) -> CallQueueStatus {
+        CallQueueStatus {
+            in_flight: self.in_flight.load(Ordering::SeqCst),
+            queued: self.queued.load(Ordering::SeqCst),
+            max_concurrent_calls: self.max_concurrent_calls,
+            rate_limit: self.rate_limit.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn limiter(limits: serde_json::Value) -> Arc<CallLimiter> {
+        let mut config = json!({ "id": "s", "name": "S", "command": "s", "args": [] });
+        for (key, value) in limits.as_object().unwrap() {
+            config[key] = value.clone();
+        }
+        Arc::new(CallLimiter::new(&serde_json::from_value(config).unwrap()))
+    }
+
+    #[tokio::test]
+    async fn queues_calls_over_the_concurrency_limit() {
+        let limiter = limiter(json!({ "maxConcurrentCalls": 1 }));
+        let first = limiter.acquire().await;
+
+        let waiting = tokio::spawn({
+            let limiter = limiter.clone();
+            async move {
+                let _permit = limiter.acquire().await;
+            }
+        });
+        tokio::task::yield_now().await;
+        let status = limiter.status();
+        assert_eq!((status.in_flight, status.queued), (1, 1));
+
+        drop(first);
+        waiting.await.unwrap();
+        let status = limiter.status();
+        assert_eq!((status.in_flight, status.queued), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn spaces_calls_out_after_a_burst() {
+        let limiter = limiter(json!({ "rateLimit": { "burst": 2, "perSecond": 20.0 } }));
+        let started = Instant::now();
+
+        for _ in 0..4 {
+            drop(limiter.acquire().await);
+        }
+
+        // Two calls from the burst, then one every 50 ms
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_millis(95), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(1000), "{:?}", elapsed);
+    }
+}
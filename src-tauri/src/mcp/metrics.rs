@@ -0,0 +1,358 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+use super::error::TimeoutError;
+use super::protocol::CallToolResult;
+
+/// Upper bounds of the latency histogram buckets, in milliseconds
+const LATENCY_BUCKETS_MS: &[u64] = &[10, 25, 50, 100, 250, 500, 1000, 2500, 5000, 10_000, 30_000];
+
+/// Latency distribution in the shape Prometheus expects
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyHistogram {
+    /// Upper bound of each bucket, in milliseconds
+    pub bounds_ms: Vec<u64>,
+    /// Observations per bucket, not cumulative; the last one is above every bound
+    pub counts: Vec<u64>,
+    pub sum_ms: u64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            bounds_ms: LATENCY_BUCKETS_MS.to_vec(),
+            counts: vec![0; LATENCY_BUCKETS_MS.len() + 1],
+            sum_ms: 0,
+        }
+    }
+}
+
+impl LatencyHistogram {
+    fn observe(&mut self, duration_ms: u64) {
+        let bucket = self
+            .bounds_ms
+            .iter()
+            .position(|bound| duration_ms <= *bound)
+            .unwrap_or(self.bounds_ms.len());
+        self.counts[bucket] += 1;
+        self.sum_ms += duration_ms;
+    }
+}
+
+/// Counters for calls to one server or tool
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallMetrics {
+    /// Calls that reached the server; only these are timed
+    pub calls: u64,
+    /// Calls answered from the result cache instead
+    pub cache_hits: u64,
+    /// Failed calls and error results, including timeouts
+    pub errors: u64,
+    pub timeouts: u64,
+    pub latency: LatencyHistogram,
+}
+
+impl CallMetrics {
+    fn observe(&mut self, outcome: &Result<CallToolResult>, duration_ms: u64) {
+        self.calls += 1;
+        match outcome {
+            Ok(result) if result.is_error != Some(true) => {}
+            Ok(_) => self.errors += 1,
+            Err(e) => {
+                self.errors += 1;
+                if e.is::<TimeoutError>() {
+                    self.timeouts += 1;
+                }
+            }
+        }
+        self.latency.observe(duration_ms);
+    }
+}
+
+/// Everything recorded for one server ID since the app started
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerMetrics {
+    pub starts: u64,
+    pub start_failures: u64,
+    pub restarts: u64,
+    /// Time from spawning to a finished `initialize` on the last successful start
+    pub last_startup_ms: Option<u64>,
+    #[serde(flatten)]
+    pub calls: CallMetrics,
+    pub tools: BTreeMap<String, CallMetrics>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MetricsFormat {
+    #[default]
+    Json,
+    /// Prometheus text exposition format
+    Prometheus,
+}
+
+/// Metrics in the requested format
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum MetricsReport {
+    /// Metrics keyed by server ID
+    Json(BTreeMap<String, ServerMetrics>),
+    Prometheus(String),
+}
+
+/// Call, startup and restart statistics per server, kept in memory
+///
+/// Counters survive stopping a server so a flaky server's history stays visible
+/// until the app exits.
+#[derive(Default)]
+pub struct McpMetrics {
+    servers: Mutex<BTreeMap<String, ServerMetrics>>,
+}
+
+impl McpMetrics {
+    pub fn record_start(&self, server_id: &str, outcome: &Result<String>, duration_ms: u64) {
+        self.update(server_id, |server| match outcome {
+            Ok(_) => {
+                server.starts += 1;
+                server.last_startup_ms = Some(duration_ms);
+            }
+            Err(_) => server.start_failures += 1,
+        });
+    }
+
+    pub fn record_restart(&self, server_id: &str) {
+        self.update(server_id, |server| server.restarts += 1);
+    }
+
+    /// Record a call's round trip to the server
+    pub fn record_call(
+        &self,
+        server_id: &str,
+        tool_name: &str,
+        outcome: &Result<CallToolResult>,
+        duration_ms: u64,
+    ) {
+        self.update(server_id, |server| {
+            server.calls.observe(outcome, duration_ms);
+            server
+                .tools
+                .entry(tool_name.to_string())
+                .or_default()
+                .observe(outcome, duration_ms);
+        });
+    }
+
+    pub fn record_cache_hit(&self, server_id: &str, tool_name: &str) {
+        self.update(server_id, |server| {
+            server.calls.cache_hits += 1;
+            server
+                .tools
+                .entry(tool_name.to_string())
+                .or_default()
+                .cache_hits += 1;
+        });
+    }
+
+    fn update(&self, server_id: &str, update: impl FnOnce(&mut ServerMetrics)) {
+        let mut servers = self
+            .servers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        update(servers.entry(server_id.to_string()).or_default());
+    }
+
+    /// Copy of the current metrics, keyed by server ID
+    pub fn snapshot(&self) -> BTreeMap<String, ServerMetrics> {
+        self.servers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    pub fn report(&self, format: MetricsFormat) -> MetricsReport {
+        match format {
+            MetricsFormat::Json => MetricsReport::Json(self.snapshot()),
+            MetricsFormat::Prometheus => MetricsReport::Prometheus(self.to_prometheus()),
+        }
+    }
+
+    /// Current metrics in the Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        let servers = self.snapshot();
+        let mut out = String::new();
+
+        let counters: [Counter<ServerMetrics>; 3] = [
+            ("mcp_server_starts_total", "Successful server starts", |s| {
+                s.starts
+            }),
+            (
+                "mcp_server_start_failures_total",
+                "Failed server starts",
+                |s| s.start_failures,
+            ),
+            ("mcp_server_restarts_total", "Server restarts", |s| {
+                s.restarts
+            }),
+        ];
+        for (name, help, value) in counters {
+            header(&mut out, name, help, "counter");
+            for (id, server) in &servers {
+                let _ = writeln!(
+                    out,
+                    "{}{{server=\"{}\"}} {}",
+                    name,
+                    escape(id),
+                    value(server)
+                );
+            }
+        }
+
+        header(
+            &mut out,
+            "mcp_server_startup_milliseconds",
+            "Duration of the last successful server start",
+            "gauge",
+        );
+        for (id, server) in &servers {
+            if let Some(startup_ms) = server.last_startup_ms {
+                let _ = writeln!(
+                    out,
+                    "mcp_server_startup_milliseconds{{server=\"{}\"}} {}",
+                    escape(id),
+                    startup_ms
+                );
+            }
+        }
+
+        // Per-tool series only; servers are their sum
+        let tools: Vec<(String, &CallMetrics)> = servers
+            .iter()
+            .flat_map(|(id, server)| {
+                server.tools.iter().map(move |(tool, metrics)| {
+                    (
+                        format!("server=\"{}\",tool=\"{}\"", escape(id), escape(tool)),
+                        metrics,
+                    )
+                })
+            })
+            .collect();
+
+        let counters: [Counter<CallMetrics>; 4] = [
+            ("mcp_tool_calls_total", "Tool calls", |m| m.calls),
+            (
+                "mcp_tool_cache_hits_total",
+                "Tool calls answered from the cache",
+                |m| m.cache_hits,
+            ),
+            (
+                "mcp_tool_errors_total",
+                "Failed tool calls and error results",
+                |m| m.errors,
+            ),
+            (
+                "mcp_tool_timeouts_total",
+                "Tool calls that timed out",
+                |m| m.timeouts,
+            ),
+        ];
+        for (name, help, value) in counters {
+            header(&mut out, name, help, "counter");
+            for (labels, metrics) in &tools {
+                let _ = writeln!(out, "{}{{{}}} {}", name, labels, value(metrics));
+            }
+        }
+
+        let name = "mcp_tool_call_duration_milliseconds";
+        header(&mut out, name, "Tool call latency", "histogram");
+        for (labels, metrics) in &tools {
+            let histogram = &metrics.latency;
+            let mut cumulative = 0;
+            for (bound, count) in histogram.bounds_ms.iter().zip(&histogram.counts) {
+                cumulative += count;
+                let _ = writeln!(
+                    out,
+                    "{}_bucket{{{},le=\"{}\"}} {}",
+                    name, labels, bound, cumulative
+                );
+            }
+            let _ = writeln!(
+                out,
+                "{}_bucket{{{},le=\"+Inf\"}} {}",
+                name, labels, metrics.calls
+            );
+            let _ = writeln!(out, "{}_sum{{{}}} {}", name, labels, histogram.sum_ms);
+            let _ = writeln!(out, "{}_count{{{}}} {}", name, labels, metrics.calls);
+        }
+
+        out
+    }
+}
+
+/// Name, help text and value of a Prometheus counter
+type Counter<T> = (&'static str, &'static str, fn(&T) -> u64);
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Escape a Prometheus label value
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    fn ok() -> Result<CallToolResult> {
+        Ok(CallToolResult::text(""))
+    }
+
+    #[test]
+    fn counts_errors_timeouts_and_latency() {
+        let metrics = McpMetrics::default();
+        metrics.record_call("fs", "read", &ok(), 5);
+        metrics.record_call("fs", "read", &Err(anyhow!("boom")), 300);
+        let timeout = TimeoutError {
+            method: "tools/call".to_string(),
+            timeout_ms: 100,
+        };
+        metrics.record_call("fs", "write", &Err(timeout.into()), 40_000);
+        metrics.record_cache_hit("fs", "read");
+
+        let servers = metrics.snapshot();
+        let fs = &servers["fs"];
+        assert_eq!(
+            (fs.calls.calls, fs.calls.errors, fs.calls.timeouts),
+            (3, 2, 1)
+        );
+        assert_eq!(fs.tools["read"].calls, 2);
+        assert_eq!(fs.tools["read"].cache_hits, 1);
+        assert_eq!(fs.calls.latency.counts[0], 1);
+        assert_eq!(fs.calls.latency.counts[5], 1);
+        assert_eq!(fs.calls.latency.counts[LATENCY_BUCKETS_MS.len()], 1);
+        assert_eq!(fs.calls.latency.sum_ms, 40_305);
+
+        let text = metrics.to_prometheus();
+        assert!(text.contains("mcp_tool_calls_total{server=\"fs\",tool=\"read\"} 2"));
+        assert!(text.contains(
+            "mcp_tool_call_duration_milliseconds_bucket{server=\"fs\",tool=\"read\",le=\"250\"} 1"
+        ));
+        assert!(text.contains(
+            "mcp_tool_call_duration_milliseconds_bucket{server=\"fs\",tool=\"read\",le=\"+Inf\"} 2"
+        ));
+        assert!(text.contains("mcp_tool_timeouts_total{server=\"fs\",tool=\"write\"} 1"));
+        assert!(text.contains("mcp_tool_cache_hits_total{server=\"fs\",tool=\"read\"} 1"));
+    }
+}
@@ -1,6 +1,38 @@
+pub mod audit;
+pub mod cache;
+pub mod cancel;
+pub mod config;
+pub mod error;
+pub mod gateway;
+pub mod history;
+pub mod interop;
+pub mod limits;
+pub mod metrics;
+pub mod naming;
+pub mod process;
 pub mod protocol;
+pub mod results;
+pub mod sandbox;
+pub mod secrets;
+pub mod serve;
 pub mod server;
+pub mod trace;
 pub mod transport;
 
+pub use audit::{ApprovalDecision, AuditEntry, AuditQuery, AuditRetention, McpAuditLog};
+pub use cancel::CancelToken;
+pub use config::McpConfigStore;
+pub use error::{CancelledError, FrameTooLargeError, SandboxError, TimeoutError};
+pub use gateway::{GatewayClient, GatewayStatus, McpGateway};
+pub use history::HistoryHandler;
+pub use limits::CallQueueStatus;
+pub use metrics::{
+    CallMetrics, LatencyHistogram, McpMetrics, MetricsFormat, MetricsReport, ServerMetrics,
+};
+pub use naming::ToolRef;
+pub use process::ProcessInfo;
 pub use protocol::{OpenAITool, *};
-pub use server::McpServerManager;
+pub use secrets::SecretVault;
+pub use trace::{TraceEntry, TRACE_DIR_NAME};
+pub use transport::ServerDiagnostics;
+pub use server::{BatchCall, CallOptions, McpServerManager};
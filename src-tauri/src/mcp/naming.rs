@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Maximum tool name length accepted by LLM providers
+pub const MAX_TOOL_NAME_LEN: usize = 64;
+
+/// Separator between the server prefix and the tool name
+const NAMESPACE_SEPARATOR: &str = "__";
+
+/// Length of the hash suffix appended to truncated or colliding names ("_" + 8 hex chars)
+const HASH_SUFFIX_LEN: usize = 9;
+
+/// Original server and tool a provider-safe name refers to
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolRef {
+    pub server_id: String,
+    pub tool_name: String,
+}
+
+/// Replace every character outside `[a-zA-Z0-9_-]` with an underscore
+pub fn sanitize_name(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    if sanitized.is_empty() {
+        "_".to_string()
+    } else {
+        sanitized
+    }
+}
+
+/// Stable 32-bit FNV-1a hash, so mangled names survive restarts
+fn fnv1a(parts: &[&str]) -> u32 {
+    let mut hash: u32 = 0x811c_9dc5;
+    // A NUL separator keeps ("ab", "c") and ("a", "bc") apart
+    for byte in parts.join("\0").bytes() {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    hash
+}
+
+/// Build the provider-safe name for a tool, without checking for collisions
+///
+/// The result is `<server>__<tool>` with both halves sanitized. When the name does
+/// not fit in 64 characters it is truncated and suffixed with a hash of the original
+/// server id and tool name.
+pub fn mangle_tool_name(server_id: &str, server_name: &str, tool_name: &str) -> String {
+    let candidate = format!(
+        "{}{}{}",
+        sanitize_name(server_name),
+        NAMESPACE_SEPARATOR,
+        sanitize_name(tool_name)
+    );
+
+    if candidate.len() <= MAX_TOOL_NAME_LEN && candidate == raw_name(server_name, tool_name) {
+        return candidate;
+    }
+
+    // Sanitizing was lossy or the name is too long, so disambiguate with a hash
+    with_hash_suffix(&candidate, fnv1a(&[server_id, tool_name]))
+}
+
+fn raw_name(server_name: &str, tool_name: &str) -> String {
+    format!("{}{}{}", server_name, NAMESPACE_SEPARATOR, tool_name)
+}
+
+fn with_hash_suffix(name: &str, hash: u32) -> String {
+    // Sanitized names are pure ASCII, so byte truncation is safe
+    let keep = name.len().min(MAX_TOOL_NAME_LEN - HASH_SUFFIX_LEN);
+    format!("{}_{:08x}", &name[..keep], hash)
+}
+
+/// Name with a hash suffix derived from the server id and tool name only
+fn hashed_name(base: &str, server_id: &str, tool_name: &str) -> String {
+    with_hash_suffix(base, fnv1a(&[server_id, tool_name]))
+}
+
+/// Bidirectional mapping between provider-safe tool names and MCP tools
+#[derive(Debug, Default)]
+pub struct ToolNameRegistry {
+    by_safe_name: HashMap<String, ToolRef>,
+    by_tool: HashMap<ToolRef, String>,
+    /// Plain names wanted by more than one tool, which none of them gets
+    contested: HashSet<String>,
+}
+
+impl ToolNameRegistry {
+    /// Register a tool and return its provider-safe name
+    ///
+    /// Registering the same tool twice returns the same name. When two tools
+    /// want the same name, e.g. from servers sharing a display name, both get a
+    /// hash suffix of their server id and tool name, so no name ever depends on
+    /// the order servers started in. The tool that had the plain name is renamed
+    /// and the plain name stops resolving.
+    pub fn register(&mut self, server_id: &str, server_name: &str, tool_name: &str) -> String {
+        let tool_ref = ToolRef {
+            server_id: server_id.to_string(),
+            tool_name: tool_name.to_string(),
+        };
+
+        if let Some(existing) = self.by_tool.get(&tool_ref) {
+            return existing.clone();
+        }
+
+        let base = mangle_tool_name(server_id, server_name, tool_name);
+        let safe_name = if self.contested.contains(&base) {
+            hashed_name(&base, server_id, tool_name)
+        } else if let Some(holder) = self.by_safe_name.remove(&base) {
+            self.contested.insert(base.clone());
+            let renamed = hashed_name(&base, &holder.server_id, &holder.tool_name);
+            self.by_safe_name.insert(renamed.clone(), holder.clone());
+            self.by_tool.insert(holder, renamed);
+            hashed_name(&base, server_id, tool_name)
+        } else {
+            base
+        };
+
+        self.by_safe_name.insert(safe_name.clone(), tool_ref.clone());
+        self.by_tool.insert(tool_ref, safe_name.clone());
+        safe_name
+    }
+
+    /// Map a provider-safe name back to the original server and tool
+    pub fn resolve(&self, safe_name: &str) -> Option<&ToolRef> {
+        self.by_safe_name.get(safe_name)
+    }
+
+    /// Forget every tool belonging to a server
+    pub fn remove_server(&mut self, server_id: &str) {
+        self.by_tool.retain(|tool_ref, _| tool_ref.server_id != server_id);
+        self.by_safe_name
+            .retain(|_, tool_ref| tool_ref.server_id != server_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_provider_safe(name: &str) -> bool {
+        name.len() <= MAX_TOOL_NAME_LEN
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    }
+
+    #[test]
+    fn mangles_names_into_the_provider_charset_and_length() {
+        assert_eq!(mangle_tool_name("fs", "Files", "read"), "Files__read");
+
+        let lossy = mangle_tool_name("gh", "Git Hub", "issues.list");
+        assert!(lossy.starts_with("Git_Hub__issues_list_"), "{}", lossy);
+        assert!(is_provider_safe(&lossy));
+        // Sanitizing the same way no longer makes two tools share a name
+        assert_ne!(lossy, mangle_tool_name("gh", "Git Hub", "issues/list"));
+
+        let long = mangle_tool_name("db", &"x".repeat(50), &"y".repeat(50));
+        assert_eq!(long.len(), MAX_TOOL_NAME_LEN);
+        assert!(is_provider_safe(&long));
+        assert_eq!(
+            long,
+            mangle_tool_name("db", &"x".repeat(50), &"y".repeat(50))
+        );
+
+        assert!(is_provider_safe(&mangle_tool_name("ü", "Überall", "größe")));
+    }
+
+    #[test]
+    fn resolves_registered_names() {
+        let mut registry = ToolNameRegistry::default();
+        let name = registry.register("gh", "Git Hub", "issues.list");
+        assert_eq!(registry.register("gh", "Git Hub", "issues.list"), name);
+
+        let tool = registry.resolve(&name).unwrap();
+        assert_eq!(
+            (tool.server_id.as_str(), tool.tool_name.as_str()),
+            ("gh", "issues.list")
+        );
+        assert!(registry.resolve("Git_Hub__issues_list").is_none());
+
+        registry.remove_server("gh");
+        assert!(registry.resolve(&name).is_none());
+    }
+
+    #[test]
+    fn names_colliding_tools_independently_of_order() {
+        let register = |order: &[&str]| {
+            let mut registry = ToolNameRegistry::default();
+            for server_id in order {
+                registry.register(server_id, "Search", "query");
+            }
+            let names: Vec<String> = ["a", "b", "c"]
+                .iter()
+                .map(|server_id| registry.register(server_id, "Search", "query"))
+                .collect();
+            (registry, names)
+        };
+
+        let (registry, names) = register(&["a", "b", "c"]);
+        assert_eq!(register(&["c", "a", "b"]).1, names);
+        assert_ne!(names[0], names[1]);
+        assert_ne!(names[1], names[2]);
+        assert!(names.iter().all(|name| name.starts_with("Search__query_")));
+
+        // The shared plain name points at none of them
+        assert!(registry.resolve("Search__query").is_none());
+        for (server_id, name) in ["a", "b", "c"].iter().zip(&names) {
+            assert_eq!(registry.resolve(name).unwrap().server_id, *server_id);
+        }
+    }
+}
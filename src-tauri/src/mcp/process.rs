@@ -0,0 +1,264 @@
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::process::Command;
+use tokio::sync::OnceCell;
+
+use super::protocol::{InheritEnv, McpServerConfig};
+
+/// Details about the process backing a stdio server
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessInfo {
+    /// Binary that was actually executed, after PATH resolution
+    pub executable: String,
+    pub pid: Option<u32>,
+    pub cwd: Option<String>,
+}
+
+/// Marker around the environment printed by the login shell, so profile banners are ignored
+const PATH_MARKER: &str = "__ORION_PATH__";
+
+static LOGIN_SHELL_PATH: OnceCell<Option<OsString>> = OnceCell::const_new();
+
+/// PATH as seen by the user's login shell
+///
+/// Apps started from a desktop launcher don't get the PATH set up in shell profiles,
+/// so `npx` from nvm or `uvx` from pipx would not be found. The result is cached.
+async fn login_shell_path() -> Option<OsString> {
+    LOGIN_SHELL_PATH
+        .get_or_init(|| async {
+            if cfg!(windows) {
+                return None;
+            }
+
+            let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string());
+            shell_path(&shell).await
+        })
+        .await
+        .clone()
+}
+
+/// PATH a shell exports to the programs it runs
+///
+/// The shell runs `env` rather than expanding `$PATH` itself, since shells like
+/// fish keep PATH as a list and only join it with colons when exporting it.
+async fn shell_path(shell: &str) -> Option<OsString> {
+    let script = format!("echo {0}; /usr/bin/env; echo {0}", PATH_MARKER);
+    let output = Command::new(shell)
+        .args(["-i", "-l", "-c", &script])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+
+    let output = tokio::time::timeout(tokio::time::Duration::from_secs(5), output)
+        .await
+        .ok()?
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let path = stdout
+        .split(PATH_MARKER)
+        .nth(1)?
+        .lines()
+        .find_map(|line| line.strip_prefix("PATH="))?;
+
+    (!path.is_empty()).then(|| OsString::from(path))
+}
+
+/// Build the PATH for a server: extra entries, then the login shell PATH, then Orion's own
+pub async fn search_path(extra_path: &[String]) -> Result<OsString> {
+    let mut dirs: Vec<PathBuf> = extra_path.iter().map(PathBuf::from).collect();
+    for path in [login_shell_path().await, std::env::var_os("PATH")]
+        .into_iter()
+        .flatten()
+    {
+        dirs.extend(std::env::split_paths(&path));
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    dirs.retain(|dir| !dir.as_os_str().is_empty() && seen.insert(dir.clone()));
+
+    std::env::join_paths(dirs).map_err(|e| anyhow!("Invalid PATH entry: {}", e))
+}
+
+/// Find the executable a command name refers to
+///
+/// Commands containing a path separator are resolved against `cwd` instead of PATH.
+pub fn find_executable(command: &str, path: &OsString, cwd: Option<&Path>) -> Result<PathBuf> {
+    let command_path = Path::new(command);
+    if command_path.components().count() > 1 {
+        let resolved = match cwd {
+            Some(cwd) if command_path.is_relative() => cwd.join(command_path),
+            _ => command_path.to_path_buf(),
+        };
+        return if resolved.is_file() {
+            Ok(resolved)
+        } else {
+            Err(anyhow!("Command '{}' does not exist", resolved.display()))
+        };
+    }
+
+    for dir in std::env::split_paths(path) {
+        for candidate in executable_candidates(&dir.join(command)) {
+            if is_executable(&candidate) {
+                return Ok(candidate);
+            }
+        }
+    }
+
+    Err(anyhow!("Command '{}' was not found on PATH", command))
+}
+
+#[cfg(windows)]
+fn executable_candidates(path: &Path) -> Vec<PathBuf> {
+    let pathext = std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+    let mut candidates = vec![path.to_path_buf()];
+    for ext in pathext.split(';').filter(|ext| !ext.is_empty()) {
+        let mut candidate = path.as_os_str().to_os_string();
+        candidate.push(ext);
+        candidates.push(PathBuf::from(candidate));
+    }
+    candidates
+}
+
+#[cfg(not(windows))]
+fn executable_candidates(path: &Path) -> Vec<PathBuf> {
+    vec![path.to_path_buf()]
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Create the command for a server with its environment, PATH and working directory
+///
+/// Returns the command together with the resolved executable. Values in
+/// `config.env` are not applied here since they may need secret resolution.
+pub async fn build_command(config: &McpServerConfig) -> Result<(Command, PathBuf)> {
+    let cwd = config.cwd.as_ref().map(PathBuf::from);
+    if let Some(cwd) = &cwd {
+        if !cwd.is_dir() {
+            return Err(anyhow!(
+                "Working directory '{}' does not exist",
+                cwd.display()
+            ));
+        }
+    }
+
+    let path = search_path(&config.extra_path).await?;
+    let executable = find_executable(&config.command, &path, cwd.as_deref())?;
+
+    let mut cmd = Command::new(&executable);
+    match &config.inherit_env {
+        InheritEnv::All => {}
+        InheritEnv::None => {
+            cmd.env_clear();
+        }
+        InheritEnv::Allowlist(names) => {
+            cmd.env_clear();
+            for name in names {
+                if let Some(value) = std::env::var_os(name) {
+                    cmd.env(name, value);
+                }
+            }
+        }
+    }
+    cmd.env("PATH", path);
+
+    if let Some(cwd) = &cwd {
+        cmd.current_dir(cwd);
+    }
+
+    Ok((cmd, executable))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "orion-process-test-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[cfg(unix)]
+    fn write_executable(path: &Path, contents: &str) {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::write(path, contents).unwrap();
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn reads_the_path_a_shell_exports() {
+        let dir = test_dir("shell");
+        // Greets like fish and runs the script with its own PATH
+        let shell = dir.join("fake-shell");
+        write_executable(
+            &shell,
+            "#!/bin/sh\necho 'Welcome to fish'\nPATH=/opt/fish/bin:/usr/bin exec /bin/sh -c \"$4\"\n",
+        );
+
+        let path = shell_path(shell.to_str().unwrap()).await;
+        assert_eq!(path, Some(OsString::from("/opt/fish/bin:/usr/bin")));
+        assert_eq!(shell_path("/nonexistent/shell").await, None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn puts_extra_entries_first_without_duplicates() {
+        let extra = vec!["/opt/tools/bin".to_string(), "/opt/tools/bin".to_string()];
+        let path = search_path(&extra).await.unwrap();
+        let dirs: Vec<PathBuf> = std::env::split_paths(&path).collect();
+
+        assert_eq!(dirs[0], PathBuf::from("/opt/tools/bin"));
+        let unique: std::collections::HashSet<_> = dirs.iter().collect();
+        assert_eq!(unique.len(), dirs.len());
+        assert!(dirs.iter().all(|dir| !dir.as_os_str().is_empty()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn finds_executables_on_the_path_and_relative_to_cwd() {
+        let dir = test_dir("find");
+        let first = dir.join("first");
+        let second = dir.join("second");
+        std::fs::create_dir_all(&first).unwrap();
+        std::fs::create_dir_all(&second).unwrap();
+        std::fs::write(first.join("tool"), "not executable").unwrap();
+        write_executable(&second.join("tool"), "#!/bin/sh\n");
+        let path = std::env::join_paths([&first, &second]).unwrap();
+
+        assert_eq!(
+            find_executable("tool", &path, None).unwrap(),
+            second.join("tool")
+        );
+        assert!(find_executable("missing", &path, None).is_err());
+
+        assert_eq!(
+            find_executable("second/tool", &path, Some(&dir)).unwrap(),
+            dir.join("second/tool")
+        );
+        assert!(find_executable("./tool", &path, Some(&dir)).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
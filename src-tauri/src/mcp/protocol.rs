@@ -42,6 +42,37 @@ pub struct JsonRpcError {
     pub data: Option<Value>,
 }
 
+/// Standard JSON-RPC 2.0 error codes
+pub const PARSE_ERROR: i32 = -32700;
+pub const INVALID_REQUEST: i32 = -32600;
+pub const METHOD_NOT_FOUND: i32 = -32601;
+pub const INVALID_PARAMS: i32 = -32602;
+pub const INTERNAL_ERROR: i32 = -32603;
+
+impl JsonRpcResponse {
+    pub fn success(id: Value, result: Value) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            result: Some(result),
+            error: None,
+            id,
+        }
+    }
+
+    pub fn failure(id: Value, code: i32, message: impl Into<String>) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            result: None,
+            error: Some(JsonRpcError {
+                code,
+                message: message.into(),
+                data: None,
+            }),
+            id,
+        }
+    }
+}
+
 /// MCP initialization parameters
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InitializeParams {
@@ -103,6 +134,28 @@ pub struct McpTool {
     pub description: String,
     #[serde(rename = "inputSchema")]
     pub input_schema: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<ToolAnnotations>,
+}
+
+/// Hints a server gives about a tool's behavior; clients must not rely on them for safety
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolAnnotations {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The tool doesn't modify its environment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_only_hint: Option<bool>,
+    /// The tool may perform destructive updates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destructive_hint: Option<bool>,
+    /// Repeating a call with the same arguments has no additional effect
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idempotent_hint: Option<bool>,
+    /// The tool interacts with external entities
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_world_hint: Option<bool>,
 }
 
 /// OpenAI-compatible tool format for LLM APIs
@@ -145,6 +198,8 @@ pub struct CallToolParams {
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub arguments: Option<Value>,
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Value>,
 }
 
 /// MCP tool call result
@@ -153,15 +208,137 @@ pub struct CallToolResult {
     pub content: Vec<ToolResultContent>,
     #[serde(rename = "isError", skip_serializing_if = "Option::is_none")]
     pub is_error: Option<bool>,
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolResultContent {
     #[serde(rename = "type")]
     pub content_type: String,
+    /// Text of a `text` block; images, audio and resources have none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Remaining fields, such as the `data` and `mimeType` of an image
+    #[serde(flatten)]
+    pub fields: serde_json::Map<String, Value>,
+}
+
+impl ToolResultContent {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            content_type: "text".to_string(),
+            text: Some(text.into()),
+            fields: serde_json::Map::new(),
+        }
+    }
+}
+
+impl CallToolResult {
+    /// Successful result with a single text block
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            content: vec![ToolResultContent::text(text)],
+            is_error: None,
+            meta: None,
+        }
+    }
+
+    /// Tool-level error reported back to the model
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            content: vec![ToolResultContent::text(message)],
+            is_error: Some(true),
+            meta: None,
+        }
+    }
+}
+
+/// MCP resource descriptor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Resource {
+    pub uri: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "mimeType", skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
+/// MCP resources list result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListResourcesResult {
+    pub resources: Vec<Resource>,
+}
+
+/// MCP resource read parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadResourceParams {
+    pub uri: String,
+}
+
+/// Text contents of a resource
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceContents {
+    pub uri: String,
+    #[serde(rename = "mimeType", skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
     pub text: String,
 }
 
+/// MCP resource read result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadResourceResult {
+    pub contents: Vec<ResourceContents>,
+}
+
+/// MCP prompt descriptor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Prompt {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub arguments: Vec<PromptArgument>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptArgument {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+}
+
+/// MCP prompts list result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListPromptsResult {
+    pub prompts: Vec<Prompt>,
+}
+
+/// MCP prompt get parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetPromptParams {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<std::collections::HashMap<String, String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptMessage {
+    pub role: String,
+    pub content: ToolResultContent,
+}
+
+/// MCP prompt get result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetPromptResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub messages: Vec<PromptMessage>,
+}
+
 /// MCP server configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpServerConfig {
@@ -171,4 +348,271 @@ pub struct McpServerConfig {
     pub args: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub env: Option<std::collections::HashMap<String, String>>,
+    #[serde(default)]
+    pub timeouts: McpTimeouts,
+    /// Start the server when the app launches
+    #[serde(default)]
+    pub autostart: bool,
+    /// Working directory for the server process (defaults to Orion's)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// Which of Orion's environment variables the server process inherits
+    #[serde(rename = "inheritEnv", default)]
+    pub inherit_env: InheritEnv,
+    /// Directories searched before the login shell PATH when resolving the command
+    #[serde(rename = "extraPath", default)]
+    pub extra_path: Vec<String>,
+    /// Linux sandbox applied to the server process
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<SandboxProfile>,
+    /// Largest message accepted from the server, in bytes
+    #[serde(rename = "maxFrameBytes", default = "default_max_frame_bytes")]
+    pub max_frame_bytes: usize,
+    /// Tools hidden from models and gateway clients, and refused if called anyway
+    #[serde(rename = "disabledTools", default)]
+    pub disabled_tools: Vec<String>,
+    /// Offer the server's tools to clients of Orion's MCP gateway
+    #[serde(rename = "exposeViaGateway", default = "default_true")]
+    pub expose_via_gateway: bool,
+    /// Record every JSON-RPC frame, with secrets redacted, to the server's trace file
+    #[serde(rename = "recordTraffic", default)]
+    pub record_traffic: bool,
+    /// Most tool calls sent to the server at once; further calls wait their turn
+    #[serde(rename = "maxConcurrentCalls", skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_calls: Option<usize>,
+    /// Token-bucket limit on how often the server's tools are called
+    #[serde(rename = "rateLimit", skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimit>,
+    /// Size limits on tool results before they reach models and gateway clients
+    #[serde(rename = "resultLimits", default)]
+    pub result_limits: ResultLimits,
+    /// Reuse results of repeated read-only or idempotent tool calls
+    #[serde(default)]
+    pub cache: CacheConfig,
+}
+
+/// Token bucket allowing `burst` calls at once, refilled at `per_second` calls per second
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateLimit {
+    pub burst: u32,
+    #[serde(rename = "perSecond")]
+    pub per_second: f64,
+}
+
+/// Largest stdio frame accepted when a server config doesn't set `maxFrameBytes`
+pub fn default_max_frame_bytes() -> usize {
+    16 * 1024 * 1024
+}
+
+/// Linux sandbox profile for a stdio server
+///
+/// Filesystem access is scoped with Landlock, dangerous syscalls are blocked with
+/// seccomp, and the process runs with no-new-privs and the given rlimits.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SandboxProfile {
+    /// Paths the server may read and execute from
+    #[serde(rename = "readPaths", default)]
+    pub read_paths: Vec<String>,
+    /// Paths the server may read and write
+    #[serde(rename = "writePaths", default)]
+    pub write_paths: Vec<String>,
+    /// Allow reading system directories (`/usr`, `/etc`, ...) and writing `/dev` and `/tmp`
+    #[serde(rename = "allowSystemPaths", default = "default_true")]
+    pub allow_system_paths: bool,
+    /// Allow IPv4 and IPv6 sockets
+    #[serde(rename = "allowNetwork", default = "default_true")]
+    pub allow_network: bool,
+    /// Address space limit in MiB
+    #[serde(rename = "maxMemoryMb", skip_serializing_if = "Option::is_none")]
+    pub max_memory_mb: Option<u64>,
+    /// CPU time limit in seconds
+    #[serde(rename = "maxCpuSeconds", skip_serializing_if = "Option::is_none")]
+    pub max_cpu_seconds: Option<u64>,
+    /// Maximum number of open file descriptors
+    #[serde(rename = "maxOpenFiles", skip_serializing_if = "Option::is_none")]
+    pub max_open_files: Option<u64>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Environment variables inherited by default, enough for typical Node/Python servers
+pub const DEFAULT_INHERITED_ENV: &[&str] = &[
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "LANG",
+    "LC_ALL",
+    "TERM",
+    "TMPDIR",
+    "TEMP",
+    "TMP",
+    "XDG_CONFIG_HOME",
+    "XDG_DATA_HOME",
+    "XDG_CACHE_HOME",
+    "XDG_RUNTIME_DIR",
+    "USERPROFILE",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "SystemRoot",
+    "SystemDrive",
+    "ComSpec",
+    "PATHEXT",
+];
+
+/// Environment inheritance mode for a server process
+///
+/// PATH is always set explicitly, whatever the mode.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InheritEnv {
+    /// Start from an empty environment
+    None,
+    /// Inherit only the listed variables
+    Allowlist(Vec<String>),
+    /// Inherit Orion's full environment, including any provider keys
+    All,
+}
+
+impl Default for InheritEnv {
+    fn default() -> Self {
+        Self::Allowlist(
+            DEFAULT_INHERITED_ENV
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+        )
+    }
+}
+
+/// Request timeouts for an MCP server, in milliseconds (0 disables the timeout)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpTimeouts {
+    #[serde(rename = "initializeMs", default = "default_initialize_timeout_ms")]
+    pub initialize_ms: u64,
+    #[serde(rename = "listMs", default = "default_list_timeout_ms")]
+    pub list_ms: u64,
+    #[serde(rename = "callMs", default = "default_call_timeout_ms")]
+    pub call_ms: u64,
+    /// Per-tool overrides of `call_ms`, keyed by MCP tool name
+    #[serde(rename = "toolOverrides", default)]
+    pub tool_overrides: std::collections::HashMap<String, u64>,
+    /// Restart the timeout whenever the server reports progress for the request
+    #[serde(rename = "resetOnProgress", default)]
+    pub reset_on_progress: bool,
+}
+
+fn default_initialize_timeout_ms() -> u64 {
+    30000
+}
+
+fn default_list_timeout_ms() -> u64 {
+    10000
+}
+
+fn default_call_timeout_ms() -> u64 {
+    60000
+}
+
+impl McpTimeouts {
+    /// Timeout for a call to the given tool
+    pub fn for_tool(&self, tool_name: &str) -> u64 {
+        self.tool_overrides
+            .get(tool_name)
+            .copied()
+            .unwrap_or(self.call_ms)
+    }
+}
+
+/// Longest tool results passed on, in characters (0 disables the limit)
+///
+/// Longer results are truncated with a marker and stored whole, to be paged
+/// through with the built-in `read_tool_result` tool.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultLimits {
+    #[serde(rename = "maxChars", default = "default_max_result_chars")]
+    pub max_chars: usize,
+    /// Per-tool overrides of `max_chars`, keyed by MCP tool name
+    #[serde(rename = "toolOverrides", default)]
+    pub tool_overrides: std::collections::HashMap<String, usize>,
+}
+
+fn default_max_result_chars() -> usize {
+    50_000
+}
+
+impl ResultLimits {
+    /// Limit for results of the given tool
+    pub fn for_tool(&self, tool_name: &str) -> usize {
+        self.tool_overrides
+            .get(tool_name)
+            .copied()
+            .unwrap_or(self.max_chars)
+    }
+}
+
+impl Default for ResultLimits {
+    fn default() -> Self {
+        Self {
+            max_chars: default_max_result_chars(),
+            tool_overrides: std::collections::HashMap::new(),
+        }
+    }
+}
+
+/// Opt-in caching of tool results, keyed by tool and arguments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheConfig {
+    /// Cache tools the server annotates with `readOnlyHint` or `idempotentHint`
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(rename = "ttlMs", default = "default_cache_ttl_ms")]
+    pub ttl_ms: u64,
+    /// Per-tool overrides of whether results are cached, regardless of annotations
+    #[serde(rename = "toolOverrides", default)]
+    pub tool_overrides: std::collections::HashMap<String, bool>,
+}
+
+fn default_cache_ttl_ms() -> u64 {
+    60_000
+}
+
+impl CacheConfig {
+    /// Whether results of the tool are cached, given its annotations
+    pub fn for_tool(&self, tool_name: &str, annotations: Option<&ToolAnnotations>) -> bool {
+        self.tool_overrides
+            .get(tool_name)
+            .copied()
+            .unwrap_or_else(|| {
+                self.enabled
+                    && annotations.is_some_and(|annotations| {
+                        annotations.read_only_hint == Some(true)
+                            || annotations.idempotent_hint == Some(true)
+                    })
+            })
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_ms: default_cache_ttl_ms(),
+            tool_overrides: std::collections::HashMap::new(),
+        }
+    }
+}
+
+impl Default for McpTimeouts {
+    fn default() -> Self {
+        Self {
+            initialize_ms: default_initialize_timeout_ms(),
+            list_ms: default_list_timeout_ms(),
+            call_ms: default_call_timeout_ms(),
+            tool_overrides: std::collections::HashMap::new(),
+            reset_on_progress: false,
+        }
+    }
 }
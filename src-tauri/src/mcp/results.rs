@@ -0,0 +1,227 @@
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use super::protocol::{CallToolResult, McpTool, ToolAnnotations, ToolResultContent};
+
+/// Directory under the app data directory holding full copies of truncated results
+pub const RESULTS_DIR_NAME: &str = "tool-results";
+
+/// Server ID of the tools Orion provides itself
+pub const BUILTIN_SERVER_ID: &str = "orion";
+
+/// Server name the built-in tools are namespaced with
+pub const BUILTIN_SERVER_NAME: &str = "Orion";
+
+/// Built-in tool returning more of a truncated result
+pub const READ_RESULT_TOOL: &str = "read_tool_result";
+
+/// Characters returned by one page when the caller doesn't ask for a length
+const DEFAULT_PAGE_CHARS: usize = 20_000;
+
+/// Stored results older than this are deleted when the store is opened
+const MAX_RESULT_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Full text of tool results that were too large to pass on whole
+///
+/// Each result gets a random handle the model pages through with
+/// `read_tool_result`. Results are plain files, kept for a week.
+pub struct ResultStore {
+    dir: PathBuf,
+}
+
+impl ResultStore {
+    /// Open the store in `dir`, deleting results past their age
+    pub fn open(dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        let store = Self {
+            dir: dir.to_path_buf(),
+        };
+        store.prune();
+        Ok(store)
+    }
+
+    /// Save a result's text and return its handle
+    pub fn store(&self, text: &str) -> Result<String> {
+        let bytes: [u8; 16] = rand::random();
+        let handle: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+
+        let path = self.path(&handle)?;
+        std::fs::write(&path, text)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(handle)
+    }
+
+    /// Up to `length` characters of a stored result starting at character `offset`
+    ///
+    /// Pages that stop before the end say where the next one starts. A zero
+    /// length is refused, since paging by it would never reach the end.
+    pub fn read(&self, handle: &str, offset: usize, length: usize) -> Result<String> {
+        if length == 0 {
+            return Err(anyhow!("'length' must be at least 1"));
+        }
+        let path = self.path(handle)?;
+        let text = std::fs::read_to_string(&path)
+            .map_err(|_| anyhow!("No stored result with handle '{}'", handle))?;
+
+        let total = text.chars().count();
+        let page: String = text.chars().skip(offset).take(length).collect();
+        let end = offset.saturating_add(length).min(total);
+
+        if end < total {
+            Ok(format!(
+                "{}\n\n[Characters {}-{} of {}. Call again with offset {} to read more.]",
+                page, offset, end, total, end
+            ))
+        } else {
+            Ok(page)
+        }
+    }
+
+    /// Cut a result's text down to `limit` characters, storing the full text
+    ///
+    /// The text blocks are joined into one that ends with a marker naming the
+    /// handle and the tool, under `pager_name`, that reads the rest. Images and
+    /// other non-text blocks follow it unchanged. Results within the limit are
+    /// returned as is.
+    pub fn truncate(
+        &self,
+        result: CallToolResult,
+        limit: usize,
+        pager_name: &str,
+    ) -> Result<CallToolResult> {
+        let text = result
+            .content
+            .iter()
+            .filter_map(|content| content.text.as_deref())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let total = text.chars().count();
+        if limit == 0 || total <= limit {
+            return Ok(result);
+        }
+
+        let handle = self.store(&text)?;
+        let kept: String = text.chars().take(limit).collect();
+        let marker = format!(
+            "[Result truncated: showing {} of {} characters. Call {} with handle \"{}\" and offset {} to read more.]",
+            limit, total, pager_name, handle, limit
+        );
+
+        let mut content = vec![ToolResultContent::text(format!("{}\n\n{}", kept, marker))];
+        content.extend(
+            result
+                .content
+                .into_iter()
+                .filter(|content| content.text.is_none()),
+        );
+
+        Ok(CallToolResult {
+            content,
+            is_error: result.is_error,
+            meta: result.meta,
+        })
+    }
+
+    /// Answer a call to `read_tool_result`
+    pub fn call_read_tool(&self, arguments: Option<&Value>) -> Result<CallToolResult> {
+        let argument = |name: &str| arguments.and_then(|arguments| arguments.get(name));
+        let handle = argument("handle")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("Missing 'handle' argument"))?;
+        let offset = argument("offset").and_then(Value::as_u64).unwrap_or(0) as usize;
+        let length = argument("length")
+            .and_then(Value::as_u64)
+            .map_or(DEFAULT_PAGE_CHARS, |length| length as usize);
+
+        Ok(CallToolResult::text(self.read(handle, offset, length)?))
+    }
+
+    /// File of a handle; anything but a hex handle is refused so paths can't escape the store
+    fn path(&self, handle: &str) -> Result<PathBuf> {
+        if handle.is_empty() || !handle.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(anyhow!("Invalid result handle '{}'", handle));
+        }
+        Ok(self.dir.join(format!("{}.txt", handle)))
+    }
+
+    fn prune(&self) {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let expired = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                .is_some_and(|age| age > MAX_RESULT_AGE);
+            if expired {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+/// Definition of the built-in `read_tool_result` tool
+pub fn read_result_tool() -> McpTool {
+    McpTool {
+        name: READ_RESULT_TOOL.to_string(),
+        description: "Read more of a tool result that was truncated. Pass the handle from the truncation notice and the offset to continue from.".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "handle": { "type": "string", "description": "Handle from the truncation notice" },
+                "offset": { "type": "integer", "description": "Character to start reading at", "minimum": 0 },
+                "length": { "type": "integer", "description": "Characters to read", "minimum": 1, "default": DEFAULT_PAGE_CHARS },
+            },
+            "required": ["handle"],
+        }),
+        annotations: Some(ToolAnnotations {
+            read_only_hint: Some(true),
+            ..ToolAnnotations::default()
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_and_pages_through_large_results() {
+        let dir = std::env::temp_dir().join(format!("orion-results-test-{}", std::process::id()));
+        let store = ResultStore::open(&dir).unwrap();
+
+        let mut result = CallToolResult::text("é".repeat(25));
+        let image: ToolResultContent = serde_json::from_value(
+            json!({ "type": "image", "data": "aGk=", "mimeType": "image/png" }),
+        )
+        .unwrap();
+        result.content.push(image);
+        let truncated = store
+            .truncate(result, 10, "Orion__read_tool_result")
+            .unwrap();
+        let text = truncated.content[0].text.as_deref().unwrap();
+        assert!(text.starts_with(&"é".repeat(10)));
+        assert!(text.contains("showing 10 of 25 characters"));
+        assert_eq!(
+            serde_json::to_value(&truncated.content[1]).unwrap(),
+            json!({ "type": "image", "data": "aGk=", "mimeType": "image/png" })
+        );
+
+        let handle = text.split('"').nth(1).unwrap();
+        let page = store.read(handle, 10, 10).unwrap();
+        assert!(page.starts_with(&"é".repeat(10)));
+        assert!(page.contains("with offset 20"));
+        assert_eq!(store.read(handle, 20, 10).unwrap(), "é".repeat(5));
+        assert_eq!(store.read(handle, 20, usize::MAX).unwrap(), "é".repeat(5));
+        assert!(store.read(handle, 20, 0).is_err());
+
+        assert!(store.read("../secrets", 0, 10).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
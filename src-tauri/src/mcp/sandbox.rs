@@ -0,0 +1,263 @@
+use anyhow::Result;
+use std::path::Path;
+use tokio::process::Command;
+
+use super::protocol::{McpServerConfig, SandboxProfile};
+
+/// Stderr fragments that usually mean the sandbox denied something
+const DENIAL_MARKERS: &[&str] = &[
+    "Permission denied",
+    "Operation not permitted",
+    "EACCES",
+    "EPERM",
+];
+
+/// Pick out stderr lines that look like sandbox denials
+pub fn denial_lines(stderr: &[String]) -> Vec<String> {
+    stderr
+        .iter()
+        .filter(|line| DENIAL_MARKERS.iter().any(|marker| line.contains(marker)))
+        .cloned()
+        .collect()
+}
+
+/// Apply a sandbox profile to a server command before it is spawned
+#[cfg(target_os = "linux")]
+pub fn apply(
+    cmd: &mut Command,
+    profile: &SandboxProfile,
+    config: &McpServerConfig,
+    executable: &Path,
+) -> Result<()> {
+    linux::apply(cmd, profile, config, executable)
+}
+
+/// Apply a sandbox profile to a server command before it is spawned
+#[cfg(not(target_os = "linux"))]
+pub fn apply(
+    _cmd: &mut Command,
+    _profile: &SandboxProfile,
+    config: &McpServerConfig,
+    _executable: &Path,
+) -> Result<()> {
+    Err(anyhow::anyhow!(
+        "Server '{}' has a sandbox profile, but sandboxing is only supported on Linux",
+        config.name
+    ))
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use anyhow::{anyhow, Result};
+    use landlock::{
+        path_beneath_rules, Access, AccessFs, Ruleset, RulesetAttr, RulesetCreatedAttr, ABI,
+    };
+    use seccompiler::{
+        BpfProgram, SeccompAction, SeccompCmpArgLen, SeccompCmpOp, SeccompCondition,
+        SeccompFilter, SeccompRule, TargetArch,
+    };
+    use std::collections::BTreeMap;
+    use std::os::fd::{AsRawFd, OwnedFd};
+    use std::path::{Path, PathBuf};
+    use tokio::process::Command;
+
+    use super::super::protocol::{McpServerConfig, SandboxProfile};
+
+    /// Read-only system locations needed to run interpreters and shared libraries
+    const SYSTEM_READ_PATHS: &[&str] = &[
+        "/usr", "/lib", "/lib32", "/lib64", "/bin", "/sbin", "/etc", "/opt", "/nix", "/snap",
+        "/proc", "/sys",
+    ];
+
+    /// System locations servers commonly write to
+    const SYSTEM_WRITE_PATHS: &[&str] = &["/dev", "/tmp"];
+
+    /// Syscalls no MCP server has a reason to make
+    const DENIED_SYSCALLS: &[i64] = &[
+        libc::SYS_ptrace,
+        libc::SYS_mount,
+        libc::SYS_umount2,
+        libc::SYS_pivot_root,
+        libc::SYS_kexec_load,
+        libc::SYS_init_module,
+        libc::SYS_finit_module,
+        libc::SYS_delete_module,
+        libc::SYS_reboot,
+        libc::SYS_swapon,
+        libc::SYS_swapoff,
+        libc::SYS_bpf,
+        libc::SYS_perf_event_open,
+        libc::SYS_keyctl,
+        libc::SYS_add_key,
+        libc::SYS_request_key,
+        libc::SYS_setns,
+        libc::SYS_unshare,
+        libc::SYS_process_vm_readv,
+        libc::SYS_process_vm_writev,
+        libc::SYS_userfaultfd,
+        libc::SYS_acct,
+        libc::SYS_settimeofday,
+        libc::SYS_clock_settime,
+        libc::SYS_open_by_handle_at,
+    ];
+
+    pub fn apply(
+        cmd: &mut Command,
+        profile: &SandboxProfile,
+        config: &McpServerConfig,
+        executable: &Path,
+    ) -> Result<()> {
+        // Checked up front so an unsupported kernel is a clear error, not a silent no-op
+        let abi_version = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                std::ptr::null::<libc::c_void>(),
+                0usize,
+                1u32, // LANDLOCK_CREATE_RULESET_VERSION
+            )
+        };
+        if abi_version < 1 {
+            return Err(anyhow!(
+                "Server '{}' has a sandbox profile, but Landlock is not available on this kernel (Linux 5.13+ with Landlock enabled is required)",
+                config.name
+            ));
+        }
+
+        let (read_paths, write_paths) = sandbox_paths(profile, config, executable);
+
+        // Build everything before fork; the child must not allocate, so it only
+        // makes the syscalls that apply what was built here
+        let abi = ABI::V3;
+        let ruleset: Option<OwnedFd> = Ruleset::default()
+            .handle_access(AccessFs::from_all(abi))?
+            .create()?
+            .add_rules(path_beneath_rules(&read_paths, AccessFs::from_read(abi)))?
+            .add_rules(path_beneath_rules(&write_paths, AccessFs::from_all(abi)))?
+            .into();
+        let ruleset = ruleset.ok_or_else(|| {
+            anyhow!(
+                "Server '{}' has a sandbox profile, but the Landlock ruleset could not be created",
+                config.name
+            )
+        })?;
+        let filter = seccomp_filter(profile)?;
+        let filter_len = u16::try_from(filter.len())
+            .map_err(|_| anyhow!("Seccomp filter of server '{}' is too long", config.name))?;
+        let limits = resource_limits(profile);
+
+        unsafe {
+            cmd.pre_exec(move || {
+                for (resource, limit) in &limits {
+                    let rlimit = libc::rlimit {
+                        rlim_cur: *limit,
+                        rlim_max: *limit,
+                    };
+                    if libc::setrlimit(*resource, &rlimit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+
+                if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+
+                if libc::syscall(libc::SYS_landlock_restrict_self, ruleset.as_raw_fd(), 0u32) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+
+                let program = libc::sock_fprog {
+                    len: filter_len,
+                    filter: filter.as_ptr() as *mut libc::sock_filter,
+                };
+                if libc::prctl(
+                    libc::PR_SET_SECCOMP,
+                    libc::SECCOMP_MODE_FILTER,
+                    &program as *const libc::sock_fprog,
+                ) != 0
+                {
+                    return Err(std::io::Error::last_os_error());
+                }
+
+                Ok(())
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Paths the server may read and write under Landlock
+    fn sandbox_paths(
+        profile: &SandboxProfile,
+        config: &McpServerConfig,
+        executable: &Path,
+    ) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        let mut read_paths: Vec<PathBuf> = profile.read_paths.iter().map(PathBuf::from).collect();
+        let mut write_paths: Vec<PathBuf> =
+            profile.write_paths.iter().map(PathBuf::from).collect();
+
+        if profile.allow_system_paths {
+            read_paths.extend(SYSTEM_READ_PATHS.iter().map(PathBuf::from));
+            write_paths.extend(SYSTEM_WRITE_PATHS.iter().map(PathBuf::from));
+        }
+
+        // The install prefix of the binary, e.g. `~/.nvm/versions/node/v20` for `npx`
+        let executable = executable.canonicalize().unwrap_or(executable.to_path_buf());
+        if let Some(prefix) = executable.parent().and_then(Path::parent) {
+            read_paths.push(prefix.to_path_buf());
+        }
+        read_paths.extend(config.extra_path.iter().map(PathBuf::from));
+
+        if let Some(cwd) = &config.cwd {
+            write_paths.push(PathBuf::from(cwd));
+        }
+
+        (read_paths, write_paths)
+    }
+
+    fn seccomp_filter(profile: &SandboxProfile) -> Result<BpfProgram> {
+        let mut rules: BTreeMap<i64, Vec<SeccompRule>> = DENIED_SYSCALLS
+            .iter()
+            .map(|syscall| (*syscall, Vec::new()))
+            .collect();
+
+        if !profile.allow_network {
+            let socket_rules = [libc::AF_INET, libc::AF_INET6]
+                .into_iter()
+                .map(|family| {
+                    SeccompRule::new(vec![SeccompCondition::new(
+                        0,
+                        SeccompCmpArgLen::Dword,
+                        SeccompCmpOp::Eq,
+                        family as u64,
+                    )?])
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            rules.insert(libc::SYS_socket, socket_rules);
+        }
+
+        let arch = TargetArch::try_from(std::env::consts::ARCH)
+            .map_err(|e| anyhow!("Unsupported architecture for seccomp: {:?}", e))?;
+        let filter = SeccompFilter::new(
+            rules,
+            SeccompAction::Allow,
+            SeccompAction::Errno(libc::EPERM as u32),
+            arch,
+        )?;
+
+        Ok(filter.try_into()?)
+    }
+
+    fn resource_limits(profile: &SandboxProfile) -> Vec<(libc::__rlimit_resource_t, u64)> {
+        let mut limits = Vec::new();
+        if let Some(mb) = profile.max_memory_mb {
+            limits.push((libc::RLIMIT_AS, mb * 1024 * 1024));
+        }
+        if let Some(seconds) = profile.max_cpu_seconds {
+            limits.push((libc::RLIMIT_CPU, seconds));
+        }
+        if let Some(files) = profile.max_open_files {
+            limits.push((libc::RLIMIT_NOFILE, files));
+        }
+        limits
+    }
+}
@@ -0,0 +1,406 @@
+use anyhow::{anyhow, Context, Result};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// File name of the encrypted secret vault in the app data dir
+pub const VAULT_FILE_NAME: &str = "secrets.vault";
+
+/// File name of the vault encryption key in the app data dir, used without a keychain
+pub const KEY_FILE_NAME: &str = "vault.key";
+
+/// Keychain service and account the vault encryption key is stored under
+const KEYCHAIN_SERVICE: &str = "com.craig.orion";
+const KEYCHAIN_ACCOUNT: &str = "mcp-secret-vault";
+
+/// Prefix that keeps a value starting like a reference from being resolved
+pub const LITERAL_PREFIX: &str = "literal:";
+
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// Encrypted local store for secrets referenced from server environments
+///
+/// The vault is a ChaCha20-Poly1305 encrypted JSON map. Values are only ever
+/// read back by [`SecretVault::resolve`] when a server is spawned.
+pub struct SecretVault {
+    path: PathBuf,
+    cipher: ChaCha20Poly1305,
+    secrets: Mutex<BTreeMap<String, String>>,
+}
+
+impl SecretVault {
+    /// Open the vault in the given directory, keeping its key in the OS keychain
+    ///
+    /// Without a usable keychain the key is kept in `vault.key`, readable only by
+    /// the current user; a key file left there is moved into the keychain once it
+    /// works. A vault that can't be decrypted, e.g. because its key was lost, is
+    /// set aside as `secrets.vault.invalid` and a new one started. Opening fails
+    /// instead if the key exists but can't be read right now, so it isn't replaced.
+    pub fn open(dir: &Path) -> Result<Self> {
+        Self::open_with(dir, true)
+    }
+
+    /// Open the vault keeping its key in `vault.key` only, e.g. for tests
+    pub fn open_with_key_file(dir: &Path) -> Result<Self> {
+        Self::open_with(dir, false)
+    }
+
+    fn open_with(dir: &Path, use_keychain: bool) -> Result<Self> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        let cipher = ChaCha20Poly1305::new(&load_or_create_key(dir, use_keychain)?);
+        let path = dir.join(VAULT_FILE_NAME);
+
+        let secrets = match read_vault(&path, &cipher) {
+            Ok(secrets) => secrets,
+            Err(e) => {
+                let aside = path.with_extension("vault.invalid");
+                eprintln!(
+                    "Starting an empty secret vault, moving the old one to {}: {:#}",
+                    aside.display(),
+                    e
+                );
+                if let Err(e) = std::fs::rename(&path, &aside) {
+                    eprintln!("Failed to move {}: {}", path.display(), e);
+                }
+                BTreeMap::new()
+            }
+        };
+
+        Ok(Self {
+            path,
+            cipher,
+            secrets: Mutex::new(secrets),
+        })
+    }
+
+    /// Names of all stored secrets
+    pub fn names(&self) -> Result<Vec<String>> {
+        let secrets = self.secrets.lock().map_err(|e| anyhow!(e.to_string()))?;
+        Ok(secrets.keys().cloned().collect())
+    }
+
+    /// Value of a stored secret
+    pub fn get(&self, name: &str) -> Result<Option<String>> {
+        let secrets = self.secrets.lock().map_err(|e| anyhow!(e.to_string()))?;
+        Ok(secrets.get(name).cloned())
+    }
+
+    /// Store or replace a secret
+    pub fn set(&self, name: &str, value: &str) -> Result<()> {
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(anyhow!("Invalid secret name '{}'", name));
+        }
+
+        let mut secrets = self.secrets.lock().map_err(|e| anyhow!(e.to_string()))?;
+        let mut updated = secrets.clone();
+        updated.insert(name.to_string(), value.to_string());
+        self.write(&updated)?;
+        *secrets = updated;
+        Ok(())
+    }
+
+    /// Delete a secret
+    pub fn remove(&self, name: &str) -> Result<()> {
+        let mut secrets = self.secrets.lock().map_err(|e| anyhow!(e.to_string()))?;
+        if !secrets.contains_key(name) {
+            return Err(anyhow!("Secret '{}' not found", name));
+        }
+
+        let mut updated = secrets.clone();
+        updated.remove(name);
+        self.write(&updated)?;
+        *secrets = updated;
+        Ok(())
+    }
+
+    /// Resolve an environment value that may be a reference
+    ///
+    /// - `secret:NAME` reads `NAME` from the vault
+    /// - `env:VAR` reads `VAR` from Orion's own environment
+    /// - `file:/path` reads the file, without its trailing newline
+    /// - `literal:VALUE` is `VALUE` as is, see [`escape_literal`]
+    ///
+    /// Any other value is returned unchanged.
+    pub fn resolve(&self, value: &str) -> Result<String> {
+        if let Some(literal) = value.strip_prefix(LITERAL_PREFIX) {
+            Ok(literal.to_string())
+        } else if let Some(name) = value.strip_prefix("secret:") {
+            let secrets = self.secrets.lock().map_err(|e| anyhow!(e.to_string()))?;
+            secrets
+                .get(name)
+                .cloned()
+                .ok_or_else(|| anyhow!("Secret '{}' is not in the vault", name))
+        } else if let Some(var) = value.strip_prefix("env:") {
+            std::env::var(var).map_err(|_| anyhow!("Environment variable '{}' is not set", var))
+        } else if let Some(path) = value.strip_prefix("file:") {
+            let contents = std::fs::read_to_string(path)
+                .map_err(|e| anyhow!("Failed to read secret file '{}': {}", path, e))?;
+            Ok(contents.trim_end_matches(['\r', '\n']).to_string())
+        } else {
+            Ok(value.to_string())
+        }
+    }
+
+    /// Encrypt and write the vault atomically
+    fn write(&self, secrets: &BTreeMap<String, String>) -> Result<()> {
+        let plaintext = serde_json::to_vec(secrets)?;
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext.as_slice())
+            .map_err(|_| anyhow!("Failed to encrypt secret vault"))?;
+
+        let mut data = nonce.to_vec();
+        data.extend_from_slice(&ciphertext);
+
+        let tmp_path = self.path.with_extension("vault.tmp");
+        write_private(&tmp_path, &data)?;
+        std::fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("Failed to replace {}", self.path.display()))?;
+
+        Ok(())
+    }
+}
+
+/// Escape a plain value so [`SecretVault::resolve`] returns it unchanged
+pub fn escape_literal(value: &str) -> String {
+    let prefixes = ["secret:", "env:", "file:", LITERAL_PREFIX];
+    if prefixes.iter().any(|prefix| value.starts_with(prefix)) {
+        format!("{}{}", LITERAL_PREFIX, value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Decrypt the vault file, which is empty until the first secret is stored
+fn read_vault(path: &Path, cipher: &ChaCha20Poly1305) -> Result<BTreeMap<String, String>> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let data = std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    if data.len() < NONCE_LEN {
+        return Err(anyhow!("{} is truncated", path.display()));
+    }
+
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow!("Failed to decrypt {}", path.display()))?;
+    Ok(serde_json::from_slice(&plaintext)?)
+}
+
+/// Load the vault key from the keychain or the key file, creating it on first use
+///
+/// Once the vault exists, a key that can't be read is an error rather than a
+/// reason to create one: the vault couldn't be decrypted with a new key, which
+/// would also replace the working key in the keychain on the next launch. A
+/// key file that can't be read is never removed or replaced; one that holds no
+/// valid key is.
+fn load_or_create_key(dir: &Path, use_keychain: bool) -> Result<Key> {
+    let key_path = dir.join(KEY_FILE_NAME);
+    let vault_exists = dir.join(VAULT_FILE_NAME).exists();
+    let file_key = match std::fs::read(&key_path) {
+        Ok(bytes) if bytes.len() == KEY_LEN => Some(*Key::from_slice(&bytes)),
+        Ok(_) => {
+            eprintln!(
+                "{} is not a valid vault key, replacing it",
+                key_path.display()
+            );
+            None
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => {
+            return Err(anyhow!(e).context(format!("Failed to read {}", key_path.display())));
+        }
+    };
+
+    if use_keychain {
+        match keychain_key(file_key) {
+            Ok(key) => {
+                if key_path.exists() {
+                    std::fs::remove_file(&key_path)
+                        .with_context(|| format!("Failed to remove {}", key_path.display()))?;
+                }
+                return Ok(key);
+            }
+            Err(e) if vault_exists && file_key.is_none() => {
+                return Err(e.context("Failed to read the vault key from the keychain"));
+            }
+            Err(e) => eprintln!(
+                "Keychain unavailable, keeping the vault key in {}: {:#}",
+                key_path.display(),
+                e
+            ),
+        }
+    }
+
+    match file_key {
+        Some(key) => Ok(key),
+        None => {
+            let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+            write_private(&key_path, &key)?;
+            Ok(key)
+        }
+    }
+}
+
+/// Read the vault key from the OS keychain
+///
+/// A key from the key file replaces the keychain's, since the vault was last
+/// written with it. A missing or invalid key is replaced with a new one.
+fn keychain_key(file_key: Option<Key>) -> Result<Key> {
+    // The Secret Service backend blocks on a runtime of its own, which panics
+    // on a thread that already runs Tokio
+    std::thread::spawn(move || -> Result<Key> {
+        let entry = keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT)?;
+        if file_key.is_none() {
+            match entry.get_secret() {
+                Ok(bytes) if bytes.len() == KEY_LEN => return Ok(*Key::from_slice(&bytes)),
+                Ok(_) => eprintln!("Replacing the invalid vault key in the keychain"),
+                Err(keyring::Error::NoEntry) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        let key = file_key.unwrap_or_else(|| ChaCha20Poly1305::generate_key(&mut OsRng));
+        entry.set_secret(&key)?;
+        Ok(key)
+    })
+    .join()
+    .map_err(|_| anyhow!("Keychain access panicked"))?
+}
+
+/// Write a file readable only by the current user
+fn write_private(path: &Path, data: &[u8]) -> Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    std::io::Write::write_all(&mut file, data)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "orion-secrets-test-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn keeps_secrets_across_reopening() {
+        let dir = test_dir("round-trip");
+        let vault = SecretVault::open_with_key_file(&dir).unwrap();
+        vault.set("TOKEN", "hunter2").unwrap();
+        vault.set("OTHER", "x").unwrap();
+        vault.remove("OTHER").unwrap();
+        assert!(vault.set("BAD NAME", "x").is_err());
+
+        let reopened = SecretVault::open_with_key_file(&dir).unwrap();
+        assert_eq!(reopened.names().unwrap(), ["TOKEN"]);
+        assert_eq!(reopened.resolve("secret:TOKEN").unwrap(), "hunter2");
+        assert!(reopened.resolve("secret:OTHER").is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn resolves_references_and_escaped_literals() {
+        let dir = test_dir("resolve");
+        let vault = SecretVault::open_with_key_file(&dir).unwrap();
+        let file = dir.join("token.txt");
+        std::fs::write(&file, "from-file\n").unwrap();
+
+        assert_eq!(vault.resolve("plain").unwrap(), "plain");
+        assert_eq!(
+            vault.resolve(&format!("file:{}", file.display())).unwrap(),
+            "from-file"
+        );
+        assert_eq!(
+            vault.resolve("env:PATH").unwrap(),
+            std::env::var("PATH").unwrap()
+        );
+        assert!(vault.resolve("env:ORION_SURELY_UNSET").is_err());
+
+        for value in [
+            "secret:abc",
+            "env:PATH",
+            "file:/etc/passwd",
+            "literal:x",
+            "plain",
+        ] {
+            assert_eq!(vault.resolve(&escape_literal(value)).unwrap(), value);
+        }
+        assert_eq!(escape_literal("plain"), "plain");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn starts_over_when_the_vault_cannot_be_decrypted() {
+        let dir = test_dir("corrupt");
+        SecretVault::open_with_key_file(&dir)
+            .unwrap()
+            .set("TOKEN", "hunter2")
+            .unwrap();
+
+        // A lost key makes the vault unreadable
+        std::fs::write(dir.join(KEY_FILE_NAME), b"too short").unwrap();
+        let vault = SecretVault::open_with_key_file(&dir).unwrap();
+        assert!(vault.names().unwrap().is_empty());
+        assert!(dir.join("secrets.vault.invalid").exists());
+
+        vault.set("TOKEN", "new").unwrap();
+        let reopened = SecretVault::open_with_key_file(&dir).unwrap();
+        assert_eq!(reopened.resolve("secret:TOKEN").unwrap(), "new");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+    #[test]
+    fn refuses_to_replace_a_key_file_it_cannot_read() {
+        let dir = test_dir("unreadable");
+        SecretVault::open_with_key_file(&dir)
+            .unwrap()
+            .set("TOKEN", "hunter2")
+            .unwrap();
+
+        // Reading a directory fails like a key file without read permission
+        let key_path = dir.join(KEY_FILE_NAME);
+        let key = std::fs::read(&key_path).unwrap();
+        std::fs::remove_file(&key_path).unwrap();
+        std::fs::create_dir(&key_path).unwrap();
+        assert!(SecretVault::open_with_key_file(&dir).is_err());
+        assert!(key_path.is_dir());
+        assert!(!dir.join("secrets.vault.invalid").exists());
+
+        std::fs::remove_dir(&key_path).unwrap();
+        std::fs::write(&key_path, key).unwrap();
+        let reopened = SecretVault::open_with_key_file(&dir).unwrap();
+        assert_eq!(reopened.resolve("secret:TOKEN").unwrap(), "hunter2");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,373 @@
+use anyhow::{anyhow, Result};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc;
+
+use super::protocol::{
+    default_max_frame_bytes, CallToolParams, CallToolResult, GetPromptParams, GetPromptResult,
+    JsonRpcResponse, ListPromptsResult, ListResourcesResult, ListToolsResult, McpTool, Prompt,
+    ReadResourceParams, ReadResourceResult, Resource, ServerCapabilities, ServerInfo,
+    INTERNAL_ERROR, INVALID_PARAMS, INVALID_REQUEST, METHOD_NOT_FOUND, PARSE_ERROR,
+};
+use super::transport::{read_frames, Frame};
+
+/// Protocol version answered to clients
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Server side of MCP: what Orion exposes to other clients
+///
+/// Only tools are required; resources and prompts default to empty lists.
+pub trait McpHandler: Send + Sync + 'static {
+    fn server_info(&self) -> ServerInfo;
+
+    fn capabilities(&self) -> ServerCapabilities;
+
+    fn list_tools(&self) -> impl Future<Output = Result<Vec<McpTool>>> + Send;
+
+    fn call_tool(
+        &self,
+        name: &str,
+        arguments: Option<Value>,
+    ) -> impl Future<Output = Result<CallToolResult>> + Send;
+
+    fn list_resources(&self) -> impl Future<Output = Result<Vec<Resource>>> + Send {
+        async { Ok(Vec::new()) }
+    }
+
+    fn read_resource(&self, uri: &str) -> impl Future<Output = Result<ReadResourceResult>> + Send {
+        let uri = uri.to_string();
+        async move { Err(anyhow!("Unknown resource '{}'", uri)) }
+    }
+
+    fn list_prompts(&self) -> impl Future<Output = Result<Vec<Prompt>>> + Send {
+        async { Ok(Vec::new()) }
+    }
+
+    fn get_prompt(
+        &self,
+        name: &str,
+        _arguments: HashMap<String, String>,
+    ) -> impl Future<Output = Result<GetPromptResult>> + Send {
+        let name = name.to_string();
+        async move { Err(anyhow!("Unknown prompt '{}'", name)) }
+    }
+}
+
+/// Serve MCP over a pair of byte streams until the client disconnects
+///
+/// Requests are handled concurrently, so a slow tool call does not block others.
+pub async fn serve<H, R, W>(handler: Arc<H>, reader: R, writer: W) -> Result<()>
+where
+    H: McpHandler,
+    R: AsyncRead + Unpin + Send + 'static,
+    W: AsyncWrite + Unpin,
+{
+    serve_with(reader, writer, move |message| {
+        let handler = handler.clone();
+        async move { handle_message(handler.as_ref(), &message).await }
+    })
+    .await
+}
+
+/// Answer JSON-RPC messages on a pair of byte streams until the client disconnects
+///
+/// Each message is passed to `respond` concurrently, and whatever it answers is
+/// written back in the order the answers arrive.
+pub async fn serve_with<R, W, F, Fut>(reader: R, mut writer: W, respond: F) -> Result<()>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+    W: AsyncWrite + Unpin,
+    F: Fn(String) -> Fut + Send + 'static,
+    Fut: Future<Output = Option<JsonRpcResponse>> + Send + 'static,
+{
+    let (frame_tx, mut frame_rx) = mpsc::channel::<Frame>(100);
+    tokio::spawn(read_frames(reader, default_max_frame_bytes(), frame_tx));
+
+    let (response_tx, mut response_rx) = mpsc::channel::<JsonRpcResponse>(100);
+    tokio::spawn(async move {
+        while let Some(frame) = frame_rx.recv().await {
+            let response_tx = response_tx.clone();
+            match frame {
+                Frame::Line(line) => {
+                    let response = respond(line);
+                    tokio::spawn(async move {
+                        if let Some(response) = response.await {
+                            let _ = response_tx.send(response).await;
+                        }
+                    });
+                }
+                Frame::TooLarge { .. } | Frame::InvalidUtf8(_) => {
+                    let response =
+                        JsonRpcResponse::failure(Value::Null, PARSE_ERROR, "Unreadable request");
+                    let _ = response_tx.send(response).await;
+                }
+            }
+        }
+    });
+
+    // Ends once the client disconnects and every running request has answered
+    while let Some(response) = response_rx.recv().await {
+        let json = serde_json::to_string(&response)?;
+        writer.write_all(json.as_bytes()).await?;
+        writer.write_all(b"\n").await?;
+        writer.flush().await?;
+    }
+
+    Ok(())
+}
+
+/// Serve MCP on the process's stdin and stdout
+pub async fn serve_stdio<H: McpHandler>(handler: Arc<H>) -> Result<()> {
+    serve(handler, tokio::io::stdin(), tokio::io::stdout()).await
+}
+
+/// Handle one JSON-RPC message
+///
+/// Returns `None` for notifications (no ID, or a null ID), which get no response.
+pub async fn handle_message<H: McpHandler>(handler: &H, message: &str) -> Option<JsonRpcResponse> {
+    let message: Value = match serde_json::from_str(message) {
+        Ok(message) => message,
+        Err(e) => {
+            return Some(JsonRpcResponse::failure(
+                Value::Null,
+                PARSE_ERROR,
+                e.to_string(),
+            ))
+        }
+    };
+
+    let Some(method) = message.get("method").and_then(Value::as_str) else {
+        return Some(JsonRpcResponse::failure(
+            message.get("id").cloned().unwrap_or(Value::Null),
+            INVALID_REQUEST,
+            "Missing method",
+        ));
+    };
+
+    let id = match message.get("id") {
+        None | Some(Value::Null) => return None,
+        Some(id) => id.clone(),
+    };
+
+    let params = message.get("params").cloned();
+    Some(match handle_request(handler, method, params).await {
+        Ok(result) => JsonRpcResponse::success(id, result),
+        Err(RequestError(code, message)) => JsonRpcResponse::failure(id, code, message),
+    })
+}
+
+/// JSON-RPC error code and message
+struct RequestError(i32, String);
+
+impl From<anyhow::Error> for RequestError {
+    fn from(error: anyhow::Error) -> Self {
+        Self(INTERNAL_ERROR, error.to_string())
+    }
+}
+
+impl From<serde_json::Error> for RequestError {
+    fn from(error: serde_json::Error) -> Self {
+        Self(INTERNAL_ERROR, error.to_string())
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(params: Option<Value>) -> Result<T, RequestError> {
+    serde_json::from_value(params.unwrap_or(Value::Null))
+        .map_err(|e| RequestError(INVALID_PARAMS, e.to_string()))
+}
+
+async fn handle_request<H: McpHandler>(
+    handler: &H,
+    method: &str,
+    params: Option<Value>,
+) -> Result<Value, RequestError> {
+    match method {
+        "initialize" => Ok(json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": handler.capabilities(),
+            "serverInfo": handler.server_info(),
+        })),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(serde_json::to_value(ListToolsResult {
+            tools: handler.list_tools().await?,
+        })?),
+        "tools/call" => {
+            let params: CallToolParams = parse_params(params)?;
+            // Tool failures are reported to the model as error results, not protocol errors
+            let result = handler
+                .call_tool(&params.name, params.arguments)
+                .await
+                .unwrap_or_else(|e| CallToolResult::error(e.to_string()));
+            Ok(serde_json::to_value(result)?)
+        }
+        "resources/list" => Ok(serde_json::to_value(ListResourcesResult {
+            resources: handler.list_resources().await?,
+        })?),
+        "resources/read" => {
+            let params: ReadResourceParams = parse_params(params)?;
+            Ok(serde_json::to_value(
+                handler.read_resource(&params.uri).await?,
+            )?)
+        }
+        "prompts/list" => Ok(serde_json::to_value(ListPromptsResult {
+            prompts: handler.list_prompts().await?,
+        })?),
+        "prompts/get" => {
+            let params: GetPromptParams = parse_params(params)?;
+            let arguments = params.arguments.unwrap_or_default();
+            Ok(serde_json::to_value(
+                handler.get_prompt(&params.name, arguments).await?,
+            )?)
+        }
+        _ => Err(RequestError(
+            METHOD_NOT_FOUND,
+            format!("Method '{}' not found", method),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    struct EchoHandler;
+
+    impl McpHandler for EchoHandler {
+        fn server_info(&self) -> ServerInfo {
+            ServerInfo {
+                name: "echo".to_string(),
+                version: "1.0.0".to_string(),
+            }
+        }
+
+        fn capabilities(&self) -> ServerCapabilities {
+            ServerCapabilities {
+                experimental: None,
+                logging: None,
+                prompts: None,
+                resources: None,
+                tools: Some(json!({})),
+            }
+        }
+
+        async fn list_tools(&self) -> Result<Vec<McpTool>> {
+            Ok(vec![McpTool {
+                name: "echo".to_string(),
+                description: "Echo the arguments".to_string(),
+                input_schema: json!({ "type": "object" }),
+                annotations: None,
+            }])
+        }
+
+        async fn call_tool(&self, name: &str, arguments: Option<Value>) -> Result<CallToolResult> {
+            match name {
+                "echo" => Ok(CallToolResult::text(
+                    arguments.unwrap_or(Value::Null).to_string(),
+                )),
+                _ => Err(anyhow!("Unknown tool '{}'", name)),
+            }
+        }
+    }
+
+    async fn handle(message: Value) -> Option<JsonRpcResponse> {
+        handle_message(&EchoHandler, &message.to_string()).await
+    }
+
+    fn error_code(response: Option<JsonRpcResponse>) -> i32 {
+        response.unwrap().error.unwrap().code
+    }
+
+    #[tokio::test]
+    async fn answers_requests_with_results_and_errors() {
+        let response = handle(json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize" }))
+            .await
+            .unwrap();
+        let result = response.result.unwrap();
+        assert_eq!(result["protocolVersion"], PROTOCOL_VERSION);
+        assert_eq!(result["serverInfo"]["name"], "echo");
+
+        let response = handle(json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "tools/call",
+            "params": { "name": "echo", "arguments": { "n": 1 } },
+        }))
+        .await
+        .unwrap();
+        assert_eq!(response.result.unwrap()["content"][0]["text"], r#"{"n":1}"#);
+
+        // Failing tools answer with an error result the model can read
+        let response = handle(json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "tools/call",
+            "params": { "name": "missing" },
+        }))
+        .await
+        .unwrap();
+        assert_eq!(response.result.unwrap()["isError"], true);
+
+        // Handlers without resources or prompts answer with empty lists
+        let response = handle(json!({ "jsonrpc": "2.0", "id": 4, "method": "prompts/list" }))
+            .await
+            .unwrap();
+        assert_eq!(response.result.unwrap(), json!({ "prompts": [] }));
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_messages_and_skips_notifications() {
+        assert!(
+            handle(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+                .await
+                .is_none()
+        );
+
+        let response = handle_message(&EchoHandler, "{ not json").await;
+        assert_eq!(error_code(response), PARSE_ERROR);
+
+        let response = handle(json!({ "jsonrpc": "2.0", "id": 1 })).await;
+        assert_eq!(error_code(response), INVALID_REQUEST);
+
+        let response = handle(json!({ "jsonrpc": "2.0", "id": 2, "method": "nope" })).await;
+        assert_eq!(error_code(response), METHOD_NOT_FOUND);
+
+        let response = handle(json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "resources/read",
+            "params": { "url": "typo" },
+        }))
+        .await;
+        assert_eq!(error_code(response), INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn serves_a_client_until_it_disconnects() {
+        let (mut client_writer, server_reader) = tokio::io::duplex(4096);
+        let (server_writer, client_reader) = tokio::io::duplex(4096);
+        let serving = tokio::spawn(serve(Arc::new(EchoHandler), server_reader, server_writer));
+
+        client_writer
+            .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"ping\"}\nnot json\n")
+            .await
+            .unwrap();
+
+        let mut lines = BufReader::new(client_reader).lines();
+        let mut responses = Vec::new();
+        for _ in 0..2 {
+            let line = lines.next_line().await.unwrap().unwrap();
+            responses.push(serde_json::from_str::<JsonRpcResponse>(&line).unwrap());
+        }
+        responses.sort_by_key(|response| response.id.is_null());
+        assert_eq!(responses[0].result, Some(json!({})));
+        assert_eq!(responses[1].error.as_ref().unwrap().code, PARSE_ERROR);
+
+        drop(client_writer);
+        serving.await.unwrap().unwrap();
+    }
+}
@@ -1,46 +1,166 @@
 use anyhow::{anyhow, Result};
+use serde::Deserialize;
 use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::Mutex;
-
+use tokio::time::{Duration, Instant};
+
+use super::audit::{ApprovalDecision, AuditEntry, McpAuditLog, AUDIT_DIR_NAME};
+use super::cache::{CacheKey, ResultCache};
+use super::cancel::CancelToken;
+use super::error::{CancelledError, SandboxError, TimeoutError};
+use super::limits::{CallLimiter, CallQueueStatus};
+use super::metrics::McpMetrics;
+use super::naming::{ToolNameRegistry, ToolRef};
+use super::process::ProcessInfo;
 use super::protocol::{
     CallToolParams, CallToolResult, ClientCapabilities, ClientInfo, InitializeParams,
-    InitializeResult, ListToolsResult, McpServerConfig, McpTool,
+    InitializeResult, ListToolsResult, McpServerConfig, McpTool, OpenAITool, ToolAnnotations,
+};
+use super::results::{
+    self, ResultStore, BUILTIN_SERVER_ID, BUILTIN_SERVER_NAME, READ_RESULT_TOOL, RESULTS_DIR_NAME,
+};
+use super::sandbox;
+use super::secrets::SecretVault;
+use super::trace::{self, ReplayTransport, TraceEntry, TRACE_DIR_NAME};
+use super::transport::{
+    ResponseTimeout, ServerDiagnostics, StdioTransport, Transport, TransportLog,
 };
-use super::transport::StdioTransport;
 
 /// MCP server instance
 pub struct McpServer {
     pub config: McpServerConfig,
-    transport: Arc<Mutex<StdioTransport>>,
+    pub process: ProcessInfo,
+    log: TransportLog,
+    transport: Transport,
     next_request_id: Arc<Mutex<u64>>,
+    limiter: CallLimiter,
+    /// Count of change notifications from the server, to expire cached results
+    changes: Arc<AtomicU64>,
+    /// Annotations from the last tool listing, keyed by tool name
+    annotations: std::sync::Mutex<HashMap<String, ToolAnnotations>>,
     pub server_info: Option<InitializeResult>,
 }
 
 impl McpServer {
     /// Create and initialize a new MCP server
-    pub async fn spawn(config: McpServerConfig) -> Result<Self> {
-        let transport = StdioTransport::spawn(
-            &config.command,
-            &config.args,
-            config.env.as_ref(),
+    ///
+    /// Traffic is recorded under `trace_dir` when the config asks for it.
+    pub async fn spawn(
+        config: McpServerConfig,
+        secrets: &SecretVault,
+        trace_dir: &Path,
+    ) -> Result<Self> {
+        let trace_dir = config.record_traffic.then_some(trace_dir);
+        let transport = StdioTransport::spawn(&config, secrets, trace_dir).await?;
+        let process = transport.process().clone();
+        let log = transport.log();
+        let changes = transport.changes();
+
+        let transport = Transport::Stdio(Box::new(transport));
+        Self::initialize_with(config, process, log, changes, transport).await
+    }
+
+    /// Create a server that plays back a recorded trace instead of running a process
+    pub async fn replay(config: McpServerConfig, trace_path: &Path) -> Result<Self> {
+        let transport = ReplayTransport::load(trace_path)?;
+        let process = ProcessInfo {
+            executable: trace_path.display().to_string(),
+            pid: None,
+            cwd: None,
+        };
+
+        Self::initialize_with(
+            config,
+            process,
+            TransportLog::default(),
+            Arc::default(),
+            Transport::Replay(std::sync::Mutex::new(transport)),
         )
-        .await?;
+        .await
+    }
 
+    async fn initialize_with(
+        config: McpServerConfig,
+        process: ProcessInfo,
+        log: TransportLog,
+        changes: Arc<AtomicU64>,
+        transport: Transport,
+    ) -> Result<Self> {
         let mut server = Self {
+            limiter: CallLimiter::new(&config),
             config,
-            transport: Arc::new(Mutex::new(transport)),
+            process,
+            log,
+            changes,
+            annotations: std::sync::Mutex::default(),
+            transport,
             next_request_id: Arc::new(Mutex::new(1)),
             server_info: None,
         };
 
         // Initialize the server
-        server.initialize().await?;
+        if let Err(e) = server.initialize().await {
+            return Err(server.explain_failure(e).await);
+        }
 
         Ok(server)
     }
 
+    /// Skipped output and recent stderr of the server
+    pub fn diagnostics(&self) -> ServerDiagnostics {
+        self.log.snapshot()
+    }
+
+    /// Calls in flight and waiting for the server's concurrency and rate limits
+    pub fn queue_status(&self) -> CallQueueStatus {
+        self.limiter.status()
+    }
+
+    /// Attach sandbox context to a failure of a sandboxed server that reported denials
+    async fn explain_failure(&self, error: anyhow::Error) -> anyhow::Error {
+        if self.config.sandbox.is_none() || error.is::<TimeoutError>() {
+            return error;
+        }
+
+        // Give the stderr reader a moment to catch up with a process that just died
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        let denials = sandbox::denial_lines(&self.log.stderr_tail());
+        if denials.is_empty() {
+            return error;
+        }
+
+        SandboxError {
+            server_name: self.config.name.clone(),
+            cause: error.to_string(),
+            denials,
+        }
+        .into()
+    }
+
+    /// Whether results of the tool may be served from the cache
+    ///
+    /// Annotations are known once the tools have been listed; before that only
+    /// per-tool overrides enable caching.
+    fn cacheable(&self, tool_name: &str) -> bool {
+        let annotations = self
+            .annotations
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        self.config
+            .cache
+            .for_tool(tool_name, annotations.get(tool_name))
+    }
+
+    /// Whether the server's policy lets the tool be listed and called
+    pub fn tool_enabled(&self, tool_name: &str) -> bool {
+        !self.config.disabled_tools.iter().any(|name| name == tool_name)
+    }
+
     /// Get next request ID
     async fn next_id(&self) -> u64 {
         let mut id = self.next_request_id.lock().await;
@@ -66,13 +186,11 @@ impl McpServer {
         let id = self.next_id().await;
         let response = self
             .transport
-            .lock()
-            .await
             .call(
                 "initialize",
                 Some(serde_json::to_value(params)?),
                 json!(id),
-                30000, // 30 second timeout for initialization
+                ResponseTimeout::fixed(self.config.timeouts.initialize_ms),
             )
             .await?;
 
@@ -98,7 +216,7 @@ impl McpServer {
             id: json!(null),
         };
 
-        self.transport.lock().await.send_request(&notification).await?;
+        self.transport.send_request(&notification).await?;
 
         Ok(())
     }
@@ -108,15 +226,17 @@ impl McpServer {
         let id = self.next_id().await;
         let response = self
             .transport
-            .lock()
-            .await
             .call(
                 "tools/list",
                 None,
                 json!(id),
-                10000, // 10 second timeout
+                ResponseTimeout::fixed(self.config.timeouts.list_ms),
             )
-            .await?;
+            .await;
+        let response = match response {
+            Ok(response) => response,
+            Err(e) => return Err(self.explain_failure(e).await),
+        };
 
         if let Some(error) = response.error {
             return Err(anyhow!(
@@ -130,28 +250,76 @@ impl McpServer {
             response.result.ok_or_else(|| anyhow!("No result in tools/list response"))?,
         )?;
 
-        Ok(result.tools)
+        let tools: Vec<McpTool> = result
+            .tools
+            .into_iter()
+            .filter(|tool| self.tool_enabled(&tool.name))
+            .collect();
+
+        *self
+            .annotations
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = tools
+            .iter()
+            .filter_map(|tool| Some((tool.name.clone(), tool.annotations.clone()?)))
+            .collect();
+        Ok(tools)
     }
 
     /// Call a tool
-    pub async fn call_tool(&self, tool_name: &str, arguments: Option<Value>) -> Result<CallToolResult> {
+    ///
+    /// `timeout_ms` overrides the configured timeout for this call only. When
+    /// `abandoned` resolves first, the server is told to stop with a
+    /// `notifications/cancelled` and the call fails with the error it resolved to.
+    pub async fn call_tool(
+        &self,
+        tool_name: &str,
+        arguments: Option<Value>,
+        timeout_ms: Option<u64>,
+        abandoned: impl Future<Output = anyhow::Error>,
+    ) -> Result<CallToolResult> {
+        if !self.tool_enabled(tool_name) {
+            return Err(anyhow!(
+                "Tool '{}' is disabled for server '{}'",
+                tool_name,
+                self.config.name
+            ));
+        }
+
+        let timeouts = &self.config.timeouts;
+        let timeout = ResponseTimeout {
+            timeout_ms: timeout_ms.unwrap_or_else(|| timeouts.for_tool(tool_name)),
+            reset_on_progress: timeouts.reset_on_progress,
+        };
+
+        let id = self.next_id().await;
+
+        // Ask for progress notifications, using the request ID as the token
         let params = CallToolParams {
             name: tool_name.to_string(),
             arguments,
+            meta: timeout
+                .reset_on_progress
+                .then(|| json!({ "progressToken": id })),
         };
 
-        let id = self.next_id().await;
-        let response = self
-            .transport
-            .lock()
-            .await
-            .call(
-                "tools/call",
-                Some(serde_json::to_value(params)?),
-                json!(id),
-                60000, // 60 second timeout for tool calls
-            )
-            .await?;
+        let params = serde_json::to_value(params)?;
+        let call = async {
+            self.transport
+                .call("tools/call", Some(params), json!(id), timeout)
+                .await
+        };
+        let response = tokio::select! {
+            response = call => response,
+            error = abandoned => {
+                self.send_cancelled(id, &error).await;
+                return Err(error);
+            }
+        };
+        let response = match response {
+            Ok(response) => response,
+            Err(e) => return Err(self.explain_failure(e).await),
+        };
 
         if let Some(error) = response.error {
             return Err(anyhow!(
@@ -167,52 +335,190 @@ impl McpServer {
 
         Ok(result)
     }
+
+    /// Tell the server a request was abandoned, so it can stop working on it
+    ///
+    /// Its late response, if any, is skipped like one to a timed out request.
+    async fn send_cancelled(&self, id: u64, reason: &anyhow::Error) {
+        let notification = super::protocol::JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "notifications/cancelled".to_string(),
+            params: Some(json!({ "requestId": id, "reason": reason.to_string() })),
+            id: json!(null),
+        };
+        if let Err(e) = self.transport.send_request(&notification).await {
+            eprintln!(
+                "Failed to notify MCP server '{}' of a cancelled request: {}",
+                self.config.name, e
+            );
+        }
+    }
+}
+
+/// Per-call options for [`McpServerManager::call_tool`]
+#[derive(Debug, Clone, Default)]
+pub struct CallOptions {
+    /// Overrides the configured timeout for this call only
+    pub timeout_ms: Option<u64>,
+    /// Conversation the call was made from, for the audit log
+    pub conversation_id: Option<String>,
+    pub approval: ApprovalDecision,
+    /// Abandons the call, including while it waits for the server's limits
+    pub cancel: Option<CancelToken>,
+    /// Time by which the call must be answered, including waiting for the
+    /// server's limits; it then fails with a [`TimeoutError`] like a cancelled one
+    pub deadline: Option<Instant>,
+}
+
+/// One call of a [`McpServerManager::call_tools_batch`] batch
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchCall {
+    pub server_id: String,
+    pub tool_name: String,
+    #[serde(default)]
+    pub arguments: Option<Value>,
+    /// Overrides the configured timeout for this call only
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub approval: ApprovalDecision,
 }
 
 /// Manager for multiple MCP servers
+///
+/// Clones share the same running servers, e.g. between the app and the gateway.
+#[derive(Clone)]
 pub struct McpServerManager {
     servers: Arc<Mutex<HashMap<String, Arc<McpServer>>>>,
+    tool_names: Arc<Mutex<ToolNameRegistry>>,
+    secrets: Arc<SecretVault>,
+    trace_dir: PathBuf,
+    audit: Arc<McpAuditLog>,
+    metrics: Arc<McpMetrics>,
+    results: Arc<ResultStore>,
+    cache: Arc<ResultCache>,
 }
 
 impl McpServerManager {
-    pub fn new() -> Self {
-        Self {
+    /// Create a manager keeping traces, the audit log and large results under `data_dir`
+    pub fn new(secrets: Arc<SecretVault>, data_dir: &Path) -> Result<Self> {
+        Ok(Self {
             servers: Arc::new(Mutex::new(HashMap::new())),
-        }
+            tool_names: Arc::new(Mutex::new(ToolNameRegistry::default())),
+            secrets,
+            trace_dir: data_dir.join(TRACE_DIR_NAME),
+            audit: Arc::new(McpAuditLog::open(&data_dir.join(AUDIT_DIR_NAME))?),
+            metrics: Arc::new(McpMetrics::default()),
+            results: Arc::new(ResultStore::open(&data_dir.join(RESULTS_DIR_NAME))?),
+            cache: Arc::new(ResultCache::default()),
+        })
+    }
+
+    /// Secret vault used to resolve server environment references
+    pub fn secrets(&self) -> &SecretVault {
+        &self.secrets
+    }
+
+    /// Log of every tool call made through the manager
+    pub fn audit_log(&self) -> &McpAuditLog {
+        &self.audit
+    }
+
+    /// Call, startup and restart statistics of every server started so far
+    pub fn metrics(&self) -> &McpMetrics {
+        &self.metrics
     }
 
     /// Start a new MCP server
     pub async fn start_server(&self, config: McpServerConfig) -> Result<String> {
+        self.ensure_not_running(&config.id).await?;
+
+        // Spawn and initialize the server
         let server_id = config.id.clone();
+        let started = std::time::Instant::now();
+        let outcome = match McpServer::spawn(config, &self.secrets, &self.trace_dir).await {
+            Ok(server) => Ok(self.insert(server).await),
+            Err(e) => Err(e),
+        };
+        self.metrics
+            .record_start(&server_id, &outcome, started.elapsed().as_millis() as u64);
+        outcome
+    }
 
-        // Check if server already exists
-        {
-            let servers = self.servers.lock().await;
-            if servers.contains_key(&server_id) {
-                return Err(anyhow!("Server with ID '{}' already running", server_id));
-            }
-        }
+    /// Start a server that answers from a recorded trace file
+    ///
+    /// Lets a misbehaving session be reproduced without the real server; it is
+    /// listed and called like any other server.
+    pub async fn start_replay(
+        &self,
+        server_id: &str,
+        name: &str,
+        trace_path: &Path,
+    ) -> Result<String> {
+        self.ensure_not_running(server_id).await?;
+
+        let config: McpServerConfig = serde_json::from_value(json!({
+            "id": server_id,
+            "name": name,
+            "command": trace_path.display().to_string(),
+            "args": [],
+        }))?;
+        let server = McpServer::replay(config, trace_path).await?;
+        Ok(self.insert(server).await)
+    }
 
-        // Spawn and initialize the server
-        let server = McpServer::spawn(config).await?;
-        let server_arc = Arc::new(server);
+    async fn ensure_not_running(&self, server_id: &str) -> Result<()> {
+        if self.servers.lock().await.contains_key(server_id) {
+            return Err(anyhow!("Server with ID '{}' already running", server_id));
+        }
+        Ok(())
+    }
 
-        // Store the server
-        self.servers.lock().await.insert(server_id.clone(), server_arc);
+    async fn insert(&self, server: McpServer) -> String {
+        let server_id = server.config.id.clone();
+        self.servers
+            .lock()
+            .await
+            .insert(server_id.clone(), Arc::new(server));
+        server_id
+    }
 
-        Ok(server_id)
+    /// Most recent recorded frames of a server, oldest first
+    ///
+    /// Works for stopped servers too, as long as their trace file exists.
+    pub fn trace(&self, server_id: &str, limit: usize) -> Result<Vec<TraceEntry>> {
+        trace::read_trace(&trace::trace_path(&self.trace_dir, server_id), limit)
     }
 
     /// Stop an MCP server
     pub async fn stop_server(&self, server_id: &str) -> Result<()> {
         let mut servers = self.servers.lock().await;
         if servers.remove(server_id).is_some() {
+            self.tool_names.lock().await.remove_server(server_id);
+            self.cache.invalidate_server(server_id);
             Ok(())
         } else {
             Err(anyhow!("Server '{}' not found", server_id))
         }
     }
 
+    /// Stop a server and start it again with the same configuration
+    pub async fn restart_server(&self, server_id: &str) -> Result<()> {
+        let server = self.get_server(server_id).await?;
+        // Only replayed servers run without a process
+        if server.process.pid.is_none() {
+            return Err(anyhow!("Replayed server '{}' can't be restarted", server_id));
+        }
+        let config = server.config.clone();
+        drop(server);
+
+        self.stop_server(server_id).await?;
+        self.metrics.record_restart(server_id);
+        self.start_server(config).await?;
+        Ok(())
+    }
+
     /// Get a server by ID
     pub async fn get_server(&self, server_id: &str) -> Result<Arc<McpServer>> {
         let servers = self.servers.lock().await;
@@ -234,20 +540,271 @@ impl McpServerManager {
         server.list_tools().await
     }
 
+    /// List tools from a specific server with provider-safe names
+    pub async fn list_provider_tools(&self, server_id: &str) -> Result<Vec<OpenAITool>> {
+        let server_name = self.get_server(server_id).await?.config.name.clone();
+        let tools = self.list_tools(server_id).await?;
+
+        let mut tool_names = self.tool_names.lock().await;
+        Ok(tools
+            .into_iter()
+            .map(|tool| {
+                let safe_name = tool_names.register(server_id, &server_name, &tool.name);
+                let mut openai_tool: OpenAITool = tool.into();
+                openai_tool.function.name = safe_name;
+                openai_tool
+            })
+            .collect())
+    }
+
+    /// Tools Orion provides itself, with provider-safe names
+    ///
+    /// Currently `read_tool_result`, which pages through truncated results.
+    pub async fn list_builtin_tools(&self) -> Vec<McpTool> {
+        let mut tool = results::read_result_tool();
+        tool.name = self.tool_names.lock().await.register(
+            BUILTIN_SERVER_ID,
+            BUILTIN_SERVER_NAME,
+            &tool.name,
+        );
+        vec![tool]
+    }
+
+    /// List tools of every running server exposed via the gateway, with namespaced names
+    ///
+    /// Servers that fail to list their tools are skipped so one broken server doesn't
+    /// hide the rest.
+    pub async fn list_gateway_tools(&self) -> Vec<McpTool> {
+        let servers: Vec<Arc<McpServer>> = self.servers.lock().await.values().cloned().collect();
+
+        let mut tools = Vec::new();
+        for server in servers {
+            if !server.config.expose_via_gateway {
+                continue;
+            }
+            let server_tools = match server.list_tools().await {
+                Ok(server_tools) => server_tools,
+                Err(e) => {
+                    eprintln!("Gateway skipped MCP server '{}': {}", server.config.name, e);
+                    continue;
+                }
+            };
+
+            let mut tool_names = self.tool_names.lock().await;
+            tools.extend(server_tools.into_iter().map(|mut tool| {
+                tool.name = tool_names.register(&server.config.id, &server.config.name, &tool.name);
+                tool
+            }));
+        }
+        tools.extend(self.list_builtin_tools().await);
+        tools
+    }
+
+    /// Call a tool by its namespaced gateway name
+    pub async fn call_gateway_tool(
+        &self,
+        name: &str,
+        arguments: Option<Value>,
+    ) -> Result<CallToolResult> {
+        let tool = self.resolve_tool_name(name).await?;
+        if tool.server_id != BUILTIN_SERVER_ID {
+            let server = self.get_server(&tool.server_id).await?;
+            if !server.config.expose_via_gateway {
+                return Err(anyhow!("Unknown tool '{}'", name));
+            }
+        }
+        self.call_tool(
+            &tool.server_id,
+            &tool.tool_name,
+            arguments,
+            CallOptions::default(),
+        )
+        .await
+    }
+
+    /// Map a provider-safe tool name back to its server and original tool name
+    pub async fn resolve_tool_name(&self, safe_name: &str) -> Result<ToolRef> {
+        self.tool_names
+            .lock()
+            .await
+            .resolve(safe_name)
+            .cloned()
+            .ok_or_else(|| anyhow!("Unknown tool '{}'", safe_name))
+    }
+
     /// Call a tool on a specific server
+    ///
+    /// Every call is written to the audit log. Denied calls are logged without
+    /// reaching the server and fail. Calls over the server's concurrency or rate
+    /// limit wait in line first; the timeout only starts once the call is sent.
+    /// Results over the tool's size limit are truncated and stored whole for
+    /// paging with the built-in `read_tool_result` tool, served under [`BUILTIN_SERVER_ID`].
+    /// Cacheable tools answer repeated calls from the cache, marked in `_meta`.
+    /// Metrics count cache hits apart from calls, which are timed from sending
+    /// the request to its response.
+    /// Firing the `cancel` token of the options fails the call with a [`CancelledError`],
+    /// passing its `deadline` with a [`TimeoutError`]; either is logged like any failure.
     pub async fn call_tool(
         &self,
         server_id: &str,
         tool_name: &str,
         arguments: Option<Value>,
+        options: CallOptions,
     ) -> Result<CallToolResult> {
-        let server = self.get_server(server_id).await?;
-        server.call_tool(tool_name, arguments).await
+        let entry = AuditEntry::new(
+            server_id,
+            tool_name,
+            arguments.as_ref(),
+            options.conversation_id,
+            options.approval,
+        );
+        let started = std::time::Instant::now();
+
+        // Resolves to the error the call fails with once it is cancelled or out of time
+        let budget_ms = options.deadline.map(|deadline| {
+            deadline
+                .saturating_duration_since(Instant::now())
+                .as_millis() as u64
+        });
+        let abandoned = || async {
+            let cancelled = async {
+                match &options.cancel {
+                    Some(cancel) => cancel.cancelled().await,
+                    None => std::future::pending().await,
+                }
+            };
+            let expired = async {
+                match options.deadline {
+                    Some(deadline) => tokio::time::sleep_until(deadline).await,
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                _ = cancelled => anyhow::Error::from(CancelledError {
+                    method: "tools/call".to_string(),
+                }),
+                _ = expired => anyhow::Error::from(TimeoutError {
+                    method: "tools/call".to_string(),
+                    timeout_ms: budget_ms.unwrap_or_default(),
+                }),
+            }
+        };
+        let outcome = async {
+            if options.approval == ApprovalDecision::Denied {
+                return Err(anyhow!("Call to tool '{}' was denied", tool_name));
+            }
+            if options.cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+                return Err(CancelledError {
+                    method: "tools/call".to_string(),
+                }
+                .into());
+            }
+            if server_id == BUILTIN_SERVER_ID {
+                let outcome = self.call_builtin_tool(tool_name, arguments.as_ref());
+                let duration_ms = started.elapsed().as_millis() as u64;
+                self.metrics
+                    .record_call(server_id, tool_name, &outcome, duration_ms);
+                return outcome;
+            }
+            let server = self.get_server(server_id).await?;
+
+            let cache_key = server
+                .cacheable(tool_name)
+                .then(|| CacheKey::new(server_id, tool_name, arguments.as_ref()));
+            let generation = server.changes.load(Ordering::SeqCst);
+            let ttl = std::time::Duration::from_millis(server.config.cache.ttl_ms);
+            if let Some(key) = &cache_key {
+                if let Some(result) = self.cache.get(key, generation, ttl) {
+                    self.metrics.record_cache_hit(server_id, tool_name);
+                    return Ok(result);
+                }
+            }
+
+            let _permit = tokio::select! {
+                permit = server.limiter.acquire() => permit,
+                error = abandoned() => return Err(error),
+            };
+            // Metrics time the round trip only, not waiting in line or post-processing
+            let sent = std::time::Instant::now();
+            let result = server
+                .call_tool(tool_name, arguments, options.timeout_ms, abandoned())
+                .await;
+            let duration_ms = sent.elapsed().as_millis() as u64;
+            self.metrics
+                .record_call(server_id, tool_name, &result, duration_ms);
+            let result = self.limit_result(&server, tool_name, result?).await?;
+
+            if let Some(key) = cache_key {
+                self.cache.insert(key, &result, generation);
+            }
+            Ok(result)
+        }
+        .await;
+
+        let duration_ms = started.elapsed().as_millis() as u64;
+        self.audit.record(&entry.finish(&outcome, duration_ms));
+        outcome
+    }
+
+    /// Run several tool calls concurrently and return their outcomes in order
+    ///
+    /// Each call is made like [`Self::call_tool`], so a failing call doesn't affect
+    /// the others. Calls overlap up to each server's concurrency limit. A call's
+    /// timeout covers its whole turn, including waiting for that limit, so a
+    /// batch can't stall behind a busy server; a call that runs out of time is
+    /// cancelled and logged like any other.
+    pub async fn call_tools_batch(
+        &self,
+        calls: Vec<BatchCall>,
+        conversation_id: Option<String>,
+    ) -> Vec<Result<CallToolResult>> {
+        futures::future::join_all(calls.into_iter().map(|call| {
+            let conversation_id = conversation_id.clone();
+            async move {
+                // Unknown servers and built-in tools fail or answer without waiting
+                let timeout_ms = self.get_server(&call.server_id).await.ok().map(|server| {
+                    call.timeout_ms
+                        .unwrap_or_else(|| server.config.timeouts.for_tool(&call.tool_name))
+                });
+                let options = CallOptions {
+                    timeout_ms: call.timeout_ms,
+                    conversation_id,
+                    approval: call.approval,
+                    cancel: None,
+                    deadline: timeout_ms
+                        .map(|timeout_ms| Instant::now() + Duration::from_millis(timeout_ms)),
+                };
+                self.call_tool(&call.server_id, &call.tool_name, call.arguments, options)
+                    .await
+            }
+        }))
+        .await
     }
-}
 
-impl Default for McpServerManager {
-    fn default() -> Self {
-        Self::new()
+    fn call_builtin_tool(
+        &self,
+        tool_name: &str,
+        arguments: Option<&Value>,
+    ) -> Result<CallToolResult> {
+        match tool_name {
+            READ_RESULT_TOOL => self.results.call_read_tool(arguments),
+            _ => Err(anyhow!("Unknown tool '{}'", tool_name)),
+        }
+    }
+
+    /// Truncate a result over the tool's size limit, keeping the full text for paging
+    async fn limit_result(
+        &self,
+        server: &McpServer,
+        tool_name: &str,
+        result: CallToolResult,
+    ) -> Result<CallToolResult> {
+        let limit = server.config.result_limits.for_tool(tool_name);
+        let pager_name = self.tool_names.lock().await.register(
+            BUILTIN_SERVER_ID,
+            BUILTIN_SERVER_NAME,
+            READ_RESULT_TOOL,
+        );
+        self.results.truncate(result, limit, &pager_name)
     }
 }
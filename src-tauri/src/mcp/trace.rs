@@ -0,0 +1,394 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::protocol::JsonRpcResponse;
+
+/// Directory under the app data directory holding trace files
+pub const TRACE_DIR_NAME: &str = "traces";
+
+/// Size at which a trace file is rotated to `<server>.jsonl.1`
+const MAX_TRACE_BYTES: u64 = 10 * 1024 * 1024;
+
+const REDACTED: &str = "[redacted]";
+
+/// Object keys whose values are always redacted, compared without `-` and `_`
+const SENSITIVE_KEYS: &[&str] = &[
+    "authorization",
+    "proxyauthorization",
+    "cookie",
+    "setcookie",
+    "password",
+    "passwd",
+    "secret",
+    "clientsecret",
+    "token",
+    "accesstoken",
+    "refreshtoken",
+    "idtoken",
+    "apikey",
+    "xapikey",
+    "privatekey",
+];
+
+/// Shortest secret value redacted from message text, so tiny values don't mangle traces
+const MIN_SECRET_LEN: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Direction {
+    /// Orion to the server
+    Sent,
+    /// Server to Orion
+    Received,
+}
+
+/// One recorded JSON-RPC frame
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceEntry {
+    pub timestamp_ms: u64,
+    pub server_id: String,
+    pub direction: Direction,
+    /// The frame as JSON, or as a string if it was not valid JSON
+    pub message: Value,
+}
+
+/// Trace file of a server, named after its ID with unsafe characters replaced
+pub fn trace_path(trace_dir: &Path, server_id: &str) -> PathBuf {
+    let name: String = server_id
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => c,
+            _ => '_',
+        })
+        .collect();
+    trace_dir.join(format!("{}.jsonl", name))
+}
+
+/// Appends a server's JSON-RPC traffic to its trace file
+///
+/// Values of keys like `authorization` or `apiKey`, and any occurrence of the
+/// server's resolved secrets, are replaced with `[redacted]` before writing.
+#[derive(Clone)]
+pub struct Recorder {
+    server_id: String,
+    path: PathBuf,
+    secrets: Arc<Vec<String>>,
+    file: Arc<Mutex<TraceFile>>,
+}
+
+struct TraceFile {
+    file: File,
+    len: u64,
+}
+
+impl Recorder {
+    pub fn open(trace_dir: &Path, server_id: &str, secrets: Vec<String>) -> Result<Self> {
+        std::fs::create_dir_all(trace_dir)
+            .with_context(|| format!("Failed to create {}", trace_dir.display()))?;
+        let path = trace_path(trace_dir, server_id);
+        let file = open_append(&path)?;
+        let len = file.metadata()?.len();
+
+        Ok(Self {
+            server_id: server_id.to_string(),
+            path,
+            secrets: Arc::new(
+                secrets
+                    .into_iter()
+                    .filter(|secret| secret.len() >= MIN_SECRET_LEN)
+                    .collect(),
+            ),
+            file: Arc::new(Mutex::new(TraceFile { file, len })),
+        })
+    }
+
+    /// Record one frame; failures are reported on stderr and otherwise ignored
+    pub fn record(&self, direction: Direction, frame: &str) {
+        if let Err(e) = self.write(direction, frame) {
+            eprintln!(
+                "Failed to record MCP traffic to {}: {}",
+                self.path.display(),
+                e
+            );
+        }
+    }
+
+    fn write(&self, direction: Direction, frame: &str) -> Result<()> {
+        let mut message =
+            serde_json::from_str(frame).unwrap_or_else(|_| Value::String(frame.to_string()));
+        redact(&mut message, &self.secrets);
+
+        let entry = TraceEntry {
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_millis() as u64)
+                .unwrap_or_default(),
+            server_id: self.server_id.clone(),
+            direction,
+            message,
+        };
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+
+        let mut trace = self
+            .file
+            .lock()
+            .map_err(|_| anyhow!("Trace file lock poisoned"))?;
+        if trace.len + line.len() as u64 > MAX_TRACE_BYTES && trace.len > 0 {
+            std::fs::rename(&self.path, self.path.with_extension("jsonl.1"))?;
+            *trace = TraceFile {
+                file: open_append(&self.path)?,
+                len: 0,
+            };
+        }
+        trace.file.write_all(line.as_bytes())?;
+        trace.len += line.len() as u64;
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))
+}
+
+/// Replace sensitive values in a message in place
+pub(super) fn redact(value: &mut Value, secrets: &[String]) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                let normalized: String = key
+                    .chars()
+                    .filter(|c| *c != '-' && *c != '_')
+                    .flat_map(char::to_lowercase)
+                    .collect();
+                if SENSITIVE_KEYS.contains(&normalized.as_str()) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact(value, secrets);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                redact(item, secrets);
+            }
+        }
+        Value::String(text) => {
+            for secret in secrets {
+                if text.contains(secret.as_str()) {
+                    *text = text.replace(secret.as_str(), REDACTED);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Read the last `limit` entries of a trace file, oldest first
+///
+/// A missing file is an empty trace. Lines that don't parse are skipped.
+pub fn read_trace(path: &Path, limit: usize) -> Result<Vec<TraceEntry>> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(anyhow!("Failed to read {}: {}", path.display(), e)),
+    };
+
+    let mut entries: Vec<TraceEntry> = contents
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .take(limit)
+        .collect();
+    entries.reverse();
+    Ok(entries)
+}
+
+/// A recorded request together with the response it got
+struct Exchange {
+    method: String,
+    params: Option<Value>,
+    response: JsonRpcResponse,
+    replayed: bool,
+}
+
+/// Plays a recorded session back in place of a real server
+///
+/// Each request is answered with the recorded response to the first unused
+/// request with the same method and parameters, falling back to the same method
+/// only. `_meta` is ignored when comparing, since it carries per-session tokens.
+pub struct ReplayTransport {
+    exchanges: Vec<Exchange>,
+}
+
+impl ReplayTransport {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let entries: Vec<TraceEntry> = contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+
+        let exchanges = pair_exchanges(&entries);
+        if exchanges.is_empty() {
+            return Err(anyhow!(
+                "No request/response pairs found in {}",
+                path.display()
+            ));
+        }
+        Ok(Self { exchanges })
+    }
+
+    pub fn call(
+        &mut self,
+        method: &str,
+        params: Option<Value>,
+        id: Value,
+    ) -> Result<JsonRpcResponse> {
+        let params = without_meta(params);
+        let unused = |exchange: &Exchange| !exchange.replayed && exchange.method == method;
+
+        let position = self
+            .exchanges
+            .iter()
+            .position(|exchange| unused(exchange) && exchange.params == params)
+            .or_else(|| self.exchanges.iter().position(unused))
+            .ok_or_else(|| anyhow!("No recorded response left for '{}'", method))?;
+
+        let exchange = &mut self.exchanges[position];
+        exchange.replayed = true;
+        let mut response = exchange.response.clone();
+        response.id = id;
+        Ok(response)
+    }
+}
+
+/// Match sent requests with the received responses carrying the same ID
+///
+/// Every request is paired with the first response after it, so IDs reused by a
+/// restarted server don't get mixed up.
+fn pair_exchanges(entries: &[TraceEntry]) -> Vec<Exchange> {
+    let mut exchanges = Vec::new();
+    for (index, entry) in entries.iter().enumerate() {
+        if entry.direction != Direction::Sent {
+            continue;
+        }
+        let Some(method) = entry.message.get("method").and_then(Value::as_str) else {
+            continue;
+        };
+        let id = match entry.message.get("id") {
+            None | Some(Value::Null) => continue,
+            Some(id) => id,
+        };
+
+        let response = entries[index + 1..]
+            .iter()
+            .filter(|later| later.direction == Direction::Received)
+            .filter(|later| later.message.get("method").is_none())
+            .find(|later| later.message.get("id") == Some(id))
+            .and_then(|later| serde_json::from_value(later.message.clone()).ok());
+
+        if let Some(response) = response {
+            exchanges.push(Exchange {
+                method: method.to_string(),
+                params: without_meta(entry.message.get("params").cloned()),
+                response,
+                replayed: false,
+            });
+        }
+    }
+    exchanges
+}
+
+fn without_meta(params: Option<Value>) -> Option<Value> {
+    params.map(|mut params| {
+        if let Some(object) = params.as_object_mut() {
+            object.remove("_meta");
+        }
+        params
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn redacts_sensitive_keys_and_secret_values() {
+        let mut message = json!({
+            "params": {
+                "headers": { "Authorization": "Bearer abc", "X-Api-Key": "k" },
+                "_meta": { "progressToken": 7 },
+                "text": "token is sk-12345 here",
+            }
+        });
+        redact(&mut message, &["sk-12345".to_string()]);
+
+        assert_eq!(
+            message,
+            json!({
+                "params": {
+                    "headers": { "Authorization": REDACTED, "X-Api-Key": REDACTED },
+                    "_meta": { "progressToken": 7 },
+                    "text": "token is [redacted] here",
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn replays_responses_by_method_and_params() {
+        let entry = |direction, message| TraceEntry {
+            timestamp_ms: 0,
+            server_id: "s".to_string(),
+            direction,
+            message,
+        };
+        let call = |id: u64, query: &str| {
+            json!({ "jsonrpc": "2.0", "id": id, "method": "tools/call",
+                    "params": { "name": "search", "arguments": { "q": query }, "_meta": { "progressToken": id } } })
+        };
+        let result = |id: u64, text: &str| json!({ "jsonrpc": "2.0", "id": id, "result": { "content": [{ "type": "text", "text": text }] } });
+        let entries = vec![
+            entry(Direction::Sent, call(1, "a")),
+            entry(
+                Direction::Received,
+                json!({ "jsonrpc": "2.0", "method": "notifications/progress" }),
+            ),
+            entry(Direction::Sent, call(2, "b")),
+            entry(Direction::Received, result(2, "B")),
+            entry(Direction::Received, result(1, "A")),
+        ];
+        let mut replay = ReplayTransport {
+            exchanges: pair_exchanges(&entries),
+        };
+
+        let params = |query: &str| {
+            Some(
+                json!({ "name": "search", "arguments": { "q": query }, "_meta": { "progressToken": 99 } }),
+            )
+        };
+        let response = replay.call("tools/call", params("b"), json!(10)).unwrap();
+        assert_eq!(response.id, json!(10));
+        assert_eq!(response.result.unwrap()["content"][0]["text"], "B");
+
+        // No exact match left for "b", so the remaining call with the same method is used
+        let response = replay.call("tools/call", params("b"), json!(11)).unwrap();
+        assert_eq!(response.result.unwrap()["content"][0]["text"], "A");
+
+        assert!(replay.call("tools/call", params("a"), json!(12)).is_err());
+    }
+}
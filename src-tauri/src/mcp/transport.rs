@@ -1,88 +1,880 @@
 use anyhow::{anyhow, Result};
+use serde::Serialize;
 use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
 use std::process::Stdio;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::process::{Child, ChildStdin, Command};
-use tokio::sync::mpsc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin};
+use tokio::sync::{mpsc, oneshot, Notify};
 
-use super::protocol::{JsonRpcRequest, JsonRpcResponse};
+use super::error::{FrameTooLargeError, TimeoutError};
+use super::process::{self, ProcessInfo};
+use super::protocol::{JsonRpcRequest, JsonRpcResponse, McpServerConfig};
+use super::sandbox;
+use super::secrets::SecretVault;
+use super::trace::{Direction, Recorder, ReplayTransport};
+
+/// Number of stderr lines and diagnostics kept for error reports
+const LOG_CAPACITY: usize = 100;
+
+/// Longest excerpt of a skipped stdout line kept in diagnostics
+const DIAGNOSTIC_EXCERPT_LEN: usize = 200;
+
+/// Leading bytes of an oversized frame kept to find the ID of the request it answers
+const FRAME_HEAD_LEN: usize = 256;
+
+/// A newline-delimited frame read from the server's stdout
+#[derive(Debug)]
+pub(super) enum Frame {
+    Line(String),
+    /// A frame over the size limit was dropped
+    TooLarge {
+        /// Size of the frame in bytes
+        size: usize,
+        /// Serialized JSON-RPC ID found at the start of the frame, if any
+        id: Option<String>,
+    },
+    /// A frame that was not valid UTF-8 was dropped; holds its size in bytes
+    InvalidUtf8(usize),
+}
+
+/// Snapshot of what a server wrote besides valid JSON-RPC messages
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerDiagnostics {
+    /// Skipped stdout output such as banners, logs or invalid frames
+    pub diagnostics: Vec<String>,
+    /// Most recent stderr lines
+    pub stderr: Vec<String>,
+}
+
+/// Bounded logs of a server's stderr and skipped stdout output
+///
+/// Shared outside the transport lock so it can be read while a call is in flight.
+#[derive(Debug, Clone, Default)]
+pub struct TransportLog {
+    stderr: Arc<Mutex<VecDeque<String>>>,
+    diagnostics: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl TransportLog {
+    fn push(buffer: &Mutex<VecDeque<String>>, line: String) {
+        if let Ok(mut buffer) = buffer.lock() {
+            if buffer.len() == LOG_CAPACITY {
+                buffer.pop_front();
+            }
+            buffer.push_back(line);
+        }
+    }
+
+    pub(crate) fn push_stderr(&self, line: String) {
+        Self::push(&self.stderr, line);
+    }
+
+    fn push_diagnostic(&self, message: String) {
+        Self::push(&self.diagnostics, message);
+    }
+
+    /// Most recent lines the server wrote to stderr
+    pub fn stderr_tail(&self) -> Vec<String> {
+        self.stderr
+            .lock()
+            .map(|tail| tail.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn snapshot(&self) -> ServerDiagnostics {
+        ServerDiagnostics {
+            diagnostics: self
+                .diagnostics
+                .lock()
+                .map(|diagnostics| diagnostics.iter().cloned().collect())
+                .unwrap_or_default(),
+            stderr: self.stderr_tail(),
+        }
+    }
+}
+
+fn excerpt(line: &str) -> String {
+    match line.char_indices().nth(DIAGNOSTIC_EXCERPT_LEN) {
+        Some((end, _)) => format!("{}...", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+/// Turn the bytes of one frame into a [`Frame`], skipping blank lines
+fn decode_frame(mut bytes: Vec<u8>) -> Option<Frame> {
+    // Accept CRLF framing from servers written on Windows
+    if bytes.last() == Some(&b'\r') {
+        bytes.pop();
+    }
+    if bytes.iter().all(u8::is_ascii_whitespace) {
+        return None;
+    }
+
+    let len = bytes.len();
+    Some(match String::from_utf8(bytes) {
+        Ok(line) => Frame::Line(line),
+        Err(_) => Frame::InvalidUtf8(len),
+    })
+}
+
+/// Find the top-level `"id"` of a JSON-RPC message from its first bytes
+///
+/// Returns the ID serialized like the keys of pending requests, or `None` when
+/// the bytes end before the ID does.
+fn frame_id(head: &[u8]) -> Option<String> {
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < head.len() {
+        match head[i] {
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            b'"' => {
+                let end = string_end(head, i)?;
+                if depth == 1 && &head[i..=end] == b"\"id\"" {
+                    let rest = head[end + 1..].trim_ascii_start();
+                    if let Some(value) = rest.strip_prefix(b":") {
+                        return id_value(value.trim_ascii_start());
+                    }
+                }
+                i = end;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Index of the quote closing the JSON string that starts at `start`
+fn string_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'"' => return Some(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Parse the string or number ID at the start of `bytes`
+fn id_value(bytes: &[u8]) -> Option<String> {
+    let len = match bytes.first()? {
+        b'"' => string_end(bytes, 0)? + 1,
+        // A number running to the end of the bytes may have been cut short
+        _ => bytes
+            .iter()
+            .position(|b| !matches!(b, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9'))?,
+    };
+    match serde_json::from_slice::<Value>(&bytes[..len]).ok()? {
+        id @ (Value::String(_) | Value::Number(_)) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+/// Split a byte stream into newline-delimited frames of at most `max_frame_bytes`
+///
+/// Frames are assembled from raw bytes, so multi-byte UTF-8 characters split across
+/// reads are decoded correctly. Oversized frames are skipped up to the next newline
+/// without buffering them; only their first [`FRAME_HEAD_LEN`] bytes are kept to
+/// tell which request they answer.
+pub(super) async fn read_frames<R: AsyncRead + Unpin>(
+    reader: R,
+    max_frame_bytes: usize,
+    tx: mpsc::Sender<Frame>,
+) {
+    let mut reader = BufReader::new(reader);
+    let mut frame = Vec::new();
+    let mut oversized: Option<usize> = None;
+    let mut head = Vec::new();
+
+    loop {
+        let (consumed, complete) = match reader.fill_buf().await {
+            Ok([]) | Err(_) => break,
+            Ok(available) => {
+                let newline = available.iter().position(|&b| b == b'\n');
+                let chunk = &available[..newline.unwrap_or(available.len())];
+
+                match &mut oversized {
+                    Some(dropped) => {
+                        *dropped += chunk.len();
+                        let keep = FRAME_HEAD_LEN.saturating_sub(head.len()).min(chunk.len());
+                        head.extend_from_slice(&chunk[..keep]);
+                    }
+                    None if frame.len() + chunk.len() > max_frame_bytes => {
+                        oversized = Some(frame.len() + chunk.len());
+                        head = std::mem::take(&mut frame);
+                        head.truncate(FRAME_HEAD_LEN);
+                        let keep = FRAME_HEAD_LEN.saturating_sub(head.len()).min(chunk.len());
+                        head.extend_from_slice(&chunk[..keep]);
+                    }
+                    None => frame.extend_from_slice(chunk),
+                }
+
+                match newline {
+                    Some(pos) => (pos + 1, true),
+                    None => (available.len(), false),
+                }
+            }
+        };
+        reader.consume(consumed);
+
+        if complete {
+            let message = match oversized.take() {
+                Some(size) => Some(Frame::TooLarge {
+                    size,
+                    id: frame_id(&std::mem::take(&mut head)),
+                }),
+                None => decode_frame(std::mem::take(&mut frame)),
+            };
+            if let Some(message) = message {
+                if tx.send(message).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+
+    // Deliver a final frame that was not newline-terminated
+    if oversized.is_none() {
+        if let Some(message) = decode_frame(frame) {
+            let _ = tx.send(message).await;
+        }
+    }
+}
+
+/// How long to wait for a response to a request
+#[derive(Debug, Clone, Copy)]
+pub struct ResponseTimeout {
+    /// Time limit in milliseconds, 0 waits indefinitely
+    pub timeout_ms: u64,
+    /// Restart the time limit on every progress notification for the request
+    pub reset_on_progress: bool,
+}
+
+impl ResponseTimeout {
+    pub fn fixed(timeout_ms: u64) -> Self {
+        Self {
+            timeout_ms,
+            reset_on_progress: false,
+        }
+    }
+
+    fn deadline(&self) -> Option<tokio::time::Instant> {
+        if self.timeout_ms == 0 {
+            None
+        } else {
+            Some(tokio::time::Instant::now() + tokio::time::Duration::from_millis(self.timeout_ms))
+        }
+    }
+}
+
+/// Token of a progress notification, which Orion sets to the request ID
+fn progress_token(message: &Value) -> Option<&Value> {
+    if message.get("method").and_then(Value::as_str) != Some("notifications/progress") {
+        return None;
+    }
+    message.get("params")?.get("progressToken")
+}
+
+/// Notifications telling clients that what a server's tools return may have changed
+const CHANGE_NOTIFICATIONS: &[&str] = &[
+    "notifications/tools/list_changed",
+    "notifications/resources/list_changed",
+    "notifications/resources/updated",
+];
+
+/// Check whether a frame is one of the [`CHANGE_NOTIFICATIONS`]
+fn is_change_notification(line: &str) -> bool {
+    // Most frames are responses, so skip parsing them
+    if !line.contains("notifications/") {
+        return false;
+    }
+    serde_json::from_str::<Value>(line)
+        .ok()
+        .and_then(|message| {
+            message
+                .get("method")
+                .and_then(Value::as_str)
+                .map(|method| CHANGE_NOTIFICATIONS.contains(&method))
+        })
+        .unwrap_or(false)
+}
+
+/// A request waiting for its response
+struct PendingRequest {
+    response: oneshot::Sender<Result<JsonRpcResponse>>,
+    /// Woken by progress notifications whose token is the request ID
+    progress: Arc<Notify>,
+}
+
+/// Requests in flight, keyed by their serialized JSON-RPC ID
+#[derive(Default)]
+struct PendingRequests {
+    requests: HashMap<String, PendingRequest>,
+    /// Set once stdout closed, so later requests fail right away
+    closed: bool,
+}
+
+type Pending = Arc<Mutex<PendingRequests>>;
+
+fn lock(pending: &Pending) -> std::sync::MutexGuard<'_, PendingRequests> {
+    pending
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Forgets a pending request however its call ends, including by being dropped
+struct Registration<'a> {
+    pending: &'a Pending,
+    key: String,
+}
+
+impl Drop for Registration<'_> {
+    fn drop(&mut self) {
+        lock(self.pending).requests.remove(&self.key);
+    }
+}
+
+/// Routes frames from a server's stdout to the requests waiting for them
+struct Router {
+    pending: Pending,
+    log: TransportLog,
+    recorder: Option<Recorder>,
+    changes: Arc<AtomicU64>,
+    max_frame_bytes: usize,
+}
+
+impl Router {
+    /// Route frames until stdout closes, then fail every request still waiting
+    ///
+    /// Frames are recorded and change notifications counted as they arrive, so
+    /// changes between calls are noticed too. Output that is not a JSON-RPC
+    /// message is recorded as a diagnostic, and late responses to requests that
+    /// timed out or were cancelled are skipped.
+    async fn run(self, mut frames: mpsc::Receiver<Frame>) {
+        while let Some(frame) = frames.recv().await {
+            match frame {
+                Frame::Line(line) => self.route(&line),
+                Frame::TooLarge { size, id } => {
+                    self.log.push_diagnostic(format!(
+                        "Dropped a {} byte message over the {} byte limit",
+                        size, self.max_frame_bytes
+                    ));
+                    let error = || FrameTooLargeError {
+                        size,
+                        limit: self.max_frame_bytes,
+                    };
+                    let mut pending = lock(&self.pending);
+                    match id {
+                        Some(id) => {
+                            if let Some(request) = pending.requests.remove(&id) {
+                                let _ = request.response.send(Err(error().into()));
+                            }
+                        }
+                        // Without an ID any request may have been answered
+                        None => {
+                            for (_, request) in pending.requests.drain() {
+                                let _ = request.response.send(Err(error().into()));
+                            }
+                        }
+                    }
+                }
+                Frame::InvalidUtf8(size) => {
+                    self.log
+                        .push_diagnostic(format!("Skipped {} bytes of invalid UTF-8 output", size));
+                }
+            }
+        }
+
+        let mut pending = lock(&self.pending);
+        pending.closed = true;
+        for (_, request) in pending.requests.drain() {
+            let _ = request.response.send(Err(anyhow!("Process stdout closed")));
+        }
+    }
+
+    fn route(&self, line: &str) {
+        if let Some(recorder) = &self.recorder {
+            recorder.record(Direction::Received, line);
+        }
+        if is_change_notification(line) {
+            self.changes.fetch_add(1, Ordering::SeqCst);
+        }
+
+        let message: Value = match serde_json::from_str(line) {
+            Ok(message @ Value::Object(_)) => message,
+            _ => {
+                self.log
+                    .push_diagnostic(format!("Skipped non-JSON output: {}", excerpt(line)));
+                return;
+            }
+        };
+
+        // Server-initiated notifications and requests carry a method
+        if message.get("method").is_some() {
+            if let Some(token) = progress_token(&message) {
+                if let Some(request) = lock(&self.pending).requests.get(&token.to_string()) {
+                    request.progress.notify_one();
+                }
+            }
+            return;
+        }
+
+        let response: JsonRpcResponse = match serde_json::from_value(message) {
+            Ok(response) => response,
+            Err(e) => {
+                self.log.push_diagnostic(format!(
+                    "Skipped malformed JSON-RPC message ({}): {}",
+                    e,
+                    excerpt(line)
+                ));
+                return;
+            }
+        };
+
+        let key = response.id.to_string();
+        if let Some(request) = lock(&self.pending).requests.remove(&key) {
+            let _ = request.response.send(Ok(response));
+        }
+    }
+}
 
 /// Async transport for JSON-RPC over stdio
+///
+/// Requests are multiplexed: one task reads stdout and hands each response to
+/// the request with its ID, so concurrent calls only take turns writing to stdin.
 pub struct StdioTransport {
-    stdin: ChildStdin,
-    stdout_receiver: mpsc::Receiver<String>,
+    stdin: tokio::sync::Mutex<ChildStdin>,
+    pending: Pending,
+    log: TransportLog,
+    process: ProcessInfo,
+    recorder: Option<Recorder>,
+    changes: Arc<AtomicU64>,
     _child: Child,
 }
 
 impl StdioTransport {
-    /// Create a new stdio transport by spawning a command
-    pub async fn spawn(command: &str, args: &[String], env: Option<&std::collections::HashMap<String, String>>) -> Result<Self> {
-        let mut cmd = Command::new(command);
-        cmd.args(args)
+    /// Create a new stdio transport by spawning a server's command
+    ///
+    /// The command is resolved against the login shell PATH and runs with the
+    /// configured working directory and inherited environment. Environment values
+    /// may be `secret:`, `env:` or `file:` references, which are resolved through the
+    /// vault right before the process starts.
+    ///
+    /// With a `trace_dir`, every frame is recorded to the server's trace file with
+    /// the resolved values redacted.
+    pub async fn spawn(
+        config: &McpServerConfig,
+        secrets: &SecretVault,
+        trace_dir: Option<&Path>,
+    ) -> Result<Self> {
+        let (mut cmd, executable) = process::build_command(config).await?;
+        cmd.args(&config.args)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .kill_on_drop(true);
 
+        if let Some(profile) = &config.sandbox {
+            sandbox::apply(&mut cmd, profile, config, &executable)?;
+        }
+
         // Add environment variables if provided
-        if let Some(env_vars) = env {
+        let mut resolved_secrets = Vec::new();
+        if let Some(env_vars) = &config.env {
             for (key, value) in env_vars {
-                cmd.env(key, value);
+                let resolved = secrets
+                    .resolve(value)
+                    .map_err(|e| anyhow!("Failed to resolve environment variable '{}': {}", key, e))?;
+                if &resolved != value {
+                    resolved_secrets.push(resolved.clone());
+                }
+                cmd.env(key, resolved);
             }
         }
 
-        let mut child = cmd.spawn().map_err(|e| anyhow!("Failed to spawn process: {}", e))?;
+        let recorder = trace_dir
+            .map(|dir| Recorder::open(dir, &config.id, resolved_secrets))
+            .transpose()?;
+
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| anyhow!("Failed to spawn '{}': {}", executable.display(), e))?;
+        let process = ProcessInfo {
+            executable: executable.display().to_string(),
+            pid: child.id(),
+            cwd: config.cwd.clone(),
+        };
 
         let stdin = child.stdin.take().ok_or_else(|| anyhow!("Failed to get stdin"))?;
         let stdout = child.stdout.take().ok_or_else(|| anyhow!("Failed to get stdout"))?;
+        let stderr = child.stderr.take().ok_or_else(|| anyhow!("Failed to get stderr"))?;
 
-        // Spawn a task to read stdout line by line
-        let (tx, rx) = mpsc::channel::<String>(100);
+        // Keep the last stderr lines around for error reports
+        let log = TransportLog::default();
+        let stderr_log = log.clone();
         tokio::spawn(async move {
-            let reader = BufReader::new(stdout);
-            let mut lines = reader.lines();
+            let mut lines = BufReader::new(stderr).lines();
             while let Ok(Some(line)) = lines.next_line().await {
-                if tx.send(line).await.is_err() {
-                    break;
-                }
+                stderr_log.push_stderr(line);
             }
         });
 
+        // Spawn a task to split stdout into frames, and one to route them
+        let (tx, rx) = mpsc::channel::<Frame>(100);
+        tokio::spawn(read_frames(stdout, config.max_frame_bytes, tx));
+
+        let pending = Pending::default();
+        let changes = Arc::new(AtomicU64::new(0));
+        let router = Router {
+            pending: pending.clone(),
+            log: log.clone(),
+            recorder: recorder.clone(),
+            changes: changes.clone(),
+            max_frame_bytes: config.max_frame_bytes,
+        };
+        tokio::spawn(router.run(rx));
+
         Ok(Self {
-            stdin,
-            stdout_receiver: rx,
+            stdin: tokio::sync::Mutex::new(stdin),
+            pending,
+            log,
+            process,
+            recorder,
+            changes,
             _child: child,
         })
     }
 
+    /// Details about the spawned process
+    pub fn process(&self) -> &ProcessInfo {
+        &self.process
+    }
+
+    /// Handle to the server's stderr and diagnostics log
+    pub fn log(&self) -> TransportLog {
+        self.log.clone()
+    }
+
+    /// Counter bumped whenever the server says its tools or resources changed
+    pub fn changes(&self) -> Arc<AtomicU64> {
+        self.changes.clone()
+    }
+
     /// Send a JSON-RPC request
-    pub async fn send_request(&mut self, request: &JsonRpcRequest) -> Result<()> {
+    pub async fn send_request(&self, request: &JsonRpcRequest) -> Result<()> {
         let json = serde_json::to_string(request)?;
-        self.stdin.write_all(json.as_bytes()).await?;
-        self.stdin.write_all(b"\n").await?;
-        self.stdin.flush().await?;
+        let mut stdin = self.stdin.lock().await;
+        if let Some(recorder) = &self.recorder {
+            recorder.record(Direction::Sent, &json);
+        }
+        stdin.write_all(json.as_bytes()).await?;
+        stdin.write_all(b"\n").await?;
+        stdin.flush().await?;
         Ok(())
     }
 
-    /// Receive a JSON-RPC response (with timeout)
-    pub async fn receive_response(&mut self, timeout_ms: u64) -> Result<JsonRpcResponse> {
-        let timeout_duration = tokio::time::Duration::from_millis(timeout_ms);
+    /// Send request and wait for response
+    ///
+    /// Progress notifications whose token equals the request ID restart the
+    /// timeout when `reset_on_progress` is set.
+    pub async fn call(
+        &self,
+        method: &str,
+        params: Option<Value>,
+        id: Value,
+        timeout: ResponseTimeout,
+    ) -> Result<JsonRpcResponse> {
+        let key = id.to_string();
+        let (response_tx, mut response_rx) = oneshot::channel();
+        let progress = Arc::new(Notify::new());
+        {
+            let mut pending = lock(&self.pending);
+            if pending.closed {
+                return Err(anyhow!("Process stdout closed"));
+            }
+            let request = PendingRequest {
+                response: response_tx,
+                progress: progress.clone(),
+            };
+            pending.requests.insert(key.clone(), request);
+        }
+        let _registration = Registration {
+            pending: &self.pending,
+            key,
+        };
+
+        self.send_request(&JsonRpcRequest::new(method, params, id))
+            .await?;
 
-        match tokio::time::timeout(timeout_duration, self.stdout_receiver.recv()).await {
-            Ok(Some(line)) => {
-                let response: JsonRpcResponse = serde_json::from_str(&line)
-                    .map_err(|e| anyhow!("Failed to parse JSON-RPC response: {}", e))?;
-                Ok(response)
+        let mut deadline = timeout.deadline();
+        loop {
+            let expired = async {
+                match deadline {
+                    Some(deadline) => tokio::time::sleep_until(deadline).await,
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                response = &mut response_rx => {
+                    return response.unwrap_or_else(|_| Err(anyhow!("Process stdout closed")));
+                }
+                _ = progress.notified(), if timeout.reset_on_progress => {
+                    deadline = timeout.deadline();
+                }
+                _ = expired => {
+                    return Err(TimeoutError {
+                        method: method.to_string(),
+                        timeout_ms: timeout.timeout_ms,
+                    }
+                    .into());
+                }
             }
-            Ok(None) => Err(anyhow!("Process stdout closed")),
-            Err(_) => Err(anyhow!("Timeout waiting for response")),
+        }
+    }
+}
+
+/// Transport behind an [`McpServer`](super::server::McpServer)
+pub enum Transport {
+    Stdio(Box<StdioTransport>),
+    /// A recorded session played back without the real server
+    Replay(Mutex<ReplayTransport>),
+}
+
+impl Transport {
+    /// Send a JSON-RPC message without waiting for a response
+    pub async fn send_request(&self, request: &JsonRpcRequest) -> Result<()> {
+        match self {
+            Self::Stdio(transport) => transport.send_request(request).await,
+            Self::Replay(_) => Ok(()),
         }
     }
 
     /// Send request and wait for response
-    pub async fn call(&mut self, method: &str, params: Option<Value>, id: Value, timeout_ms: u64) -> Result<JsonRpcResponse> {
-        let request = JsonRpcRequest::new(method, params, id);
-        self.send_request(&request).await?;
-        self.receive_response(timeout_ms).await
+    pub async fn call(
+        &self,
+        method: &str,
+        params: Option<Value>,
+        id: Value,
+        timeout: ResponseTimeout,
+    ) -> Result<JsonRpcResponse> {
+        match self {
+            Self::Stdio(transport) => transport.call(method, params, id, timeout).await,
+            Self::Replay(transport) => transport
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .call(method, params, id),
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::AsyncWriteExt;
+
+    static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+
+    /// Spawn a misbehaving fake server implemented as a shell script
+    async fn fake_server(script: &str, max_frame_bytes: usize) -> StdioTransport {
+        let dir = std::env::temp_dir().join(format!(
+            "orion-transport-test-{}-{}",
+            std::process::id(),
+            NEXT_DIR.fetch_add(1, Ordering::SeqCst)
+        ));
+        let secrets = SecretVault::open_with_key_file(&dir).unwrap();
+        let config: McpServerConfig = serde_json::from_value(json!({
+            "id": "fake",
+            "name": "fake",
+            "command": "sh",
+            "args": ["-c", script],
+            "maxFrameBytes": max_frame_bytes,
+        }))
+        .unwrap();
+
+        let transport = StdioTransport::spawn(&config, &secrets, None).await.unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        transport
+    }
+
+    async fn frames_from(chunks: &[&[u8]], max_frame_bytes: usize) -> Vec<Frame> {
+        let (mut writer, reader) = tokio::io::duplex(64);
+        let (tx, mut rx) = mpsc::channel(16);
+        let task = tokio::spawn(read_frames(reader, max_frame_bytes, tx));
+
+        for chunk in chunks {
+            writer.write_all(chunk).await.unwrap();
+            writer.flush().await.unwrap();
+            tokio::task::yield_now().await;
+        }
+        drop(writer);
+        task.await.unwrap();
+
+        let mut frames = Vec::new();
+        while let Some(frame) = rx.recv().await {
+            frames.push(frame);
+        }
+        frames
+    }
+
+    #[tokio::test]
+    async fn reassembles_utf8_split_across_reads() {
+        let frames = frames_from(&[b"{\"t\":\"caf\xc3", b"\xa9\"}\n"], 1024).await;
+
+        assert!(matches!(&frames[..], [Frame::Line(line)] if line == "{\"t\":\"café\"}"));
+    }
+
+    #[tokio::test]
+    async fn strips_crlf_and_skips_blank_lines() {
+        let frames = frames_from(&[b"\r\n{\"a\":1}\r\n\n", b"{\"b\":2}"], 1024).await;
+
+        let lines: Vec<_> = frames
+            .iter()
+            .map(|frame| match frame {
+                Frame::Line(line) => line.as_str(),
+                other => panic!("unexpected frame {:?}", other),
+            })
+            .collect();
+        assert_eq!(lines, ["{\"a\":1}", "{\"b\":2}"]);
+    }
+
+    #[tokio::test]
+    async fn drops_oversized_frames_without_losing_the_next_one() {
+        let big = vec![b'x'; 100];
+        let frames = frames_from(&[&big, b"\n{}\n"], 16).await;
+
+        assert!(matches!(
+            &frames[..],
+            [Frame::TooLarge { size: 100, id: None }, Frame::Line(line)] if line == "{}"
+        ));
+    }
+
+    #[tokio::test]
+    async fn keeps_the_top_level_id_of_oversized_frames() {
+        let big = format!(
+            "{{\"jsonrpc\":\"2.0\",\"result\":{{\"id\":7}},\"id\":\"req-3\",\"data\":\"{}\"}}\n",
+            "x".repeat(1000)
+        );
+        let frames = frames_from(&[big.as_bytes()], 64).await;
+
+        assert!(matches!(
+            &frames[..],
+            [Frame::TooLarge { id: Some(id), .. }] if id == "\"req-3\""
+        ));
+    }
+
+    #[tokio::test]
+    async fn skips_banner_and_notifications() {
+        let transport = fake_server(
+            r#"read line
+echo 'Starting fake server v1.0'
+printf '{"jsonrpc":"2.0","method":"notifications/message","params":{}}\n'
+printf '{"jsonrpc":"2.0","id":1,"result":{"ok":true}}\r\n'
+sleep 1"#,
+            1024,
+        )
+        .await;
+
+        let response = transport
+            .call("ping", None, json!(1), ResponseTimeout::fixed(5000))
+            .await
+            .unwrap();
+
+        assert_eq!(response.result, Some(json!({ "ok": true })));
+        let diagnostics = transport.log().snapshot().diagnostics;
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].contains("Starting fake server v1.0"));
+    }
+
+    #[tokio::test]
+    async fn skips_invalid_utf8_and_malformed_messages() {
+        let transport = fake_server(
+            r#"read line
+printf '\377\376garbage\n'
+printf '{"unexpected":true}\n'
+printf '[1, 2, 3]\n'
+printf '{"jsonrpc":"2.0","id":1,"result":{}}\n'
+sleep 1"#,
+            1024,
+        )
+        .await;
+
+        let response = transport
+            .call("ping", None, json!(1), ResponseTimeout::fixed(5000))
+            .await
+            .unwrap();
+
+        assert_eq!(response.result, Some(json!({})));
+        assert_eq!(transport.log().snapshot().diagnostics.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn oversized_response_fails_call_and_transport_recovers() {
+        let transport = fake_server(
+            r#"read line
+printf '{"jsonrpc":"2.0","id":1,"result":{"data":"%0200d"}}\n' 0
+read line
+printf '{"jsonrpc":"2.0","id":2,"result":{}}\n'
+sleep 1"#,
+            64,
+        )
+        .await;
+
+        let error = transport
+            .call("big", None, json!(1), ResponseTimeout::fixed(5000))
+            .await
+            .unwrap_err();
+        let error = error.downcast_ref::<FrameTooLargeError>().unwrap();
+        assert_eq!(error.limit, 64);
+        assert!(error.size > 200);
+
+        let response = transport
+            .call("small", None, json!(2), ResponseTimeout::fixed(5000))
+            .await
+            .unwrap();
+        assert_eq!(response.id, json!(2));
+    }
+
+    #[tokio::test]
+    async fn oversized_response_fails_only_its_own_call() {
+        let transport = fake_server(
+            r#"read line
+read line
+printf '{"jsonrpc":"2.0","id":2,"result":{"data":"%0200d"}}\n' 0
+printf '{"jsonrpc":"2.0","id":1,"result":{}}\n'
+sleep 1"#,
+            64,
+        )
+        .await;
+
+        let (small, big) = tokio::join!(
+            transport.call("small", None, json!(1), ResponseTimeout::fixed(5000)),
+            transport.call("big", None, json!(2), ResponseTimeout::fixed(5000)),
+        );
+
+        assert_eq!(small.unwrap().id, json!(1));
+        let error = big.unwrap_err();
+        assert!(error.downcast_ref::<FrameTooLargeError>().is_some());
+    }
+
+    #[tokio::test]
+    async fn closed_stdout_fails_call() {
+        let transport = fake_server("read line; echo 'bye'", 1024).await;
+
+        let error = transport
+            .call("ping", None, json!(1), ResponseTimeout::fixed(5000))
+            .await
+            .unwrap_err();
+
+        assert!(error.to_string().contains("stdout closed"));
     }
 }
@@ -0,0 +1,244 @@
+use anyhow::{anyhow, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Configuration kept in a [`ConfigStore`]
+pub trait StoredConfig: Clone + Serialize + DeserializeOwned {
+    /// Key of the list in the file, e.g. `servers`
+    const LIST_KEY: &'static str;
+    /// What an entry is called in errors, e.g. `Server config`
+    const ENTRY_NAME: &'static str;
+    /// Latest file format version this build reads and writes
+    const VERSION: u32;
+
+    fn id(&self) -> &str;
+
+    /// Check the configuration before it is saved
+    fn validate(&self) -> Result<()>;
+}
+
+/// On-disk layout: `{ "version": 1, "<list key>": [...] }`
+#[derive(Deserialize)]
+struct VersionedFile {
+    version: u32,
+    #[serde(flatten)]
+    lists: Map<String, Value>,
+}
+
+/// Persistent list of configurations in a versioned JSON file
+///
+/// Changes are written atomically before they are applied, so a failed write
+/// leaves both the file and the store as they were.
+pub struct ConfigStore<T> {
+    path: PathBuf,
+    entries: Mutex<Vec<T>>,
+}
+
+impl<T: StoredConfig> ConfigStore<T> {
+    /// Load the configuration file, starting empty if it does not exist yet
+    pub fn load(path: PathBuf) -> Result<Self> {
+        let entries = if path.exists() {
+            let contents = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let mut file: VersionedFile = serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+            if file.version > T::VERSION {
+                return Err(anyhow!(
+                    "{} has version {}, but this build only supports up to version {}",
+                    path.display(),
+                    file.version,
+                    T::VERSION
+                ));
+            }
+
+            let list = file
+                .lists
+                .remove(T::LIST_KEY)
+                .ok_or_else(|| anyhow!("{} has no '{}' list", path.display(), T::LIST_KEY))?;
+            serde_json::from_value(list)
+                .with_context(|| format!("Failed to parse {}", path.display()))?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            path,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    /// Load the configuration file, or set an unusable one aside and start empty
+    ///
+    /// A file that can't be read, parsed or understood, e.g. one written by a newer
+    /// build, gets an `.invalid` extension so the next save doesn't overwrite it.
+    pub fn load_or_empty(path: PathBuf) -> Self {
+        match Self::load(path.clone()) {
+            Ok(store) => store,
+            Err(e) => {
+                let aside = path.with_extension("json.invalid");
+                eprintln!(
+                    "Starting without saved {}, moving the config to {}: {:#}",
+                    T::LIST_KEY,
+                    aside.display(),
+                    e
+                );
+                if let Err(e) = std::fs::rename(&path, &aside) {
+                    eprintln!("Failed to move {}: {}", path.display(), e);
+                }
+                Self {
+                    path,
+                    entries: Mutex::new(Vec::new()),
+                }
+            }
+        }
+    }
+
+    /// All saved configurations
+    pub fn list(&self) -> Result<Vec<T>> {
+        let entries = self.entries.lock().map_err(|e| anyhow!(e.to_string()))?;
+        Ok(entries.clone())
+    }
+
+    pub fn get(&self, id: &str) -> Result<T> {
+        self.list()?
+            .into_iter()
+            .find(|entry| entry.id() == id)
+            .ok_or_else(|| anyhow!("{} '{}' not found", T::ENTRY_NAME, id))
+    }
+
+    /// Add a new configuration
+    pub fn add(&self, config: T) -> Result<()> {
+        config.validate()?;
+
+        let mut entries = self.entries.lock().map_err(|e| anyhow!(e.to_string()))?;
+        if entries.iter().any(|existing| existing.id() == config.id()) {
+            return Err(anyhow!(
+                "{} '{}' already exists",
+                T::ENTRY_NAME,
+                config.id()
+            ));
+        }
+
+        let mut updated = entries.clone();
+        updated.push(config);
+        write_file(&self.path, &updated)?;
+        *entries = updated;
+        Ok(())
+    }
+
+    /// Replace an existing configuration
+    pub fn update(&self, config: T) -> Result<()> {
+        config.validate()?;
+
+        let mut entries = self.entries.lock().map_err(|e| anyhow!(e.to_string()))?;
+        let index = entries
+            .iter()
+            .position(|existing| existing.id() == config.id())
+            .ok_or_else(|| anyhow!("{} '{}' not found", T::ENTRY_NAME, config.id()))?;
+
+        let mut updated = entries.clone();
+        updated[index] = config;
+        write_file(&self.path, &updated)?;
+        *entries = updated;
+        Ok(())
+    }
+
+    /// Remove a configuration
+    pub fn remove(&self, id: &str) -> Result<()> {
+        let mut entries = self.entries.lock().map_err(|e| anyhow!(e.to_string()))?;
+        if !entries.iter().any(|existing| existing.id() == id) {
+            return Err(anyhow!("{} '{}' not found", T::ENTRY_NAME, id));
+        }
+
+        let updated: Vec<T> = entries
+            .iter()
+            .filter(|existing| existing.id() != id)
+            .cloned()
+            .collect();
+        write_file(&self.path, &updated)?;
+        *entries = updated;
+        Ok(())
+    }
+}
+
+/// Write the configuration file atomically via a temporary file
+fn write_file<T: StoredConfig>(path: &Path, entries: &[T]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+
+    let mut file = Map::new();
+    file.insert("version".to_string(), T::VERSION.into());
+    file.insert(T::LIST_KEY.to_string(), serde_json::to_value(entries)?);
+    let json = serde_json::to_string_pretty(&file)?;
+
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, json)
+        .with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+    std::fs::rename(&tmp_path, path)
+        .with_context(|| format!("Failed to replace {}", path.display()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Serialize, Deserialize)]
+    struct Entry {
+        id: String,
+    }
+
+    impl StoredConfig for Entry {
+        const LIST_KEY: &'static str = "entries";
+        const ENTRY_NAME: &'static str = "Entry";
+        const VERSION: u32 = 2;
+
+        fn id(&self) -> &str {
+            &self.id
+        }
+
+        fn validate(&self) -> Result<()> {
+            match self.id.is_empty() {
+                true => Err(anyhow!("Entry ID must not be empty")),
+                false => Ok(()),
+            }
+        }
+    }
+
+    fn entry(id: &str) -> Entry {
+        Entry { id: id.to_string() }
+    }
+
+    #[test]
+    fn saves_entries_in_a_versioned_file() {
+        let dir = std::env::temp_dir().join(format!("orion-store-test-{}", std::process::id()));
+        let path = dir.join("entries.json");
+
+        let store = ConfigStore::<Entry>::load(path.clone()).unwrap();
+        store.add(entry("a")).unwrap();
+        store.add(entry("b")).unwrap();
+        assert!(store.add(entry("a")).is_err());
+        assert!(store.add(entry("")).is_err());
+        store.remove("a").unwrap();
+
+        let file: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            file,
+            serde_json::json!({ "version": 2, "entries": [{ "id": "b" }] })
+        );
+        let reloaded = ConfigStore::<Entry>::load(path.clone()).unwrap();
+        assert_eq!(reloaded.get("b").unwrap().id, "b");
+
+        std::fs::write(&path, r#"{ "version": 3, "entries": [] }"#).unwrap();
+        assert!(ConfigStore::<Entry>::load(path.clone()).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
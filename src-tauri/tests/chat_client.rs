@@ -0,0 +1,372 @@
+//! Drives the chat provider clients against a local mock of their streaming endpoints
+
+use axum::body::Body;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::Response;
+use axum::routing::post;
+use axum::{Json, Router};
+use orion_lib::llm::{
+    AnthropicClient, ChatChunk, ChatMessage, ChatProvider, ChatRequest, ChatResponse,
+    FallbackChain, LocalProvider, OpenAiClient, ProviderError, ProviderErrorKind, RequestLimits,
+    RequestScheduler, RetryPolicy,
+};
+use serde_json::Value;
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// What the mock answers with; the body is sent in the given pieces
+struct MockResponse {
+    status: StatusCode,
+    headers: Vec<(&'static str, &'static str)>,
+    body: Vec<String>,
+}
+
+impl MockResponse {
+    fn stream(events: &[&str]) -> Self {
+        Self {
+            status: StatusCode::OK,
+            headers: vec![("content-type", "text/event-stream")],
+            body: events
+                .iter()
+                .map(|event| format!("data: {}\n\n", event))
+                .collect(),
+        }
+    }
+
+    fn error(status: StatusCode, headers: Vec<(&'static str, &'static str)>) -> Self {
+        Self {
+            status,
+            headers,
+            body: vec![format!(
+                r#"{{"error":{{"message":"Failed with {}"}}}}"#,
+                status
+            )],
+        }
+    }
+}
+
+/// Headers and JSON body of the request the mock received
+type Received = Arc<Mutex<Option<(HeaderMap, Value)>>>;
+
+/// Serve `response` once per request on a free port, returning the base URL
+async fn mock_server(response: MockResponse) -> (String, Received) {
+    mock_server_answering(vec![response]).await
+}
+
+/// Serve the responses in turn, the last one to every request after that
+async fn mock_server_answering(responses: Vec<MockResponse>) -> (String, Received) {
+    let received: Received = Arc::default();
+    let responses = Arc::new(responses);
+    let requests = Arc::new(AtomicUsize::new(0));
+
+    let handler = post({
+        let received = received.clone();
+        move |headers: HeaderMap, Json(body): Json<Value>| async move {
+            *received.lock().unwrap() = Some((headers, body));
+            let index = requests.fetch_add(1, Ordering::SeqCst);
+            let response = &responses[index.min(responses.len() - 1)];
+
+            let pieces: Vec<Result<String, Infallible>> =
+                response.body.iter().cloned().map(Ok).collect();
+            let mut builder = Response::builder().status(response.status);
+            for (name, value) in &response.headers {
+                builder = builder.header(*name, *value);
+            }
+            builder
+                .body(Body::from_stream(futures::stream::iter(pieces)))
+                .unwrap()
+        }
+    });
+    let app = Router::new()
+        .route("/chat/completions", handler.clone())
+        .route("/messages", handler.clone())
+        .route("/api/chat", handler);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    (format!("http://{}", address), received)
+}
+
+fn openrouter(base_url: &str) -> Box<dyn ChatProvider> {
+    Box::new(OpenAiClient::openrouter("sk-test").with_base_url(base_url))
+}
+
+fn request() -> ChatRequest {
+    ChatRequest {
+        model: "test/model".to_string(),
+        messages: vec![ChatMessage::user("hi")],
+        tools: Vec::new(),
+    }
+}
+
+async fn stream(base_url: &str) -> (anyhow::Result<ChatResponse>, Vec<ChatChunk>) {
+    stream_from(&OpenAiClient::openrouter("sk-test").with_base_url(base_url)).await
+}
+
+async fn stream_from(client: &dyn ChatProvider) -> (anyhow::Result<ChatResponse>, Vec<ChatChunk>) {
+    let chunks = Mutex::new(Vec::new());
+    let on_chunk = |chunk| chunks.lock().unwrap().push(chunk);
+    let response = client.stream(&request(), &on_chunk).await;
+    (response, chunks.into_inner().unwrap())
+}
+
+fn provider_error(result: anyhow::Result<ChatResponse>) -> ProviderError {
+    result.unwrap_err().downcast::<ProviderError>().unwrap()
+}
+
+#[tokio::test]
+async fn streams_content_reasoning_tool_calls_and_usage() {
+    let mut response = MockResponse::stream(&[
+        r#"{"choices":[{"delta":{"reasoning":"Hmm"}}]}"#,
+        r#"{"choices":[{"delta":{"content":"Hel"}}]}"#,
+        r#"{"choices":[{"delta":{"content":"lo"}}]}"#,
+        r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"id":"call_1","function":{"name":"fs__read","arguments":"{\"pa"}}]}}]}"#,
+        r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"th\":\"/\"}"}}]}}]}"#,
+        r#"{"choices":[{"delta":{},"finish_reason":"tool_calls"}]}"#,
+        r#"{"choices":[],"usage":{"prompt_tokens":3,"completion_tokens":4,"total_tokens":7,"cost":0.001}}"#,
+        "[DONE]",
+    ]);
+    // Keep-alive comment and an event split across two writes
+    response
+        .body
+        .insert(0, ": OPENROUTER PROCESSING\n\n".to_string());
+    let split = response.body.remove(3);
+    let (head, tail) = split.split_at(10);
+    response.body.insert(3, tail.to_string());
+    response.body.insert(3, head.to_string());
+
+    let (base_url, received) = mock_server(response).await;
+    let (response, chunks) = stream(&base_url).await;
+    let response = response.unwrap();
+
+    assert_eq!(response.message.content.as_deref(), Some("Hello"));
+    assert_eq!(response.message.reasoning.as_deref(), Some("Hmm"));
+    assert_eq!(response.message.tool_calls[0].function.name, "fs__read");
+    assert_eq!(
+        response.message.tool_calls[0].function.arguments,
+        r#"{"path":"/"}"#
+    );
+    assert_eq!(response.finish_reason.as_deref(), Some("tool_calls"));
+    assert_eq!(response.usage.unwrap().total_tokens, 7);
+
+    let contents: Vec<&str> = chunks
+        .iter()
+        .filter_map(|chunk| match chunk {
+            ChatChunk::Content { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(contents, vec!["Hel", "lo"]);
+    assert!(matches!(chunks.last(), Some(ChatChunk::Usage { .. })));
+
+    let (headers, body) = received.lock().unwrap().take().unwrap();
+    assert_eq!(headers["authorization"], "Bearer sk-test");
+    assert_eq!(body["stream"], true);
+    assert_eq!(body["usage"]["include"], true);
+    assert_eq!(body["messages"][0]["content"], "hi");
+    assert!(body.get("tools").is_none());
+}
+
+#[tokio::test]
+async fn maps_error_responses() {
+    let (base_url, _) = mock_server(MockResponse {
+        status: StatusCode::TOO_MANY_REQUESTS,
+        headers: vec![("content-type", "application/json"), ("retry-after", "2")],
+        body: vec![r#"{"error":{"code":429,"message":"Rate limit exceeded"}}"#.to_string()],
+    })
+    .await;
+    let error = provider_error(stream(&base_url).await.0);
+    assert_eq!(error.kind, ProviderErrorKind::RateLimited);
+    assert_eq!(error.message, "Rate limit exceeded");
+    assert_eq!(error.retry_after_ms, Some(2000));
+
+    let (base_url, _) = mock_server(MockResponse {
+        status: StatusCode::UNAUTHORIZED,
+        headers: Vec::new(),
+        body: vec!["not json".to_string()],
+    })
+    .await;
+    let error = provider_error(stream(&base_url).await.0);
+    assert_eq!(error.kind, ProviderErrorKind::Auth);
+    assert_eq!(error.message, "OpenRouter API error: 401");
+
+    // Nothing listening on the port
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+    let error = provider_error(stream(&base_url).await.0);
+    assert_eq!(error.kind, ProviderErrorKind::Network);
+}
+
+#[tokio::test]
+async fn fails_on_errors_sent_mid_stream() {
+    let (base_url, _) = mock_server(MockResponse::stream(&[
+        r#"{"choices":[{"delta":{"content":"Par"}}]}"#,
+        r#"{"error":{"code":502,"message":"Provider returned error"},"choices":[{"delta":{"content":""},"finish_reason":"error"}]}"#,
+    ]))
+    .await;
+
+    let (response, chunks) = stream(&base_url).await;
+    let error = provider_error(response);
+    assert_eq!(error.kind, ProviderErrorKind::Server);
+    assert_eq!(error.status, Some(502));
+    assert_eq!(error.message, "Provider returned error");
+    assert_eq!(chunks.len(), 1);
+}
+
+#[tokio::test]
+async fn streams_anthropic_messages() {
+    let (base_url, received) = mock_server(MockResponse::stream(&[
+        r#"{"type":"message_start","message":{"usage":{"input_tokens":5,"output_tokens":1}}}"#,
+        r#"{"type":"content_block_start","index":0,"content_block":{"type":"thinking","thinking":""}}"#,
+        r#"{"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"Hmm"}}"#,
+        r#"{"type":"content_block_start","index":1,"content_block":{"type":"text","text":""}}"#,
+        r#"{"type":"content_block_delta","index":1,"delta":{"type":"text_delta","text":"Reading"}}"#,
+        r#"{"type":"content_block_start","index":2,"content_block":{"type":"tool_use","id":"toolu_1","name":"fs__read","input":{}}}"#,
+        r#"{"type":"content_block_delta","index":2,"delta":{"type":"input_json_delta","partial_json":"{\"path\":"}}"#,
+        r#"{"type":"content_block_delta","index":2,"delta":{"type":"input_json_delta","partial_json":"\"/\"}"}}"#,
+        r#"{"type":"message_delta","delta":{"stop_reason":"tool_use"},"usage":{"output_tokens":9}}"#,
+        r#"{"type":"message_stop"}"#,
+    ]))
+    .await;
+
+    let client = AnthropicClient::new("sk-ant-test").with_base_url(base_url);
+    let (response, _) = stream_from(&client).await;
+    let response = response.unwrap();
+
+    assert_eq!(response.message.content.as_deref(), Some("Reading"));
+    assert_eq!(response.message.reasoning.as_deref(), Some("Hmm"));
+    assert_eq!(response.message.tool_calls[0].id, "toolu_1");
+    assert_eq!(
+        response.message.tool_calls[0].function.arguments,
+        r#"{"path":"/"}"#
+    );
+    assert_eq!(response.finish_reason.as_deref(), Some("tool_calls"));
+    assert_eq!(response.usage.unwrap().total_tokens, 14);
+
+    let (headers, body) = received.lock().unwrap().take().unwrap();
+    assert_eq!(headers["x-api-key"], "sk-ant-test");
+    assert_eq!(body["messages"][0]["content"][0]["text"], "hi");
+    assert!(body["max_tokens"].is_u64());
+}
+
+#[tokio::test]
+async fn streams_ollama_chat_lines() {
+    let (base_url, received) = mock_server(MockResponse {
+        status: StatusCode::OK,
+        headers: vec![("content-type", "application/x-ndjson")],
+        body: vec![
+            "{\"message\":{\"role\":\"assistant\",\"content\":\"Rea\"},\"done\":false}\n{\"mess".to_string(),
+            "age\":{\"role\":\"assistant\",\"content\":\"ding\"},\"done\":false}\n".to_string(),
+            "{\"message\":{\"role\":\"assistant\",\"content\":\"\",\"tool_calls\":[{\"function\":{\"name\":\"fs__read\",\"arguments\":{\"path\":\"/\"}}}]},\"done\":false}\n".to_string(),
+            "{\"message\":{\"role\":\"assistant\",\"content\":\"\"},\"done\":true,\"done_reason\":\"stop\",\"prompt_eval_count\":4,\"eval_count\":6}\n".to_string(),
+        ],
+    })
+    .await;
+
+    let (response, _) = stream_from(&LocalProvider::ollama(Some(base_url))).await;
+    let response = response.unwrap();
+
+    assert_eq!(response.message.content.as_deref(), Some("Reading"));
+    assert_eq!(response.message.tool_calls[0].function.name, "fs__read");
+    assert_eq!(
+        response.message.tool_calls[0].function.arguments,
+        r#"{"path":"/"}"#
+    );
+    assert_eq!(response.finish_reason.as_deref(), Some("tool_calls"));
+    assert_eq!(response.usage.unwrap().total_tokens, 10);
+
+    let (_, body) = received.lock().unwrap().take().unwrap();
+    assert_eq!(body["model"], "test/model");
+    assert_eq!(body["messages"][0]["content"], "hi");
+}
+
+#[tokio::test]
+async fn explains_local_servers_that_are_not_running() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    let error = provider_error(
+        stream_from(&LocalProvider::ollama(Some(base_url.clone())))
+            .await
+            .0,
+    );
+    assert_eq!(error.kind, ProviderErrorKind::Network);
+    assert!(error
+        .message
+        .starts_with(&format!("Ollama is not running at {}", base_url)));
+
+    let llama_cpp = LocalProvider::llama_cpp(Some(base_url));
+    let error = llama_cpp.list_models().await.unwrap_err();
+    assert!(error.to_string().contains("llama-server"));
+}
+
+#[tokio::test]
+async fn retries_rate_limited_requests_after_retry_after() {
+    let (base_url, _) = mock_server_answering(vec![
+        MockResponse::error(StatusCode::TOO_MANY_REQUESTS, vec![("retry-after", "0.1")]),
+        MockResponse::error(StatusCode::BAD_GATEWAY, Vec::new()),
+        MockResponse::stream(&[r#"{"choices":[{"delta":{"content":"Hi"}}]}"#, "[DONE]"]),
+    ])
+    .await;
+    let limits = RequestLimits {
+        retry: RetryPolicy {
+            max_retries: 2,
+            base_delay_ms: 20,
+            max_delay_ms: 1000,
+        },
+        ..RequestLimits::default()
+    };
+    let scheduler = RequestScheduler::new();
+
+    let client = scheduler
+        .schedule("mock", &limits, openrouter(&base_url))
+        .unwrap();
+    let started = std::time::Instant::now();
+    let (response, _) = stream_from(client.as_ref()).await;
+    assert_eq!(response.unwrap().message.content.as_deref(), Some("Hi"));
+    assert!(started.elapsed() >= std::time::Duration::from_millis(100));
+
+    // Waiting longer than the policy allows fails right away
+    let (base_url, _) = mock_server(MockResponse::error(
+        StatusCode::TOO_MANY_REQUESTS,
+        vec![("retry-after", "5")],
+    ))
+    .await;
+    let client = scheduler
+        .schedule("slow", &limits, openrouter(&base_url))
+        .unwrap();
+    let error = provider_error(stream_from(client.as_ref()).await.0);
+    assert_eq!(error.kind, ProviderErrorKind::RateLimited);
+    assert_eq!(error.retry_after_ms, Some(5000));
+}
+
+#[tokio::test]
+async fn falls_back_to_the_next_model() {
+    let (failing_url, _) = mock_server(MockResponse::error(
+        StatusCode::SERVICE_UNAVAILABLE,
+        Vec::new(),
+    ))
+    .await;
+    let (base_url, received) = mock_server(MockResponse::stream(&[
+        r#"{"choices":[{"delta":{"content":"Hi"}}]}"#,
+        "[DONE]",
+    ]))
+    .await;
+
+    let chain = FallbackChain::new("anthropic/claude-sonnet-4", openrouter(&failing_url))
+        .or_else("openai/gpt-4o", openrouter(&base_url));
+    let response = stream_from(&chain).await.0.unwrap();
+    assert_eq!(response.message.content.as_deref(), Some("Hi"));
+    assert_eq!(response.model.as_deref(), Some("openai/gpt-4o"));
+    let (_, body) = received.lock().unwrap().take().unwrap();
+    assert_eq!(body["model"], "openai/gpt-4o");
+
+    // The last model's error is returned when every model fails
+    let chain = FallbackChain::new("openai/gpt-4o", openrouter(&failing_url));
+    let error = provider_error(stream_from(&chain).await.0);
+    assert_eq!(error.kind, ProviderErrorKind::Server);
+}
@@ -0,0 +1,846 @@
+//! Drives `McpServerManager` against the scriptable `fake-mcp-server` binary
+//!
+//! The binary is only built with the `test-support` feature, so run these with
+//! `cargo test --features test-support`.
+
+use futures::future::BoxFuture;
+use orion_lib::agent::runtime::{AgentLimits, AgentRequest, AgentRuntime, StopReason};
+use orion_lib::llm::{
+    ChatMessage, ChatProvider, ChatRequest, ChatResponse, ChunkSink, FunctionCall, ModelInfo, Role,
+    ToolCall,
+};
+use orion_lib::mcp::{
+    ApprovalDecision, AuditQuery, BatchCall, CallOptions, CancelToken, CancelledError,
+    GatewayClient, McpGateway, McpServerConfig, McpServerManager, SecretVault, TimeoutError,
+};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+
+/// Manager with its own vault and trace directory, removed on drop
+struct Harness {
+    manager: McpServerManager,
+    dir: PathBuf,
+}
+
+impl Harness {
+    fn new() -> Self {
+        let dir = std::env::temp_dir().join(format!(
+            "orion-manager-test-{}-{}",
+            std::process::id(),
+            NEXT_DIR.fetch_add(1, Ordering::SeqCst)
+        ));
+        let secrets = SecretVault::open_with_key_file(&dir).unwrap();
+        let manager = McpServerManager::new(Arc::new(secrets), &dir).unwrap();
+        Self { manager, dir }
+    }
+
+    /// Start a fake server running `script` under the given ID
+    async fn start(&self, id: &str, script: Value) -> anyhow::Result<String> {
+        self.manager
+            .start_server(fake_config(id, script, json!({})))
+            .await
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Config for a fake server; `extra` is merged into the config JSON
+fn fake_config(id: &str, script: Value, extra: Value) -> McpServerConfig {
+    let mut config = json!({
+        "id": id,
+        "name": "Fake",
+        "command": env!("CARGO_BIN_EXE_fake-mcp-server"),
+        "args": [script.to_string()],
+    });
+    for (key, value) in extra.as_object().unwrap() {
+        config[key] = value.clone();
+    }
+    serde_json::from_value(config).unwrap()
+}
+
+fn timeout(timeout_ms: u64) -> CallOptions {
+    CallOptions {
+        timeout_ms: Some(timeout_ms),
+        ..CallOptions::default()
+    }
+}
+
+fn text(result: &orion_lib::mcp::CallToolResult) -> &str {
+    result.content[0].text.as_deref().unwrap()
+}
+
+#[tokio::test]
+async fn starts_lists_and_calls_tools() {
+    let harness = Harness::new();
+    harness.start("fake", json!({})).await.unwrap();
+
+    assert_eq!(
+        harness.manager.list_servers().await,
+        vec!["fake".to_string()]
+    );
+    let server = harness.manager.get_server("fake").await.unwrap();
+    let info = server.server_info.as_ref().unwrap();
+    assert_eq!(info.server_info.name, "fake-mcp-server");
+
+    let tools = harness.manager.list_provider_tools("fake").await.unwrap();
+    let names: Vec<&str> = tools
+        .iter()
+        .map(|tool| tool.function.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["Fake__echo", "Fake__crash"]);
+
+    let tool = harness
+        .manager
+        .resolve_tool_name("Fake__echo")
+        .await
+        .unwrap();
+    assert_eq!(
+        (tool.server_id.as_str(), tool.tool_name.as_str()),
+        ("fake", "echo")
+    );
+
+    let result = harness
+        .manager
+        .call_tool(
+            "fake",
+            "echo",
+            Some(json!({ "greeting": "hi" })),
+            CallOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(text(&result), r#"{"greeting":"hi"}"#);
+}
+
+#[tokio::test]
+async fn skips_banners_notifications_and_malformed_output() {
+    let harness = Harness::new();
+    let script = json!({
+        "banner": "Fake server v1 starting...",
+        "stderr": ["warming up"],
+        "tools": [{ "name": "noisy", "result": "done", "notify": true, "malformed": true }],
+    });
+    harness.start("fake", script).await.unwrap();
+
+    let result = harness
+        .manager
+        .call_tool("fake", "noisy", None, CallOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(text(&result), "done");
+
+    let diagnostics = harness
+        .manager
+        .get_server("fake")
+        .await
+        .unwrap()
+        .diagnostics();
+    assert!(diagnostics
+        .diagnostics
+        .iter()
+        .any(|line| line.contains("Fake server v1 starting")));
+    assert!(diagnostics
+        .diagnostics
+        .iter()
+        .any(|line| line.contains("this is not JSON-RPC")));
+    assert_eq!(diagnostics.stderr, vec!["warming up".to_string()]);
+}
+
+#[tokio::test]
+async fn reports_tool_errors() {
+    let harness = Harness::new();
+    let script = json!({
+        "tools": [
+            { "name": "broken", "error": "database unavailable" },
+            { "name": "refuses", "result": "no such file", "isError": true },
+        ],
+    });
+    harness.start("fake", script).await.unwrap();
+
+    let error = harness
+        .manager
+        .call_tool("fake", "broken", None, CallOptions::default())
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("database unavailable"));
+
+    let result = harness
+        .manager
+        .call_tool("fake", "refuses", None, CallOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(result.is_error, Some(true));
+    assert_eq!(text(&result), "no such file");
+}
+
+#[tokio::test]
+async fn times_out_and_recovers() {
+    let harness = Harness::new();
+    let script = json!({
+        "tools": [{ "name": "slow", "delayMs": 1000 }, { "name": "echo" }],
+    });
+    harness.start("fake", script).await.unwrap();
+
+    let error = harness
+        .manager
+        .call_tool("fake", "slow", None, timeout(100))
+        .await
+        .unwrap_err();
+    let timeout = error.downcast_ref::<TimeoutError>().unwrap();
+    assert_eq!(
+        (timeout.method.as_str(), timeout.timeout_ms),
+        ("tools/call", 100)
+    );
+
+    // The late response to the timed out call is skipped
+    let result = harness
+        .manager
+        .call_tool(
+            "fake",
+            "echo",
+            Some(json!({ "n": 1 })),
+            CallOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(text(&result), r#"{"n":1}"#);
+}
+
+#[tokio::test]
+async fn cancels_calls_in_flight() {
+    let harness = Harness::new();
+    let script = json!({
+        "tools": [{ "name": "slow", "delayMs": 1000 }, { "name": "echo" }],
+    });
+    harness.start("fake", script).await.unwrap();
+
+    let cancel = CancelToken::new();
+    let trigger = cancel.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        trigger.cancel();
+    });
+    let options = CallOptions {
+        cancel: Some(cancel.clone()),
+        ..CallOptions::default()
+    };
+    let error = harness
+        .manager
+        .call_tool("fake", "slow", None, options.clone())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<CancelledError>().unwrap().method,
+        "tools/call"
+    );
+
+    // Calls made after the cancellation don't start at all
+    let error = harness
+        .manager
+        .call_tool("fake", "echo", None, options)
+        .await
+        .unwrap_err();
+    assert!(error.is::<CancelledError>());
+
+    let result = harness
+        .manager
+        .call_tool(
+            "fake",
+            "echo",
+            Some(json!({ "n": 1 })),
+            CallOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(text(&result), r#"{"n":1}"#);
+}
+
+#[tokio::test]
+async fn progress_resets_the_timeout() {
+    let harness = Harness::new();
+    let script = json!({
+        "tools": [{ "name": "steady", "delayMs": 1000, "progress": 10, "result": "finished" }],
+    });
+    let config = fake_config(
+        "fake",
+        script,
+        json!({ "timeouts": { "callMs": 400, "resetOnProgress": true } }),
+    );
+    harness.manager.start_server(config).await.unwrap();
+
+    let result = harness
+        .manager
+        .call_tool("fake", "steady", None, CallOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(text(&result), "finished");
+}
+
+#[tokio::test]
+async fn fails_to_start_when_initialize_fails() {
+    let harness = Harness::new();
+
+    let error = harness
+        .start("fake", json!({ "initializeError": "bad credentials" }))
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("bad credentials"));
+    assert!(harness.manager.list_servers().await.is_empty());
+
+    let error = harness
+        .manager
+        .start_server(fake_config(
+            "slow",
+            json!({ "initializeDelayMs": 1000 }),
+            json!({ "timeouts": { "initializeMs": 100 } }),
+        ))
+        .await
+        .unwrap_err();
+    assert!(error.is::<TimeoutError>());
+}
+
+#[tokio::test]
+async fn restarts_after_a_crash() {
+    let harness = Harness::new();
+    harness.start("fake", json!({})).await.unwrap();
+    let first_pid = harness
+        .manager
+        .get_server("fake")
+        .await
+        .unwrap()
+        .process
+        .pid;
+
+    assert!(harness
+        .manager
+        .call_tool("fake", "crash", None, CallOptions::default())
+        .await
+        .is_err());
+    assert!(harness
+        .manager
+        .call_tool("fake", "echo", None, CallOptions::default())
+        .await
+        .is_err());
+
+    harness.manager.restart_server("fake").await.unwrap();
+    let server = harness.manager.get_server("fake").await.unwrap();
+    assert_ne!(server.process.pid, first_pid);
+
+    let result = harness
+        .manager
+        .call_tool("fake", "echo", Some(json!({})), CallOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(text(&result), "{}");
+
+    let metrics = &harness.manager.metrics().snapshot()["fake"];
+    assert_eq!((metrics.starts, metrics.restarts), (2, 1));
+    assert_eq!((metrics.calls.calls, metrics.calls.errors), (3, 2));
+    assert_eq!(metrics.tools["echo"].calls, 2);
+}
+
+#[tokio::test]
+async fn stops_servers() {
+    let harness = Harness::new();
+    harness.start("fake", json!({})).await.unwrap();
+    harness.manager.list_provider_tools("fake").await.unwrap();
+    let pid = harness
+        .manager
+        .get_server("fake")
+        .await
+        .unwrap()
+        .process
+        .pid
+        .unwrap();
+
+    harness.manager.stop_server("fake").await.unwrap();
+
+    assert!(harness.manager.list_servers().await.is_empty());
+    assert!(harness
+        .manager
+        .resolve_tool_name("Fake__echo")
+        .await
+        .is_err());
+    assert!(harness
+        .manager
+        .call_tool("fake", "echo", None, CallOptions::default())
+        .await
+        .is_err());
+    assert!(harness.manager.stop_server("fake").await.is_err());
+
+    #[cfg(target_os = "linux")]
+    assert!(
+        process_exited(pid).await,
+        "server process {} still running",
+        pid
+    );
+    #[cfg(not(target_os = "linux"))]
+    let _ = pid;
+}
+
+#[tokio::test]
+async fn rejects_duplicate_ids() {
+    let harness = Harness::new();
+    harness.start("fake", json!({})).await.unwrap();
+
+    let error = harness.start("fake", json!({})).await.unwrap_err();
+    assert!(error.to_string().contains("already running"));
+}
+
+/// Wait up to two seconds for a process to exit; zombies count as exited
+#[cfg(target_os = "linux")]
+async fn process_exited(pid: u32) -> bool {
+    for _ in 0..40 {
+        match std::fs::read_to_string(format!("/proc/{}/stat", pid)) {
+            Err(_) => return true,
+            Ok(stat) => {
+                // The state follows the parenthesized command name
+                let state = stat
+                    .rsplit(')')
+                    .next()
+                    .and_then(|rest| rest.trim().chars().next());
+                if matches!(state, Some('Z') | Some('X')) {
+                    return true;
+                }
+            }
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    false
+}
+
+#[tokio::test]
+async fn audits_calls_and_denials() {
+    let harness = Harness::new();
+    harness.start("fake", json!({})).await.unwrap();
+
+    let options = CallOptions {
+        conversation_id: Some("conversation-1".to_string()),
+        ..CallOptions::default()
+    };
+    harness
+        .manager
+        .call_tool(
+            "fake",
+            "echo",
+            Some(json!({ "q": "x", "apiKey": "k" })),
+            options,
+        )
+        .await
+        .unwrap();
+
+    let denied = CallOptions {
+        approval: ApprovalDecision::Denied,
+        ..CallOptions::default()
+    };
+    assert!(harness
+        .manager
+        .call_tool("fake", "echo", None, denied)
+        .await
+        .is_err());
+
+    let entries = harness
+        .manager
+        .audit_log()
+        .query(&AuditQuery::default())
+        .unwrap();
+    assert_eq!(entries.len(), 2);
+
+    // Newest first
+    assert_eq!(entries[0].approval, ApprovalDecision::Denied);
+    assert!(entries[0].error.is_some());
+
+    let call = &entries[1];
+    assert_eq!(call.conversation_id.as_deref(), Some("conversation-1"));
+    assert_eq!(call.tool_name, "echo");
+    assert_eq!(
+        call.arguments,
+        Some(json!({ "q": "x", "apiKey": "[redacted]" }))
+    );
+    assert_eq!(
+        call.result_summary.as_deref(),
+        Some(r#"{"apiKey":"k","q":"x"}"#)
+    );
+
+    let errors = AuditQuery {
+        errors_only: true,
+        ..AuditQuery::default()
+    };
+    assert_eq!(harness.manager.audit_log().query(&errors).unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn queues_calls_over_the_concurrency_limit() {
+    let harness = Harness::new();
+    let script = json!({ "tools": [{ "name": "slow", "delayMs": 200, "result": "done" }] });
+    let config = fake_config("fake", script, json!({ "maxConcurrentCalls": 1 }));
+    harness.manager.start_server(config).await.unwrap();
+
+    let calls: Vec<_> = (0..3)
+        .map(|_| {
+            let manager = harness.manager.clone();
+            tokio::spawn(async move {
+                manager
+                    .call_tool("fake", "slow", None, CallOptions::default())
+                    .await
+            })
+        })
+        .collect();
+
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    let status = harness
+        .manager
+        .get_server("fake")
+        .await
+        .unwrap()
+        .queue_status();
+    assert_eq!((status.in_flight, status.queued), (1, 2));
+
+    for call in calls {
+        assert_eq!(text(&call.await.unwrap().unwrap()), "done");
+    }
+}
+
+#[tokio::test]
+async fn overlaps_calls_to_one_server_up_to_the_limit() {
+    let harness = Harness::new();
+    let script = json!({ "tools": [{ "name": "slow", "delayMs": 400, "result": "done" }] });
+    let config = fake_config("fake", script, json!({ "maxConcurrentCalls": 2 }));
+    harness.manager.start_server(config).await.unwrap();
+
+    let started = std::time::Instant::now();
+    let calls: Vec<_> = (0..2)
+        .map(|_| {
+            let manager = harness.manager.clone();
+            tokio::spawn(async move {
+                manager
+                    .call_tool("fake", "slow", None, CallOptions::default())
+                    .await
+            })
+        })
+        .collect();
+
+    for call in calls {
+        assert_eq!(text(&call.await.unwrap().unwrap()), "done");
+    }
+    // Taking turns would need at least 800ms
+    assert!(started.elapsed() < std::time::Duration::from_millis(700));
+}
+
+#[tokio::test]
+async fn truncates_large_results_and_pages_through_them() {
+    let harness = Harness::new();
+    let script = json!({ "tools": [{ "name": "dump", "result": "x".repeat(120) }] });
+    let config = fake_config(
+        "fake",
+        script,
+        json!({ "resultLimits": { "maxChars": 50 } }),
+    );
+    harness.manager.start_server(config).await.unwrap();
+
+    let result = harness
+        .manager
+        .call_tool("fake", "dump", None, CallOptions::default())
+        .await
+        .unwrap();
+    let truncated = text(&result);
+    assert!(truncated.starts_with(&format!("{}\n\n", "x".repeat(50))));
+    assert!(truncated.contains("showing 50 of 120 characters"));
+    assert!(truncated.contains("Orion__read_tool_result"));
+
+    let handle = truncated.split('"').nth(1).unwrap();
+    let pager = harness
+        .manager
+        .resolve_tool_name("Orion__read_tool_result")
+        .await
+        .unwrap();
+    let page = harness
+        .manager
+        .call_tool(
+            &pager.server_id,
+            &pager.tool_name,
+            Some(json!({ "handle": handle, "offset": 50 })),
+            CallOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(text(&page), "x".repeat(70));
+}
+
+#[tokio::test]
+async fn caches_read_only_tools_until_the_server_reports_changes() {
+    let harness = Harness::new();
+    let script = json!({
+        "tools": [
+            { "name": "list", "annotations": { "readOnlyHint": true } },
+            { "name": "write", "listChanged": true },
+        ],
+    });
+    let config = fake_config("fake", script, json!({ "cache": { "enabled": true } }));
+    harness.manager.start_server(config).await.unwrap();
+    harness.manager.list_tools("fake").await.unwrap();
+
+    let call = |tool: &'static str, arguments: Value| {
+        let manager = harness.manager.clone();
+        async move {
+            manager
+                .call_tool("fake", tool, Some(arguments), CallOptions::default())
+                .await
+                .unwrap()
+        }
+    };
+    let cache_hit = |result: &orion_lib::mcp::CallToolResult| {
+        result
+            .meta
+            .as_ref()
+            .is_some_and(|meta| meta["orion/cache"]["hit"] == true)
+    };
+
+    assert!(!cache_hit(&call("list", json!({ "a": 1, "b": 2 })).await));
+    assert!(cache_hit(&call("list", json!({ "b": 2, "a": 1 })).await));
+    assert!(!cache_hit(&call("list", json!({ "a": 2 })).await));
+
+    // Tools without read-only or idempotent hints are never cached
+    assert!(!cache_hit(&call("write", json!({})).await));
+    assert!(!cache_hit(&call("write", json!({})).await));
+
+    // The write announced a change, so the cached listing is stale
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert!(!cache_hit(&call("list", json!({ "a": 1, "b": 2 })).await));
+    assert!(cache_hit(&call("list", json!({ "a": 1, "b": 2 })).await));
+}
+
+#[tokio::test]
+async fn runs_batches_concurrently_with_per_call_errors() {
+    let harness = Harness::new();
+    let slow = json!({ "tools": [{ "name": "slow", "delayMs": 300, "result": "done" }] });
+    harness.start("a", slow.clone()).await.unwrap();
+    harness.start("b", slow).await.unwrap();
+
+    let calls: Vec<BatchCall> = serde_json::from_value(json!([
+        { "serverId": "a", "toolName": "slow" },
+        { "serverId": "missing", "toolName": "slow" },
+        { "serverId": "b", "toolName": "slow" },
+        { "serverId": "b", "toolName": "slow", "timeoutMs": 50 },
+        { "serverId": "a", "toolName": "slow" },
+    ]))
+    .unwrap();
+
+    let started = std::time::Instant::now();
+    let outcomes = harness.manager.call_tools_batch(calls, None).await;
+    // Calls to the same server overlap too
+    assert!(started.elapsed() < std::time::Duration::from_millis(550));
+
+    assert_eq!(text(outcomes[0].as_ref().unwrap()), "done");
+    assert!(outcomes[1]
+        .as_ref()
+        .unwrap_err()
+        .to_string()
+        .contains("not found"));
+    assert_eq!(text(outcomes[2].as_ref().unwrap()), "done");
+    assert!(outcomes[3].as_ref().unwrap_err().is::<TimeoutError>());
+    assert_eq!(text(outcomes[4].as_ref().unwrap()), "done");
+}
+
+#[tokio::test]
+async fn times_out_batch_calls_waiting_for_the_concurrency_limit() {
+    let harness = Harness::new();
+    let script = json!({ "tools": [{ "name": "slow", "delayMs": 300, "result": "done" }] });
+    let config = fake_config("fake", script, json!({ "maxConcurrentCalls": 1 }));
+    harness.manager.start_server(config).await.unwrap();
+
+    let calls: Vec<BatchCall> = serde_json::from_value(json!([
+        { "serverId": "fake", "toolName": "slow", "timeoutMs": 450 },
+        { "serverId": "fake", "toolName": "slow", "timeoutMs": 450 },
+    ]))
+    .unwrap();
+
+    let outcomes = harness.manager.call_tools_batch(calls, None).await;
+    assert_eq!(text(outcomes[0].as_ref().unwrap()), "done");
+    assert!(outcomes[1].as_ref().unwrap_err().is::<TimeoutError>());
+
+    // The timed out call is logged with its error like any other
+    let errors = AuditQuery {
+        errors_only: true,
+        ..AuditQuery::default()
+    };
+    let entries = harness.manager.audit_log().query(&errors).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert!(entries[0].error.as_deref().unwrap().contains("Timeout"));
+}
+
+#[tokio::test]
+async fn forwards_clients_to_the_running_gateway() {
+    let harness = Harness::new();
+    harness.start("fake", json!({})).await.unwrap();
+    assert!(GatewayClient::connect(&harness.dir).is_err());
+
+    let gateway = McpGateway::new(&harness.dir);
+    gateway.start(harness.manager.clone(), 0).await.unwrap();
+    let client = GatewayClient::connect(&harness.dir).unwrap();
+
+    let list = json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" });
+    let response = client.forward(list.to_string()).await.unwrap();
+    assert_eq!(response.id, json!(1));
+    let tools = response.result.unwrap()["tools"].clone();
+    let echo = tools
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap())
+        .find(|name| name.ends_with("echo"))
+        .unwrap()
+        .to_string();
+
+    let call = json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "tools/call",
+        "params": { "name": echo, "arguments": { "n": 1 } },
+    });
+    let response = client.forward(call.to_string()).await.unwrap();
+    assert_eq!(response.result.unwrap()["content"][0]["text"], r#"{"n":1}"#);
+
+    let notification = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+    assert!(client.forward(notification.to_string()).await.is_none());
+
+    gateway.stop().await.unwrap();
+    assert!(GatewayClient::connect(&harness.dir).is_err());
+    let response = client.forward(call.to_string()).await.unwrap();
+    assert!(response.error.unwrap().message.contains("Failed to reach"));
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn runs_sandboxed_servers() {
+    let harness = Harness::new();
+    let config = fake_config(
+        "fake",
+        json!({}),
+        json!({ "sandbox": { "allowNetwork": false, "maxOpenFiles": 256 } }),
+    );
+    match harness.manager.start_server(config).await {
+        Ok(_) => {}
+        // Nothing to test on kernels without Landlock
+        Err(e) if e.to_string().contains("Landlock is not available") => return,
+        Err(e) => panic!("{:#}", e),
+    }
+
+    let result = harness
+        .manager
+        .call_tool(
+            "fake",
+            "echo",
+            Some(json!({ "n": 1 })),
+            CallOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(text(&result), r#"{"n":1}"#);
+}
+
+/// Model that calls the given tools at once and then answers
+struct CallingProvider {
+    tool_names: Vec<String>,
+}
+
+impl ChatProvider for CallingProvider {
+    fn stream<'a>(
+        &'a self,
+        request: &'a ChatRequest,
+        _: ChunkSink<'a>,
+    ) -> BoxFuture<'a, anyhow::Result<ChatResponse>> {
+        let message = match request.messages.last() {
+            Some(message) if message.role == Role::Tool => ChatMessage::assistant("done"),
+            _ => ChatMessage {
+                content: None,
+                tool_calls: self
+                    .tool_names
+                    .iter()
+                    .enumerate()
+                    .map(|(index, name)| ToolCall {
+                        id: format!("call-{}", index + 1),
+                        call_type: "function".to_string(),
+                        function: FunctionCall {
+                            name: name.clone(),
+                            arguments: "{}".to_string(),
+                        },
+                    })
+                    .collect(),
+                ..ChatMessage::assistant("")
+            },
+        };
+        Box::pin(async move {
+            Ok(ChatResponse {
+                message,
+                usage: None,
+                finish_reason: None,
+                model: None,
+            })
+        })
+    }
+
+    fn list_models(&self) -> BoxFuture<'_, anyhow::Result<Vec<ModelInfo>>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+}
+
+#[tokio::test]
+async fn answers_unfinished_agent_tool_calls_at_the_deadline() {
+    let harness = Harness::new();
+    let script = json!({ "tools": [
+        { "name": "quick", "result": "done" },
+        { "name": "slow", "delayMs": 2000 },
+    ] });
+    harness.start("fake", script).await.unwrap();
+    let tools = harness.manager.list_provider_tools("fake").await.unwrap();
+    let provider = CallingProvider {
+        tool_names: tools
+            .iter()
+            .map(|tool| tool.function.name.clone())
+            .collect(),
+    };
+
+    let request = AgentRequest {
+        model: "test/model".to_string(),
+        fallbacks: Vec::new(),
+        messages: vec![ChatMessage::user("hi")],
+        conversation_id: None,
+        limits: AgentLimits {
+            max_duration_ms: 200,
+            ..AgentLimits::default()
+        },
+    };
+    let run = AgentRuntime::new(&provider, &harness.manager)
+        .run(request, |_| {})
+        .await
+        .unwrap();
+
+    assert_eq!(run.reason, StopReason::Timeout);
+    // Both calls got a result, so the conversation can be continued
+    assert_eq!(run.messages.len(), 3);
+    assert_eq!(run.messages[1].tool_call_id.as_deref(), Some("call-1"));
+    assert_eq!(run.messages[1].content.as_deref(), Some("done"));
+    assert_eq!(run.messages[2].tool_call_id.as_deref(), Some("call-2"));
+    assert!(run.messages[2]
+        .content
+        .as_deref()
+        .unwrap()
+        .contains("time limit"));
+
+    // The unfinished call was cancelled and logged rather than dropped
+    let entries = harness
+        .manager
+        .audit_log()
+        .query(&AuditQuery::default())
+        .unwrap();
+    assert_eq!(entries.len(), 2);
+}
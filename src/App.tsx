@@ -6,6 +6,9 @@ import { ToastProvider } from './components/ui/toast';
 import { useKeyboardShortcuts } from './hooks/useKeyboardShortcuts';
 import { useChatStore } from './stores/chatStore';
 import { useSettingsStore } from './stores/settingsStore';
+import { useMcpStore } from './stores/mcpStore';
+import { usePromptStore } from './stores/promptStore';
+import { useProviderStore } from './stores/providerStore';
 
 function App() {
   const [showShortcuts, setShowShortcuts] = useState(false);
@@ -15,6 +18,24 @@ function App() {
 
   const createConversation = useChatStore((state) => state.createConversation);
   const selectedModel = useSettingsStore((state) => state.selectedModel);
+  const loadMcpServers = useMcpStore((state) => state.loadServers);
+  const loadConversations = useChatStore((state) => state.loadConversations);
+  const loadPrompts = usePromptStore((state) => state.loadPrompts);
+  const loadApiKeyStatus = useSettingsStore((state) => state.loadApiKeyStatus);
+  const loadProviders = useProviderStore((state) => state.loadProviders);
+
+  // Sync MCP servers with the configs saved by the backend
+  useEffect(() => {
+    loadMcpServers();
+  }, [loadMcpServers]);
+
+  // Conversations, prompts, providers and the API key are saved by the backend too
+  useEffect(() => {
+    loadConversations();
+    loadPrompts();
+    loadApiKeyStatus();
+    loadProviders();
+  }, [loadConversations, loadPrompts, loadApiKeyStatus, loadProviders]);
 
   // Expose refs for keyboard shortcuts
   useEffect(() => {
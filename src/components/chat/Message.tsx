@@ -70,6 +70,9 @@ export const Message = memo(function Message({ message }: MessageProps) {
             ) : message.isStreaming ? (
               <TypingIndicator />
             ) : null}
+            {message.stopped && (
+              <div className="mt-2 text-xs italic text-muted-foreground">Stopped</div>
+            )}
           </div>
         )}
 
@@ -7,8 +7,12 @@ import {
   Check,
   Keyboard,
   RefreshCw,
+  Plus,
+  X,
 } from 'lucide-react';
+import { useChatStore } from '@/stores/chatStore';
 import { useSettingsStore } from '@/stores/settingsStore';
+import { useProviderStore } from '@/stores/providerStore';
 import { SettingsDialog } from '@/components/settings/SettingsDialog';
 import { Button } from '@/components/ui/button';
 import { Input } from '@/components/ui/input';
@@ -22,6 +26,7 @@ import {
   DropdownMenuLabel,
 } from '@/components/ui/dropdown-menu';
 import { fetchOpenRouterModels, type ModelInfo } from '@/services/openrouterModels';
+import { DEFAULT_PROVIDER_ID } from '@/types/provider';
 
 export function Header() {
   const [isSettingsOpen, setIsSettingsOpen] = useState(false);
@@ -32,8 +37,16 @@ export function Header() {
   const [modelsLoaded, setModelsLoaded] = useState(false);
 
   const selectedModel = useSettingsStore((state) => state.selectedModel);
+  const selectedProvider = useSettingsStore((state) => state.selectedProvider);
   const setSelectedModel = useSettingsStore((state) => state.setSelectedModel);
   const setAvailableModels = useSettingsStore((state) => state.setAvailableModels);
+  const providers = useProviderStore((state) => state.providers);
+  const providerModels = useProviderStore((state) => state.models);
+  const activeConversation = useChatStore((state) =>
+    state.conversations.find((c) => c.id === state.activeConversationId)
+  );
+  const setFallbackModels = useChatStore((state) => state.setFallbackModels);
+  const fallbackModels = activeConversation?.fallbackModels ?? [];
 
   useEffect(() => {
     loadModels();
@@ -82,7 +95,72 @@ export function Header() {
   const featuredModels = filteredModels.filter((m) => m.featured);
   const otherModels = filteredModels.filter((m) => !m.featured);
 
-  const currentModel = getModelInfo(selectedModel);
+  const currentProvider = providers.find((provider) => provider.id === selectedProvider);
+  const currentModel = currentProvider
+    ? { name: selectedModel, provider: currentProvider.name, contextWindow: 'N/A' }
+    : getModelInfo(selectedModel);
+
+  const isSelected = (modelId: string, providerId: string) =>
+    selectedModel === modelId && selectedProvider === providerId;
+
+  const selectModel = (modelId: string, providerId: string) => {
+    setSelectedModel(modelId, providerId);
+    setIsModelMenuOpen(false);
+    setModelSearch('');
+  };
+
+  // Fallbacks remember the provider of the model, like the selected model does
+  const isFallback = (modelId: string, providerId: string) =>
+    fallbackModels.some(
+      (f) => f.model === modelId && (f.providerId ?? DEFAULT_PROVIDER_ID) === providerId
+    );
+
+  const toggleFallback = (modelId: string, providerId: string) => {
+    if (!activeConversation) return;
+    setFallbackModels(
+      activeConversation.id,
+      isFallback(modelId, providerId)
+        ? fallbackModels.filter(
+            (f) => f.model !== modelId || (f.providerId ?? DEFAULT_PROVIDER_ID) !== providerId
+          )
+        : [...fallbackModels, { providerId, model: modelId }]
+    );
+  };
+
+  const fallbackButton = (modelId: string, providerId: string = DEFAULT_PROVIDER_ID) =>
+    activeConversation && !isSelected(modelId, providerId) ? (
+      <button
+        type="button"
+        onClick={(e) => {
+          e.stopPropagation();
+          toggleFallback(modelId, providerId);
+        }}
+        className={`ml-auto rounded-md p-1 transition-colors hover:bg-white/10 ${
+          isFallback(modelId, providerId) ? 'text-primary' : 'text-muted-foreground'
+        }`}
+        title={
+          isFallback(modelId, providerId)
+            ? 'Remove from fallbacks'
+            : 'Use as fallback for this conversation when the current model fails'
+        }
+      >
+        {isFallback(modelId, providerId) ? (
+          <X className="h-3.5 w-3.5" />
+        ) : (
+          <Plus className="h-3.5 w-3.5" />
+        )}
+      </button>
+    ) : null;
+
+  const query = modelSearch.trim().toLowerCase();
+  const providerSections = providers
+    .map((provider) => ({
+      provider,
+      models: (providerModels[provider.id] ?? []).filter((model) =>
+        model.id.toLowerCase().includes(query)
+      ),
+    }))
+    .filter((section) => section.models.length > 0);
 
   return (
     <header className="flex h-16 items-center justify-between border-b border-white/5 bg-gradient-to-r from-white/10 via-transparent to-transparent px-6">
@@ -140,6 +218,29 @@ export function Header() {
               {modelsLoaded && (
                 <p className="text-xs text-muted-foreground">{allModels.length} models available</p>
               )}
+              {fallbackModels.length > 0 && (
+                <div className="flex flex-wrap items-center gap-1.5 text-xs text-muted-foreground">
+                  <span>Falls back to</span>
+                  {fallbackModels.map((fallback) => (
+                    <span
+                      key={`${fallback.providerId ?? DEFAULT_PROVIDER_ID}:${fallback.model}`}
+                      className="flex items-center gap-1 rounded-full border border-white/10 bg-white/5 px-2 py-0.5"
+                    >
+                      {getModelInfo(fallback.model).name}
+                      <button
+                        type="button"
+                        onClick={() =>
+                          toggleFallback(fallback.model, fallback.providerId ?? DEFAULT_PROVIDER_ID)
+                        }
+                        className="hover:text-foreground"
+                        title="Remove from fallbacks"
+                      >
+                        <X className="h-3 w-3" />
+                      </button>
+                    </span>
+                  ))}
+                </div>
+              )}
             </div>
 
             <ScrollArea className="max-h-[420px]">
@@ -148,7 +249,7 @@ export function Header() {
                   <RefreshCw className="mx-auto mb-2 h-5 w-5 animate-spin" />
                   Loading models...
                 </div>
-              ) : filteredModels.length === 0 ? (
+              ) : filteredModels.length === 0 && providerSections.length === 0 ? (
                 <div className="px-3 py-8 text-center text-sm text-muted-foreground">
                   No models found
                 </div>
@@ -163,11 +264,7 @@ export function Header() {
                       {featuredModels.map((model) => (
                         <DropdownMenuItem
                           key={model.id}
-                          onClick={() => {
-                            setSelectedModel(model.id);
-                            setIsModelMenuOpen(false);
-                            setModelSearch('');
-                          }}
+                          onClick={() => selectModel(model.id, DEFAULT_PROVIDER_ID)}
                           className="cursor-pointer px-3 py-3 transition-colors hover:bg-primary/5"
                         >
                           <div className="flex flex-1 items-start gap-3">
@@ -177,9 +274,10 @@ export function Header() {
                             <div className="min-w-0 flex-1">
                               <div className="mb-1 flex items-center gap-2">
                                 <span className="font-medium">{model.name}</span>
-                                {selectedModel === model.id && (
+                                {isSelected(model.id, DEFAULT_PROVIDER_ID) && (
                                   <Check className="h-4 w-4 flex-shrink-0 text-primary" />
                                 )}
+                                {fallbackButton(model.id)}
                               </div>
                               <div className="space-y-0.5 text-xs text-muted-foreground">
                                 <div>{model.provider}</div>
@@ -207,11 +305,7 @@ export function Header() {
                       {otherModels.map((model) => (
                         <DropdownMenuItem
                           key={model.id}
-                          onClick={() => {
-                            setSelectedModel(model.id);
-                            setIsModelMenuOpen(false);
-                            setModelSearch('');
-                          }}
+                          onClick={() => selectModel(model.id, DEFAULT_PROVIDER_ID)}
                           className="cursor-pointer px-3 py-3 transition-colors hover:bg-primary/5"
                         >
                           <div className="flex flex-1 items-start gap-3">
@@ -221,9 +315,10 @@ export function Header() {
                             <div className="min-w-0 flex-1">
                               <div className="mb-1 flex items-center gap-2">
                                 <span className="font-medium">{model.name}</span>
-                                {selectedModel === model.id && (
+                                {isSelected(model.id, DEFAULT_PROVIDER_ID) && (
                                   <Check className="h-4 w-4 flex-shrink-0 text-primary" />
                                 )}
+                                {fallbackButton(model.id)}
                               </div>
                               <div className="space-y-0.5 text-xs text-muted-foreground">
                                 <div>{model.provider}</div>
@@ -239,6 +334,30 @@ export function Header() {
                       ))}
                     </>
                   )}
+
+                  {providerSections.map(({ provider, models }) => (
+                    <div key={provider.id}>
+                      <DropdownMenuSeparator className="bg-white/5" />
+                      <DropdownMenuLabel className="py-2 text-muted-foreground">
+                        {provider.name}
+                      </DropdownMenuLabel>
+                      {models.map((model) => (
+                        <DropdownMenuItem
+                          key={model.id}
+                          onClick={() => selectModel(model.id, provider.id)}
+                          className="cursor-pointer px-3 py-2 transition-colors hover:bg-primary/5"
+                        >
+                          <div className="flex flex-1 items-center gap-2">
+                            <span className="font-medium">{model.name ?? model.id}</span>
+                            {isSelected(model.id, provider.id) && (
+                              <Check className="h-4 w-4 flex-shrink-0 text-primary" />
+                            )}
+                            {fallbackButton(model.id, provider.id)}
+                          </div>
+                        </DropdownMenuItem>
+                      ))}
+                    </div>
+                  ))}
                 </>
               )}
             </ScrollArea>
@@ -1,18 +1,20 @@
-import { useState } from 'react';
+import { useEffect, useState } from 'react';
 import { useMcpStore } from '@/stores/mcpStore';
 import { Button } from '@/components/ui/button';
 import { Dialog, DialogContent, DialogDescription, DialogFooter, DialogHeader, DialogTitle } from '@/components/ui/dialog';
 import { Input } from '@/components/ui/input';
 import { Label } from '@/components/ui/label';
-import type { McpServerConfig } from '@/types/mcp';
+import type { McpServer, McpServerConfig } from '@/types/mcp';
 
 interface McpServerDialogProps {
   open: boolean;
   onOpenChange: (open: boolean) => void;
+  /** Server to edit; a new server is added when omitted */
+  server?: McpServer;
 }
 
-export function McpServerDialog({ open, onOpenChange }: McpServerDialogProps) {
-  const { addServer } = useMcpStore();
+export function McpServerDialog({ open, onOpenChange, server }: McpServerDialogProps) {
+  const { addServer, updateServer } = useMcpStore();
   const [config, setConfig] = useState<Partial<McpServerConfig>>({
     name: '',
     command: 'node',
@@ -23,6 +25,31 @@ export function McpServerDialog({ open, onOpenChange }: McpServerDialogProps) {
   const [argsInput, setArgsInput] = useState('');
   const [envInput, setEnvInput] = useState('');
 
+  // Fill the form with the server being edited, or start a new one blank
+  useEffect(() => {
+    if (!open) return;
+    if (!server) {
+      setConfig({ name: '', command: 'node', args: [], env: {}, autoStart: true });
+      setArgsInput('');
+      setEnvInput('');
+      return;
+    }
+    setConfig({
+      name: server.name,
+      command: server.command,
+      args: server.args,
+      env: server.env,
+      timeouts: server.timeouts,
+      autoStart: server.autostart ?? false,
+    });
+    setArgsInput(server.args.join(' '));
+    setEnvInput(
+      Object.entries(server.env || {})
+        .map(([key, value]) => `${key}=${value}`)
+        .join('\n')
+    );
+  }, [open, server]);
+
   const handleSave = async () => {
     if (!config.name || !config.command) {
       return;
@@ -47,23 +74,23 @@ export function McpServerDialog({ open, onOpenChange }: McpServerDialogProps) {
       command: config.command,
       args,
       env,
+      ...(config.timeouts && { timeouts: config.timeouts }),
       autoStart: config.autoStart ?? true,
     };
 
-    await addServer(fullConfig);
+    if (server) {
+      await updateServer(server.id, fullConfig);
+    } else {
+      await addServer(fullConfig);
+    }
     onOpenChange(false);
-
-    // Reset form
-    setConfig({ name: '', command: 'node', args: [], env: {}, autoStart: true });
-    setArgsInput('');
-    setEnvInput('');
   };
 
   return (
     <Dialog open={open} onOpenChange={onOpenChange}>
       <DialogContent className="sm:max-w-[500px]">
         <DialogHeader>
-          <DialogTitle>Add MCP Server</DialogTitle>
+          <DialogTitle>{server ? 'Edit MCP Server' : 'Add MCP Server'}</DialogTitle>
           <DialogDescription>
             Configure a Model Context Protocol server to provide tools to the AI.
           </DialogDescription>
@@ -129,7 +156,7 @@ export function McpServerDialog({ open, onOpenChange }: McpServerDialogProps) {
               onChange={(e) => setConfig({ ...config, autoStart: e.target.checked })}
             />
             <Label htmlFor="autoStart" className="cursor-pointer">
-              Auto-start when added
+              {server ? 'Auto-start with Orion' : 'Auto-start when added'}
             </Label>
           </div>
         </div>
@@ -139,7 +166,7 @@ export function McpServerDialog({ open, onOpenChange }: McpServerDialogProps) {
             Cancel
           </Button>
           <Button onClick={handleSave} disabled={!config.name || !config.command}>
-            Add Server
+            {server ? 'Save Server' : 'Add Server'}
           </Button>
         </DialogFooter>
       </DialogContent>
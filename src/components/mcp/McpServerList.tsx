@@ -1,9 +1,14 @@
 import { useMcpStore } from '@/stores/mcpStore';
 import { Button } from '@/components/ui/button';
 import { ScrollArea } from '@/components/ui/scroll-area';
-import { Play, Square, Trash2, Wrench } from 'lucide-react';
+import { Pencil, Play, Square, Trash2, Wrench } from 'lucide-react';
+import type { McpServer } from '@/types/mcp';
 
-export function McpServerList() {
+interface McpServerListProps {
+  onEdit?: (server: McpServer) => void;
+}
+
+export function McpServerList({ onEdit }: McpServerListProps) {
   const { servers, startServer, stopServer, removeServer, getServerTools } = useMcpStore();
 
   const getStatusColor = (status: string) => {
@@ -92,6 +97,17 @@ export function McpServerList() {
                       <Play className="h-4 w-4" />
                     </Button>
                   )}
+                  {onEdit && (
+                    <Button
+                      variant="ghost"
+                      size="icon"
+                      className="h-8 w-8"
+                      onClick={() => onEdit(server)}
+                      title="Edit server"
+                    >
+                      <Pencil className="h-4 w-4" />
+                    </Button>
+                  )}
                   <Button
                     variant="ghost"
                     size="icon"
@@ -0,0 +1,132 @@
+import { useState } from 'react';
+import { useProviderStore } from '@/stores/providerStore';
+import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
+import { Plus, Trash2 } from 'lucide-react';
+import type { ProviderKind } from '@/types/provider';
+
+const PROVIDER_KINDS: { kind: ProviderKind; label: string }[] = [
+  { kind: 'openAi', label: 'OpenAI' },
+  { kind: 'anthropic', label: 'Anthropic' },
+  { kind: 'openRouter', label: 'OpenRouter (another key)' },
+  { kind: 'openAiCompatible', label: 'OpenAI-compatible server' },
+  { kind: 'ollama', label: 'Ollama' },
+  { kind: 'llamaCpp', label: 'llama.cpp' },
+];
+
+/** Chat providers besides OpenRouter; their models show up in the model picker */
+export function ProviderList() {
+  const { providers, addProvider, removeProvider } = useProviderStore();
+  const [name, setName] = useState('');
+  const [kind, setKind] = useState<ProviderKind>('openAi');
+  const [baseUrl, setBaseUrl] = useState('');
+  const [apiKey, setApiKey] = useState('');
+  const [models, setModels] = useState('');
+  const [error, setError] = useState<string | null>(null);
+
+  const handleAdd = async () => {
+    try {
+      const id = name.trim().toLowerCase().replace(/[^a-z0-9]+/g, '-');
+      await addProvider({
+        id,
+        name: name.trim(),
+        kind,
+        baseUrl: baseUrl.trim() || undefined,
+        apiKey: apiKey.trim() || undefined,
+        models: models
+          .split(',')
+          .map((model) => model.trim())
+          .filter(Boolean),
+      });
+      setName('');
+      setBaseUrl('');
+      setApiKey('');
+      setModels('');
+      setError(null);
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  const handleRemove = async (id: string) => {
+    try {
+      await removeProvider(id);
+      setError(null);
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  return (
+    <div className="space-y-2">
+      {providers.map((provider) => (
+        <div
+          key={provider.id}
+          className="flex items-center justify-between rounded-md border border-border px-3 py-2"
+        >
+          <div className="min-w-0">
+            <p className="text-sm font-medium truncate">{provider.name}</p>
+            <p className="text-xs text-muted-foreground truncate">
+              {PROVIDER_KINDS.find((option) => option.kind === provider.kind)?.label}
+              {provider.baseUrl && ` • ${provider.baseUrl}`}
+            </p>
+          </div>
+          <Button
+            variant="ghost"
+            size="icon"
+            className="h-8 w-8"
+            onClick={() => handleRemove(provider.id)}
+            title="Delete provider"
+          >
+            <Trash2 className="h-4 w-4" />
+          </Button>
+        </div>
+      ))}
+
+      <div className="grid grid-cols-2 gap-2">
+        <Input value={name} onChange={(e) => setName(e.target.value)} placeholder="Name" />
+        <select
+          value={kind}
+          onChange={(e) => setKind(e.target.value as ProviderKind)}
+          className="flex h-9 w-full rounded-md border border-input bg-transparent px-3 py-1 text-sm shadow-sm"
+        >
+          {PROVIDER_KINDS.map((option) => (
+            <option key={option.kind} value={option.kind}>
+              {option.label}
+            </option>
+          ))}
+        </select>
+      </div>
+      <Input
+        value={baseUrl}
+        onChange={(e) => setBaseUrl(e.target.value)}
+        placeholder="Base URL (optional, required for compatible servers)"
+      />
+      <Input
+        type="password"
+        value={apiKey}
+        onChange={(e) => setApiKey(e.target.value)}
+        placeholder="API key or secret:NAME (not needed for local servers)"
+      />
+      <Input
+        value={models}
+        onChange={(e) => setModels(e.target.value)}
+        placeholder="Models, comma separated (fetched from the provider when empty)"
+      />
+      <div className="flex justify-end">
+        <Button
+          variant="outline"
+          size="sm"
+          onClick={handleAdd}
+          disabled={!name.trim()}
+          className="h-8"
+        >
+          <Plus className="h-4 w-4 mr-1" />
+          Add Provider
+        </Button>
+      </div>
+
+      {error && <p className="text-xs text-destructive">{error}</p>}
+    </div>
+  );
+}
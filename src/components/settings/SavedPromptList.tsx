@@ -0,0 +1,80 @@
+import { useState } from 'react';
+import { usePromptStore } from '@/stores/promptStore';
+import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
+import { Textarea } from '@/components/ui/textarea';
+import { Plus, Trash2 } from 'lucide-react';
+
+/** Saved prompt templates; `{{name}}` placeholders become arguments over MCP */
+export function SavedPromptList() {
+  const { prompts, addPrompt, removePrompt } = usePromptStore();
+  const [name, setName] = useState('');
+  const [content, setContent] = useState('');
+  const [error, setError] = useState<string | null>(null);
+
+  const handleAdd = async () => {
+    try {
+      await addPrompt({ name: name.trim(), content });
+      setName('');
+      setContent('');
+      setError(null);
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  const handleRemove = async (id: string) => {
+    try {
+      await removePrompt(id);
+      setError(null);
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
+  return (
+    <div className="space-y-2">
+      {prompts.map((prompt) => (
+        <div
+          key={prompt.id}
+          className="flex items-center justify-between rounded-md border border-border px-3 py-2"
+        >
+          <div className="min-w-0">
+            <p className="text-sm font-medium truncate">{prompt.name}</p>
+            <p className="text-xs text-muted-foreground truncate">{prompt.content}</p>
+          </div>
+          <Button
+            variant="ghost"
+            size="icon"
+            className="h-8 w-8"
+            onClick={() => handleRemove(prompt.id)}
+            title="Delete prompt"
+          >
+            <Trash2 className="h-4 w-4" />
+          </Button>
+        </div>
+      ))}
+
+      <Input value={name} onChange={(e) => setName(e.target.value)} placeholder="Prompt name" />
+      <Textarea
+        value={content}
+        onChange={(e) => setContent(e.target.value)}
+        placeholder="Summarize {{text}} in {{language}}"
+      />
+      <div className="flex justify-end">
+        <Button
+          variant="outline"
+          size="sm"
+          onClick={handleAdd}
+          disabled={!name.trim() || !content.trim()}
+          className="h-8"
+        >
+          <Plus className="h-4 w-4 mr-1" />
+          Add Prompt
+        </Button>
+      </div>
+
+      {error && <p className="text-xs text-destructive">{error}</p>}
+    </div>
+  );
+}
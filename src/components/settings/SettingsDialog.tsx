@@ -1,6 +1,5 @@
 import { useState } from 'react';
 import { useSettingsStore } from '@/stores/settingsStore';
-import { getOpenRouterService, resetOpenRouterService } from '@/services/openrouter';
 import {
   Dialog,
   DialogContent,
@@ -15,6 +14,9 @@ import { Eye, EyeOff, ExternalLink, Plus, Globe } from 'lucide-react';
 import { McpServerList } from '@/components/mcp/McpServerList';
 import { McpServerDialog } from '@/components/mcp/McpServerDialog';
 import { SmitheryBrowseDialog } from '@/components/mcp/SmitheryBrowseDialog';
+import { ProviderList } from '@/components/settings/ProviderList';
+import { SavedPromptList } from '@/components/settings/SavedPromptList';
+import type { McpServer } from '@/types/mcp';
 
 interface SettingsDialogProps {
   open: boolean;
@@ -22,42 +24,32 @@ interface SettingsDialogProps {
 }
 
 export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
-  const apiKey = useSettingsStore((state) => state.apiKey);
+  const hasApiKey = useSettingsStore((state) => state.hasApiKey);
   const setApiKey = useSettingsStore((state) => state.setApiKey);
 
-  const [localApiKey, setLocalApiKey] = useState(apiKey || '');
+  // The stored key is never sent back to the webview, so the field only takes a new one
+  const [localApiKey, setLocalApiKey] = useState('');
   const [showApiKey, setShowApiKey] = useState(false);
   const [isSaving, setIsSaving] = useState(false);
   const [saveStatus, setSaveStatus] = useState<'idle' | 'success' | 'error'>('idle');
   const [showMcpDialog, setShowMcpDialog] = useState(false);
+  const [editingServer, setEditingServer] = useState<McpServer | undefined>();
   const [showSmitheryDialog, setShowSmitheryDialog] = useState(false);
 
-  const handleSave = async () => {
+  const saveApiKey = async (key: string) => {
     setIsSaving(true);
     setSaveStatus('idle');
 
     try {
-      if (localApiKey.trim()) {
-        // Validate API key by trying to initialize the service
-        getOpenRouterService(localApiKey.trim());
-        setApiKey(localApiKey.trim());
-        setSaveStatus('success');
-
-        // Close dialog after short delay
-        setTimeout(() => {
-          onOpenChange(false);
-        }, 1000);
-      } else {
-        // Clear API key
-        setApiKey('');
-        resetOpenRouterService();
-        setSaveStatus('success');
-
-        // Close dialog after short delay
-        setTimeout(() => {
-          onOpenChange(false);
-        }, 1000);
-      }
+      // Saved to the backend, which makes the completion requests
+      await setApiKey(key);
+      setLocalApiKey('');
+      setSaveStatus('success');
+
+      // Close dialog after short delay
+      setTimeout(() => {
+        onOpenChange(false);
+      }, 1000);
     } catch (error) {
       console.error('Error saving API key:', error);
       setSaveStatus('error');
@@ -66,15 +58,23 @@ export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
     }
   };
 
+  const handleSave = async () => {
+    if (localApiKey.trim()) {
+      await saveApiKey(localApiKey.trim());
+    } else {
+      onOpenChange(false);
+    }
+  };
+
   const handleCancel = () => {
-    setLocalApiKey(apiKey || '');
+    setLocalApiKey('');
     setSaveStatus('idle');
     onOpenChange(false);
   };
 
   return (
     <Dialog open={open} onOpenChange={onOpenChange}>
-      <DialogContent className="sm:max-w-[500px]">
+      <DialogContent className="sm:max-w-[500px] max-h-[90vh] overflow-y-auto">
         <DialogHeader>
           <DialogTitle>Settings</DialogTitle>
           <DialogDescription>
@@ -104,7 +104,9 @@ export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
                 type={showApiKey ? 'text' : 'password'}
                 value={localApiKey}
                 onChange={(e) => setLocalApiKey(e.target.value)}
-                placeholder="sk-or-v1-..."
+                placeholder={
+                  hasApiKey ? 'Key saved; enter a new key to replace it' : 'sk-or-v1-...'
+                }
                 className="pr-10"
               />
               <button
@@ -120,9 +122,22 @@ export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
               </button>
             </div>
 
-            <p className="text-xs text-muted-foreground">
-              Your API key is stored locally and never sent to our servers.
-            </p>
+            <div className="flex items-center justify-between gap-2">
+              <p className="text-xs text-muted-foreground">
+                Your API key is stored in the local secret vault and never sent to our servers.
+              </p>
+              {hasApiKey && (
+                <Button
+                  variant="outline"
+                  size="sm"
+                  onClick={() => saveApiKey('')}
+                  disabled={isSaving}
+                  className="h-8 shrink-0"
+                >
+                  Remove Key
+                </Button>
+              )}
+            </div>
           </div>
 
           {/* Status Messages */}
@@ -137,6 +152,15 @@ export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
             </div>
           )}
 
+          {/* Chat Providers Section */}
+          <div className="space-y-3">
+            <Label>Other Providers</Label>
+            <ProviderList />
+            <p className="text-xs text-muted-foreground">
+              Their models are offered in the model picker alongside the OpenRouter models.
+            </p>
+          </div>
+
           {/* MCP Servers Section */}
           <div className="space-y-3">
             <div className="flex items-center justify-between">
@@ -154,7 +178,10 @@ export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
                 <Button
                   variant="outline"
                   size="sm"
-                  onClick={() => setShowMcpDialog(true)}
+                  onClick={() => {
+                    setEditingServer(undefined);
+                    setShowMcpDialog(true);
+                  }}
                   className="h-8"
                 >
                   <Plus className="h-4 w-4 mr-1" />
@@ -164,7 +191,12 @@ export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
             </div>
 
             <div className="rounded-md border border-border bg-muted/20 h-[200px]">
-              <McpServerList />
+              <McpServerList
+                onEdit={(server) => {
+                  setEditingServer(server);
+                  setShowMcpDialog(true);
+                }}
+              />
             </div>
 
             <p className="text-xs text-muted-foreground">
@@ -172,7 +204,20 @@ export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
             </p>
           </div>
 
-          <McpServerDialog open={showMcpDialog} onOpenChange={setShowMcpDialog} />
+          {/* Saved Prompts Section */}
+          <div className="space-y-3">
+            <Label>Saved Prompts</Label>
+            <SavedPromptList />
+            <p className="text-xs text-muted-foreground">
+              Prompts are offered to MCP clients connected to Orion.
+            </p>
+          </div>
+
+          <McpServerDialog
+            open={showMcpDialog}
+            onOpenChange={setShowMcpDialog}
+            server={editingServer}
+          />
           <SmitheryBrowseDialog open={showSmitheryDialog} onOpenChange={setShowSmitheryDialog} />
         </div>
 
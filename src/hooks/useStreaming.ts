@@ -1,39 +1,51 @@
 import { useCallback, useRef } from 'react';
-import { invoke } from '@tauri-apps/api/core';
+import { Channel, invoke } from '@tauri-apps/api/core';
 import { useChatStore } from '@/stores/chatStore';
 import { useSettingsStore } from '@/stores/settingsStore';
-import { useMcpStore } from '@/stores/mcpStore';
-import { getOpenRouterService } from '@/services/openrouter';
-import type { OpenRouterMessage } from '@/types/openrouter';
-import type { ToolCall, ToolResult } from '@/types/chat';
+import type { AgentEvent, AgentMessage, AgentRun, ChatCommandError } from '@/types/agent';
+import type { Message, ToolResult } from '@/types/chat';
+import { DEFAULT_PROVIDER_ID } from '@/types/provider';
+
+/** A stored message as the model saw it, including the tool calls of its run */
+function toAgentMessages(msg: Message): AgentMessage[] {
+  if (msg.role !== 'assistant' || !msg.toolCalls?.length) {
+    return [{ role: msg.role, content: msg.content }];
+  }
+  const results = new Map(msg.toolResults?.map((result) => [result.toolCallId, result.content]));
+  const messages: AgentMessage[] = [
+    { role: 'assistant', content: null, tool_calls: msg.toolCalls },
+    ...msg.toolCalls.map((call) => ({
+      role: 'tool' as const,
+      tool_call_id: call.id,
+      content: results.get(call.id) ?? '',
+    })),
+  ];
+  if (msg.content) {
+    messages.push({ role: 'assistant', content: msg.content });
+  }
+  return messages;
+}
 
 export function useStreaming() {
   const addMessage = useChatStore((state) => state.addMessage);
   const updateMessage = useChatStore((state) => state.updateMessage);
   const updateMessageStreaming = useChatStore((state) => state.updateMessageStreaming);
+  const markMessageStopped = useChatStore((state) => state.markMessageStopped);
+  const setMessageModel = useChatStore((state) => state.setMessageModel);
+  const setMessageTools = useChatStore((state) => state.setMessageTools);
   const setStreaming = useChatStore((state) => state.setStreaming);
   const setError = useChatStore((state) => state.setError);
-  const apiKey = useSettingsStore((state) => state.apiKey);
+  const hasApiKey = useSettingsStore((state) => state.hasApiKey);
   const selectedModel = useSettingsStore((state) => state.selectedModel);
-  const getAllTools = useMcpStore((state) => state.getAllTools);
-  const servers = useMcpStore((state) => state.servers);
-  const tools = useMcpStore((state) => state.tools);
-
-  const abortControllerRef = useRef<AbortController | null>(null);
+  const selectedProvider = useSettingsStore((state) => state.selectedProvider);
 
-  // Helper to find which server has a specific tool
-  const findServerForTool = useCallback((toolName: string): string | null => {
-    for (const [serverId, serverTools] of tools.entries()) {
-      if (serverTools.some((t) => t.name === toolName)) {
-        return serverId;
-      }
-    }
-    return null;
-  }, [tools]);
+  // Run whose events are still shown, and which `cancel_generation` stops
+  const activeRunRef = useRef<string | null>(null);
 
   const sendMessage = useCallback(
     async (conversationId: string, userMessage: string) => {
-      if (!apiKey) {
+      // Other providers have their own keys, checked by the backend
+      if (selectedProvider === DEFAULT_PROVIDER_ID && !hasApiKey) {
         setError('Please set your OpenRouter API key in settings');
         return;
       }
@@ -43,8 +55,9 @@ export function useStreaming() {
       const conversation = conversations.find((c) => c.id === conversationId);
       if (!conversation) return;
 
-      // Create abort controller
-      abortControllerRef.current = new AbortController();
+      const runId = crypto.randomUUID();
+      activeRunRef.current = runId;
+      const isActive = () => activeRunRef.current === runId;
 
       let assistantMessageId: string | null = null;
 
@@ -52,24 +65,14 @@ export function useStreaming() {
         setStreaming(true);
         setError(null);
 
-        // Build message history for API
-        const messages: OpenRouterMessage[] = conversation.messages.map((msg) => ({
-          role: msg.role,
-          content: msg.content,
-          ...(msg.toolCalls && { tool_calls: msg.toolCalls }),
-        }));
-
-        // Add current user message
-        messages.push({
-          role: 'user',
-          content: userMessage,
-        });
-
-        // Get available MCP tools
-        const tools = getAllTools();
+        // Build message history for the model
+        const messages: AgentMessage[] = conversation.messages.flatMap(toAgentMessages);
 
-        // Initialize OpenRouter service
-        const service = getOpenRouterService(apiKey);
+        // Add the current user message unless it's already in the conversation
+        const last = conversation.messages[conversation.messages.length - 1];
+        if (!last || last.role !== 'user' || last.content !== userMessage) {
+          messages.push({ role: 'user', content: userMessage });
+        }
 
         // Create assistant message placeholder
         assistantMessageId = addMessage(conversationId, {
@@ -112,99 +115,112 @@ export function useStreaming() {
           }, delay);
         };
 
-        // Stream the response
-        await service.streamChat(
-          messages,
-          selectedModel,
-          {
-            onContent: (content: string) => {
-              accumulatedContent += content;
-
-              scheduleUpdate();
-            },
-            onToolCall: async (toolCall: any) => {
-              try {
-                // Extract tool information from the call
-                const toolName = toolCall.function?.name;
-                const toolArgs = toolCall.function?.arguments
-                  ? JSON.parse(toolCall.function.arguments)
-                  : null;
-
-                if (!toolName) {
-                  console.error('Tool call missing name');
-                  return;
-                }
-
-                // Find which server has this tool
-                const serverId = findServerForTool(toolName);
-                if (!serverId) {
-                  console.error(`No server found for tool: ${toolName}`);
-                  return;
-                }
-
-                console.log(`Calling MCP tool ${toolName} on server ${serverId}`, toolArgs);
-
-                // Call the tool via Tauri backend
-                const result = await invoke('call_mcp_tool', {
-                  serverId,
-                  toolName,
-                  arguments: toolArgs,
-                });
-
-                console.log('Tool result:', result);
-
-                // TODO: Add tool call and result to the message
-                // This would require updating the message structure to include tool calls
-                // For now, we just log it
-              } catch (error) {
-                console.error('Error calling MCP tool:', error);
+        // The backend runs the model and the MCP tools it calls, reporting each step
+        const onEvent = new Channel<AgentEvent>();
+        onEvent.onmessage = (event) => {
+          if (!isActive()) return;
+
+          switch (event.type) {
+            case 'chunk':
+              if (event.chunk.type === 'content') {
+                accumulatedContent += event.chunk.text;
+                scheduleUpdate();
+              }
+              break;
+            case 'stepStarted':
+              // Separate the text of consecutive model turns
+              if (event.step > 1 && accumulatedContent && !accumulatedContent.endsWith('\n\n')) {
+                accumulatedContent += '\n\n';
               }
-            },
-            onComplete: () => {
-              flushUpdate();
-              // Mark streaming as complete
-              updateMessageStreaming(conversationId, messageId, false);
-              setStreaming(false);
-            },
-            onError: (error: Error) => {
-              flushUpdate();
-              setError(error.message);
-              updateMessageStreaming(conversationId, messageId, false);
-              setStreaming(false);
-            },
-            signal: abortControllerRef.current.signal,
+              break;
+            case 'assistantMessage':
+              // A fallback answers when the selected model fails
+              setMessageModel(conversationId, messageId, event.model);
+              break;
+          }
+        };
+
+        const run = await invoke<AgentRun>('run_agent', {
+          runId,
+          // The backend saves a stopped answer under this ID
+          messageId,
+          providerId: selectedProvider,
+          request: {
+            model: selectedModel,
+            fallbacks: conversation.fallbackModels,
+            messages,
+            conversationId,
           },
-          tools.length > 0 ? tools : undefined
-        );
+          onEvent,
+        });
+        flushUpdate();
+
+        // Keep the run's tool calls with the answer, so later turns send them again
+        const toolCalls = run.messages.flatMap((msg) => msg.tool_calls ?? []);
+        if (toolCalls.length > 0) {
+          const toolResults: ToolResult[] = run.messages
+            .filter((msg) => msg.role === 'tool')
+            .map((msg) => ({ toolCallId: msg.tool_call_id ?? '', content: msg.content ?? '' }));
+          setMessageTools(conversationId, messageId, toolCalls, toolResults);
+        }
+
+        if (run.reason === 'stopped') {
+          // The backend kept the text generated before the cancellation
+          markMessageStopped(conversationId, messageId);
+          if (isActive()) {
+            activeRunRef.current = null;
+            setStreaming(false);
+          }
+          return;
+        }
+        updateMessageStreaming(conversationId, messageId, false);
+        if (!isActive()) return;
+        activeRunRef.current = null;
+
+        if (run.reason !== 'completed') {
+          setError(`Stopped early: ${run.reason} after ${run.steps} steps`);
+        }
+        setStreaming(false);
       } catch (error) {
-        if (error instanceof Error && error.message !== 'Request aborted') {
-          setError(error.message);
+        if (isActive()) {
+          activeRunRef.current = null;
+          const message =
+            (error as ChatCommandError)?.message ??
+            (error instanceof Error ? error.message : String(error));
+          setError(message);
+          setStreaming(false);
         }
         if (assistantMessageId) {
           updateMessageStreaming(conversationId, assistantMessageId, false);
         }
-        setStreaming(false);
       }
     },
     [
-      apiKey,
+      hasApiKey,
+      selectedProvider,
       selectedModel,
       addMessage,
       updateMessage,
       updateMessageStreaming,
+      markMessageStopped,
+      setMessageModel,
+      setMessageTools,
       setStreaming,
       setError,
-      getAllTools,
-      findServerForTool,
     ]
   );
 
   const cancelStreaming = useCallback(() => {
-    if (abortControllerRef.current) {
-      abortControllerRef.current.abort();
-      abortControllerRef.current = null;
+    const runId = activeRunRef.current;
+    if (!runId) {
       setStreaming(false);
+      return;
     }
+    // The run then returns with the `stopped` reason and the partial answer, which
+    // ends the streaming; until then it may still be running
+    invoke('cancel_generation', { runId }).catch((error) => {
+      console.warn('Failed to cancel generation', error);
+    });
   }, [setStreaming]);
 
   return {
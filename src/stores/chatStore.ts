@@ -1,22 +1,39 @@
 import { create } from 'zustand';
 import { persist } from 'zustand/middleware';
 import { nanoid } from 'nanoid';
-import type { Conversation, Message } from '@/types/chat';
+import { invoke } from '@tauri-apps/api/core';
+import type { Conversation, Message, ToolCall, ToolResult } from '@/types/chat';
+import type { ModelChoice } from '@/types/provider';
+
+/** Delay before changed conversations are saved, so a streaming reply is not saved per chunk */
+const SAVE_DELAY_MS = 500;
 
 interface ChatStore {
   conversations: Conversation[];
   activeConversationId: string | null;
   isStreaming: boolean;
   error: string | null;
+  /** Conversations were loaded from the backend, so changes may be saved back */
+  loaded: boolean;
 
   // Actions
+  loadConversations: () => Promise<void>;
   createConversation: (model: string) => string;
   setActiveConversation: (id: string) => void;
   addMessage: (conversationId: string, message: Omit<Message, 'id' | 'timestamp'>) => string;
   updateMessage: (conversationId: string, messageId: string, content: string) => void;
   updateMessageStreaming: (conversationId: string, messageId: string, isStreaming: boolean) => void;
+  markMessageStopped: (conversationId: string, messageId: string) => void;
+  setMessageModel: (conversationId: string, messageId: string, model: string) => void;
+  setMessageTools: (
+    conversationId: string,
+    messageId: string,
+    toolCalls: ToolCall[],
+    toolResults: ToolResult[]
+  ) => void;
   deleteConversation: (id: string) => void;
   updateConversationTitle: (id: string, title: string) => void;
+  setFallbackModels: (id: string, fallbackModels: ModelChoice[]) => void;
   setStreaming: (isStreaming: boolean) => void;
   setError: (error: string | null) => void;
   clearConversations: () => void;
@@ -29,6 +46,33 @@ export const useChatStore = create<ChatStore>()(
       activeConversationId: null,
       isStreaming: false,
       error: null,
+      loaded: false,
+
+      loadConversations: async () => {
+        try {
+          // The backend owns the conversations in conversations.json
+          let conversations = await invoke<Conversation[]>('load_conversations');
+
+          // Conversations from before the backend kept them only exist in local storage
+          const localConversations = get().conversations;
+          if (conversations.length === 0 && localConversations.length > 0) {
+            await invoke('save_conversations', { conversations: localConversations });
+            conversations = localConversations;
+          }
+
+          set((state) => ({
+            conversations,
+            activeConversationId: conversations.some(
+              (conv) => conv.id === state.activeConversationId
+            )
+              ? state.activeConversationId
+              : conversations[0]?.id || null,
+            loaded: true,
+          }));
+        } catch (error) {
+          console.error('Failed to load conversations:', error);
+        }
+      },
 
       createConversation: (model: string) => {
         const id = nanoid();
@@ -116,6 +160,59 @@ export const useChatStore = create<ChatStore>()(
         }));
       },
 
+      markMessageStopped: (conversationId: string, messageId: string) => {
+        set((state) => ({
+          conversations: state.conversations.map((conv) => {
+            if (conv.id === conversationId) {
+              return {
+                ...conv,
+                messages: conv.messages.map((msg) =>
+                  msg.id === messageId ? { ...msg, isStreaming: false, stopped: true } : msg
+                ),
+              };
+            }
+            return conv;
+          }),
+        }));
+      },
+
+      setMessageModel: (conversationId: string, messageId: string, model: string) => {
+        set((state) => ({
+          conversations: state.conversations.map((conv) => {
+            if (conv.id === conversationId) {
+              return {
+                ...conv,
+                messages: conv.messages.map((msg) =>
+                  msg.id === messageId ? { ...msg, model } : msg
+                ),
+              };
+            }
+            return conv;
+          }),
+        }));
+      },
+
+      setMessageTools: (
+        conversationId: string,
+        messageId: string,
+        toolCalls: ToolCall[],
+        toolResults: ToolResult[]
+      ) => {
+        set((state) => ({
+          conversations: state.conversations.map((conv) => {
+            if (conv.id === conversationId) {
+              return {
+                ...conv,
+                messages: conv.messages.map((msg) =>
+                  msg.id === messageId ? { ...msg, toolCalls, toolResults } : msg
+                ),
+              };
+            }
+            return conv;
+          }),
+        }));
+      },
+
       deleteConversation: (id: string) => {
         set((state) => {
           const newConversations = state.conversations.filter((conv) => conv.id !== id);
@@ -139,6 +236,14 @@ export const useChatStore = create<ChatStore>()(
         }));
       },
 
+      setFallbackModels: (id: string, fallbackModels: ModelChoice[]) => {
+        set((state) => ({
+          conversations: state.conversations.map((conv) =>
+            conv.id === id ? { ...conv, fallbackModels } : conv
+          ),
+        }));
+      },
+
       setStreaming: (isStreaming: boolean) => {
         set({ isStreaming });
       },
@@ -154,9 +259,28 @@ export const useChatStore = create<ChatStore>()(
     {
       name: 'orion-chat-storage',
       partialize: (state) => ({
-        conversations: state.conversations,
         activeConversationId: state.activeConversationId,
       }),
     }
   )
 );
+
+let saveTimer: ReturnType<typeof setTimeout> | undefined;
+
+// Save conversations to the backend shortly after they change
+useChatStore.subscribe((state, prevState) => {
+  if (!prevState.loaded || state.conversations === prevState.conversations) {
+    return;
+  }
+  clearTimeout(saveTimer);
+  saveTimer = setTimeout(() => {
+    // A reply still streaming when the app closes is saved as far as it got
+    const conversations = useChatStore.getState().conversations.map((conv) => ({
+      ...conv,
+      messages: conv.messages.map((msg) => ({ ...msg, isStreaming: undefined })),
+    }));
+    invoke('save_conversations', { conversations }).catch((error) =>
+      console.error('Failed to save conversations:', error)
+    );
+  }, SAVE_DELAY_MS);
+});
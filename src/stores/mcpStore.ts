@@ -2,14 +2,42 @@ import { create } from 'zustand';
 import { persist } from 'zustand/middleware';
 import { nanoid } from 'nanoid';
 import { invoke } from '@tauri-apps/api/core';
-import type { McpServer, McpTool, McpServerConfig } from '@/types/mcp';
+import type { McpServer, McpTool, McpServerConfig, McpServerBackendConfig } from '@/types/mcp';
+
+/** Server ID the backend serves its built-in tools under */
+const BUILTIN_SERVER_ID = 'orion';
+
+function toBackendConfig(server: McpServer): McpServerBackendConfig {
+  return {
+    id: server.id,
+    name: server.name,
+    command: server.command,
+    args: server.args,
+    env: server.env || {},
+    ...(server.timeouts && { timeouts: server.timeouts }),
+    autostart: server.autostart ?? false,
+    ...(server.cwd && { cwd: server.cwd }),
+    ...(server.inheritEnv && { inheritEnv: server.inheritEnv }),
+    ...(server.extraPath && { extraPath: server.extraPath }),
+    ...(server.sandbox && { sandbox: server.sandbox }),
+    ...(server.disabledTools && { disabledTools: server.disabledTools }),
+    ...(server.exposeViaGateway !== undefined && { exposeViaGateway: server.exposeViaGateway }),
+    ...(server.recordTraffic && { recordTraffic: server.recordTraffic }),
+    ...(server.maxConcurrentCalls && { maxConcurrentCalls: server.maxConcurrentCalls }),
+    ...(server.rateLimit && { rateLimit: server.rateLimit }),
+    ...(server.resultLimits && { resultLimits: server.resultLimits }),
+    ...(server.cache && { cache: server.cache }),
+  };
+}
 
 interface McpStore {
   servers: McpServer[];
   tools: Map<string, McpTool[]>;
 
   // Actions
+  loadServers: () => Promise<void>;
   addServer: (config: McpServerConfig) => Promise<string>;
+  updateServer: (id: string, config: McpServerConfig) => Promise<void>;
   removeServer: (id: string) => Promise<void>;
   startServer: (id: string) => Promise<void>;
   stopServer: (id: string) => Promise<void>;
@@ -25,6 +53,46 @@ export const useMcpStore = create<McpStore>()(
       servers: [],
       tools: new Map(),
 
+      loadServers: async () => {
+        try {
+          // The backend owns the authoritative server list in mcp_servers.json
+          let configs = await invoke<McpServerBackendConfig[]>('list_mcp_server_configs');
+
+          // Servers added before the backend kept the list only exist in local storage
+          const localServers = get().servers;
+          if (configs.length === 0 && localServers.length > 0) {
+            for (const server of localServers) {
+              try {
+                await invoke('add_mcp_server_config', { config: toBackendConfig(server) });
+              } catch (error) {
+                console.error(`Failed to migrate MCP server '${server.name}':`, error);
+              }
+            }
+            configs = await invoke<McpServerBackendConfig[]>('list_mcp_server_configs');
+          }
+          const running = await invoke<string[]>('list_mcp_servers');
+
+          set({
+            servers: configs.map((config) => ({
+              ...config,
+              status: running.includes(config.id) ? 'running' : 'stopped',
+            })),
+          });
+
+          // Servers autostarted by the backend already have tools available
+          for (const serverId of running) {
+            const tools = await invoke<McpTool[]>('list_mcp_tools', { serverId });
+            get().setServerTools(serverId, tools);
+          }
+
+          // Orion's own tools, e.g. paging through truncated results
+          const builtinTools = await invoke<McpTool[]>('list_builtin_mcp_tools');
+          get().setServerTools(BUILTIN_SERVER_ID, builtinTools);
+        } catch (error) {
+          console.error('Failed to load MCP server configs:', error);
+        }
+      },
+
       addServer: async (config: McpServerConfig) => {
         const id = nanoid();
         const newServer: McpServer = {
@@ -33,9 +101,13 @@ export const useMcpStore = create<McpStore>()(
           command: config.command,
           args: config.args,
           env: config.env,
+          timeouts: config.timeouts,
+          autostart: config.autoStart,
           status: 'stopped',
         };
 
+        await invoke('add_mcp_server_config', { config: toBackendConfig(newServer) });
+
         set((state) => ({
           servers: [...state.servers, newServer],
         }));
@@ -48,6 +120,33 @@ export const useMcpStore = create<McpStore>()(
         return id;
       },
 
+      updateServer: async (id: string, config: McpServerConfig) => {
+        const server = get().servers.find((s) => s.id === id);
+        if (!server) return;
+
+        const updated: McpServer = {
+          ...server,
+          name: config.name,
+          command: config.command,
+          args: config.args,
+          env: config.env,
+          timeouts: config.timeouts,
+          autostart: config.autoStart,
+        };
+
+        await invoke('update_mcp_server_config', { config: toBackendConfig(updated) });
+
+        set((state) => ({
+          servers: state.servers.map((s) => (s.id === id ? updated : s)),
+        }));
+
+        // A running server keeps its old command and environment until restarted
+        if (server.status === 'running') {
+          await get().stopServer(id);
+          await get().startServer(id);
+        }
+      },
+
       removeServer: async (id: string) => {
         // Stop server first if running
         const server = get().servers.find((s) => s.id === id);
@@ -55,6 +154,12 @@ export const useMcpStore = create<McpStore>()(
           await get().stopServer(id);
         }
 
+        try {
+          await invoke('delete_mcp_server_config', { serverId: id });
+        } catch (error) {
+          console.error('Failed to delete MCP server config:', error);
+        }
+
         set((state) => ({
           servers: state.servers.filter((s) => s.id !== id),
         }));
@@ -74,15 +179,7 @@ export const useMcpStore = create<McpStore>()(
 
         try {
           // Call Tauri command to start MCP server
-          await invoke('start_mcp_server', {
-            config: {
-              id,
-              name: server.name,
-              command: server.command,
-              args: server.args,
-              env: server.env || {},
-            },
-          });
+          await invoke('start_mcp_server', { config: toBackendConfig(server) });
 
           get().updateServerStatus(id, 'running');
 
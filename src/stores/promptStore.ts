@@ -0,0 +1,38 @@
+import { create } from 'zustand';
+import { nanoid } from 'nanoid';
+import { invoke } from '@tauri-apps/api/core';
+import type { SavedPrompt } from '@/types/chat';
+
+interface PromptStore {
+  prompts: SavedPrompt[];
+
+  // Actions
+  loadPrompts: () => Promise<void>;
+  addPrompt: (prompt: Omit<SavedPrompt, 'id'>) => Promise<void>;
+  removePrompt: (id: string) => Promise<void>;
+}
+
+/** Prompt templates, saved by the backend to prompts.json and served over MCP */
+export const usePromptStore = create<PromptStore>()((set, get) => ({
+  prompts: [],
+
+  loadPrompts: async () => {
+    try {
+      set({ prompts: await invoke<SavedPrompt[]>('load_prompts') });
+    } catch (error) {
+      console.error('Failed to load prompts:', error);
+    }
+  },
+
+  addPrompt: async (prompt) => {
+    const prompts = [...get().prompts, { id: nanoid(), ...prompt }];
+    await invoke('save_prompts', { prompts });
+    set({ prompts });
+  },
+
+  removePrompt: async (id: string) => {
+    const prompts = get().prompts.filter((prompt) => prompt.id !== id);
+    await invoke('save_prompts', { prompts });
+    set({ prompts });
+  },
+}));
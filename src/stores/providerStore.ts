@@ -0,0 +1,59 @@
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import type { ModelInfo, ProviderConfig } from '@/types/provider';
+
+interface ProviderStore {
+  providers: ProviderConfig[];
+  /** Models of each provider, keyed by provider ID */
+  models: Record<string, ModelInfo[]>;
+
+  // Actions
+  loadProviders: () => Promise<void>;
+  addProvider: (config: ProviderConfig) => Promise<void>;
+  removeProvider: (id: string) => Promise<void>;
+  loadModels: (id: string) => Promise<void>;
+}
+
+/** Chat providers besides OpenRouter, saved by the backend to providers.json */
+export const useProviderStore = create<ProviderStore>()((set, get) => ({
+  providers: [],
+  models: {},
+
+  loadProviders: async () => {
+    try {
+      const providers = await invoke<ProviderConfig[]>('list_chat_providers');
+      set({ providers });
+      await Promise.all(providers.map((provider) => get().loadModels(provider.id)));
+    } catch (error) {
+      console.error('Failed to load providers:', error);
+    }
+  },
+
+  addProvider: async (config: ProviderConfig) => {
+    await invoke('add_chat_provider', { config });
+    set((state) => ({ providers: [...state.providers, config] }));
+    await get().loadModels(config.id);
+  },
+
+  removeProvider: async (id: string) => {
+    await invoke('delete_chat_provider', { providerId: id });
+    set((state) => {
+      const models = { ...state.models };
+      delete models[id];
+      return {
+        providers: state.providers.filter((provider) => provider.id !== id),
+        models,
+      };
+    });
+  },
+
+  loadModels: async (id: string) => {
+    try {
+      const models = await invoke<ModelInfo[]>('list_provider_models', { providerId: id });
+      set((state) => ({ models: { ...state.models, [id]: models } }));
+    } catch (error) {
+      // A local server that isn't running yet has no models to list
+      console.warn(`Failed to list the models of '${id}':`, error);
+    }
+  },
+}));
@@ -1,21 +1,33 @@
 import { create } from 'zustand';
 import { persist } from 'zustand/middleware';
+import { invoke } from '@tauri-apps/api/core';
+import { DEFAULT_PROVIDER_ID } from '@/types/provider';
 
 interface SettingsStore {
-  apiKey: string | null;
+  /** An OpenRouter API key is stored; the key itself stays in the backend */
+  hasApiKey: boolean;
+  /** Provider that `selectedModel` belongs to */
+  selectedProvider: string;
   selectedModel: string;
   availableModels: string[];
 
   // Actions
-  setApiKey: (key: string) => void;
-  setSelectedModel: (model: string) => void;
+  loadApiKeyStatus: () => Promise<void>;
+  setApiKey: (key: string) => Promise<void>;
+  setSelectedModel: (model: string, provider?: string) => void;
   setAvailableModels: (models: string[]) => void;
 }
 
+/** Save the settings the backend needs to talk to the model provider */
+function syncToBackend(selectedModel: string): Promise<void> {
+  return invoke('save_settings', { settings: { selected_model: selectedModel } });
+}
+
 export const useSettingsStore = create<SettingsStore>()(
   persist(
     (set) => ({
-      apiKey: null,
+      hasApiKey: false,
+      selectedProvider: DEFAULT_PROVIDER_ID,
       selectedModel: 'openai/gpt-4',
       availableModels: [
         'openai/gpt-4',
@@ -27,12 +39,28 @@ export const useSettingsStore = create<SettingsStore>()(
         'meta-llama/llama-3.1-70b-instruct',
       ],
 
-      setApiKey: (key: string) => {
-        set({ apiKey: key });
+      loadApiKeyStatus: async () => {
+        try {
+          set({ hasApiKey: await invoke<boolean>('has_api_key') });
+        } catch (error) {
+          console.error('Failed to check the API key:', error);
+        }
       },
 
-      setSelectedModel: (model: string) => {
-        set({ selectedModel: model });
+      setApiKey: async (key: string) => {
+        // Kept in the backend's secret vault; an empty key removes it
+        await invoke('set_api_key', { apiKey: key });
+        set({ hasApiKey: key.trim() !== '' });
+      },
+
+      setSelectedModel: (model: string, provider?: string) => {
+        set((state) => ({
+          selectedModel: model,
+          selectedProvider: provider ?? state.selectedProvider,
+        }));
+        syncToBackend(model).catch((error) =>
+          console.error('Failed to save settings:', error)
+        );
       },
 
       setAvailableModels: (models: string[]) => {
@@ -41,6 +69,24 @@ export const useSettingsStore = create<SettingsStore>()(
     }),
     {
       name: 'orion-settings-storage',
+      version: 1,
+      partialize: (state) => ({
+        selectedProvider: state.selectedProvider,
+        selectedModel: state.selectedModel,
+        availableModels: state.availableModels,
+      }),
+      // Earlier builds kept the API key in local storage; move it to the vault
+      migrate: (persisted, version) => {
+        const { apiKey, ...state } = persisted as Partial<SettingsStore> & {
+          apiKey?: string | null;
+        };
+        if (version < 1 && apiKey) {
+          invoke('set_api_key', { apiKey })
+            .then(() => useSettingsStore.setState({ hasApiKey: true }))
+            .catch((error) => console.error('Failed to save API key:', error));
+        }
+        return state as SettingsStore;
+      },
     }
   )
 );
@@ -0,0 +1,111 @@
+import type { ToolCall } from './chat';
+import type { ModelChoice } from './provider';
+
+/** Message in the chat completions format the backend agent sends to the model */
+export interface AgentMessage {
+  role: 'system' | 'user' | 'assistant' | 'tool';
+  content: string | null;
+  tool_calls?: ToolCall[];
+  tool_call_id?: string;
+  reasoning?: string;
+}
+
+/** Reasons a provider request failed, from `ProviderErrorKind` */
+export type ProviderErrorKind =
+  | 'auth'
+  | 'insufficientCredits'
+  | 'rateLimited'
+  | 'invalidRequest'
+  | 'server'
+  | 'network';
+
+/** Error returned by `stream_chat` and `run_agent` */
+export type ChatCommandError =
+  | {
+      kind: 'provider';
+      message: string;
+      reason: ProviderErrorKind;
+      status?: number;
+      retryAfterMs?: number;
+    }
+  | { kind: 'other'; message: string };
+
+export interface AgentUsage {
+  prompt_tokens: number;
+  completion_tokens: number;
+  total_tokens: number;
+  /** USD, when the provider reports it */
+  cost?: number;
+}
+
+/** Piece of a streamed completion */
+export type ChatChunk =
+  | { type: 'content'; text: string }
+  | { type: 'reasoning'; text: string }
+  | { type: 'toolCall'; index: number; id?: string; name?: string; arguments: string }
+  | { type: 'usage'; usage: AgentUsage }
+  | { type: 'finish'; reason: string }
+  /** Model the following chunks come from, as a fallback chain tries it */
+  | { type: 'model'; model: string };
+
+/** Complete answer returned by `stream_chat` */
+export interface ChatResponse {
+  message: AgentMessage;
+  usage: AgentUsage | null;
+  finishReason: string | null;
+  /** Model that answered, when a fallback chain picked it */
+  model?: string;
+}
+
+export interface AgentLimits {
+  /** Model calls per run, default 10 */
+  maxSteps?: number;
+  /** USD; no limit if unset */
+  maxCost?: number | null;
+  /** Wall-clock limit for the whole run, default 5 minutes */
+  maxDurationMs?: number;
+}
+
+export interface AgentRequest {
+  model: string;
+  /** Asked in order when `model` fails */
+  fallbacks?: ModelChoice[];
+  messages: AgentMessage[];
+  conversationId?: string;
+  limits?: AgentLimits;
+}
+
+export type AgentStopReason = 'completed' | 'maxSteps' | 'maxCost' | 'timeout' | 'stopped';
+
+/** Progress of a `run_agent` run, delivered over its channel */
+export type AgentEvent =
+  | { type: 'stepStarted'; step: number }
+  | { type: 'chunk'; step: number; chunk: ChatChunk }
+  | {
+      type: 'assistantMessage';
+      step: number;
+      message: AgentMessage;
+      usage: AgentUsage | null;
+      /** Model that wrote the message, which may be a fallback */
+      model: string;
+    }
+  | { type: 'toolCallStarted'; step: number; toolCallId: string; name: string }
+  | {
+      type: 'toolResult';
+      step: number;
+      toolCallId: string;
+      name: string;
+      content: string;
+      isError: boolean;
+    }
+  | { type: 'finished'; reason: AgentStopReason; steps: number; cost: number };
+
+export interface AgentRun {
+  /** Messages the run added to the conversation */
+  messages: AgentMessage[];
+  reason: AgentStopReason;
+  steps: number;
+  cost: number;
+  /** Model that wrote the last message, unless no model answered */
+  model: string | null;
+}
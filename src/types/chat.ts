@@ -1,3 +1,5 @@
+import type { ModelChoice } from './provider';
+
 export interface ToolCall {
   id: string;
   type: 'function';
@@ -21,6 +23,8 @@ export interface Message {
   toolCalls?: ToolCall[];
   toolResults?: ToolResult[];
   isStreaming?: boolean;
+  /** Generation was cancelled; the content is what arrived before that */
+  stopped?: boolean;
 }
 
 export interface Conversation {
@@ -30,6 +34,16 @@ export interface Conversation {
   createdAt: number;
   updatedAt: number;
   model: string;
+  /** Models to try in order when the selected one fails */
+  fallbackModels?: ModelChoice[];
+}
+
+/** Reusable prompt template; `{{name}}` placeholders become arguments when served over MCP */
+export interface SavedPrompt {
+  id: string;
+  name: string;
+  description?: string;
+  content: string;
 }
 
 export interface ChatState {
@@ -4,6 +4,19 @@ export interface McpServer {
   command: string;
   args: string[];
   env?: Record<string, string>;
+  timeouts?: Partial<McpTimeouts>;
+  autostart?: boolean;
+  cwd?: string;
+  inheritEnv?: McpInheritEnv;
+  extraPath?: string[];
+  sandbox?: McpSandboxProfile;
+  disabledTools?: string[];
+  exposeViaGateway?: boolean;
+  recordTraffic?: boolean;
+  maxConcurrentCalls?: number;
+  rateLimit?: McpRateLimit;
+  resultLimits?: Partial<McpResultLimits>;
+  cache?: Partial<McpCacheConfig>;
   status: 'stopped' | 'starting' | 'running' | 'error';
   pid?: number;
 }
@@ -17,20 +30,188 @@ export interface McpTool {
   };
 }
 
+export interface McpToolRef {
+  serverId: string;
+  toolName: string;
+}
+
 export interface McpToolCallRequest {
   serverId: string;
   toolName: string;
   arguments: Record<string, unknown>;
 }
 
+export type McpInheritEnv = 'none' | 'all' | { allowlist: string[] };
+
+export interface McpSandboxProfile {
+  readPaths?: string[];
+  writePaths?: string[];
+  allowSystemPaths?: boolean;
+  allowNetwork?: boolean;
+  maxMemoryMb?: number;
+  maxCpuSeconds?: number;
+  maxOpenFiles?: number;
+}
+
+export interface McpTimeouts {
+  initializeMs: number;
+  listMs: number;
+  callMs: number;
+  toolOverrides: Record<string, number>;
+  resetOnProgress: boolean;
+}
+
 export interface McpServerConfig {
   name: string;
   command: string;
   args: string[];
   env?: Record<string, string>;
+  timeouts?: Partial<McpTimeouts>;
   autoStart?: boolean;
 }
 
+// Server configuration as persisted by the backend in mcp_servers.json
+export interface McpServerBackendConfig {
+  id: string;
+  name: string;
+  command: string;
+  args: string[];
+  env?: Record<string, string>;
+  timeouts?: Partial<McpTimeouts>;
+  autostart: boolean;
+  cwd?: string;
+  inheritEnv?: McpInheritEnv;
+  extraPath?: string[];
+  sandbox?: McpSandboxProfile;
+  maxFrameBytes?: number;
+  disabledTools?: string[];
+  exposeViaGateway?: boolean;
+  recordTraffic?: boolean;
+  maxConcurrentCalls?: number;
+  rateLimit?: McpRateLimit;
+  resultLimits?: Partial<McpResultLimits>;
+  cache?: Partial<McpCacheConfig>;
+}
+
+/** Reuse results of tools annotated read-only or idempotent; overrides force caching on or off */
+export interface McpCacheConfig {
+  enabled: boolean;
+  ttlMs: number;
+  toolOverrides: Record<string, boolean>;
+}
+
+/** Longest tool results in characters (0 disables); longer ones are truncated and paged */
+export interface McpResultLimits {
+  maxChars: number;
+  toolOverrides: Record<string, number>;
+}
+
+export interface McpRateLimit {
+  burst: number;
+  perSecond: number;
+}
+
+export interface McpCallQueueStatus {
+  inFlight: number;
+  queued: number;
+  maxConcurrentCalls?: number;
+  rateLimit?: McpRateLimit;
+}
+
+export interface McpGatewayStatus {
+  url: string;
+  token: string;
+}
+
+export interface McpTraceEntry {
+  timestampMs: number;
+  serverId: string;
+  direction: 'sent' | 'received';
+  message: unknown;
+}
+
+export type McpApprovalDecision = 'auto' | 'approved' | 'denied';
+
+export interface McpAuditEntry {
+  timestampMs: number;
+  conversationId?: string;
+  serverId: string;
+  toolName: string;
+  arguments?: unknown;
+  resultSummary?: string;
+  isError: boolean;
+  durationMs: number;
+  approval: McpApprovalDecision;
+  error?: string;
+}
+
+export interface McpAuditQuery {
+  conversationId?: string;
+  serverId?: string;
+  toolName?: string;
+  sinceMs?: number;
+  untilMs?: number;
+  errorsOnly?: boolean;
+  limit?: number;
+  offset?: number;
+}
+
+export interface McpAuditRetention {
+  maxAgeDays: number | null;
+}
+
+export interface McpLatencyHistogram {
+  boundsMs: number[];
+  /** Per bucket, not cumulative; the last bucket is above every bound */
+  counts: number[];
+  sumMs: number;
+}
+
+export interface McpCallMetrics {
+  calls: number;
+  errors: number;
+  timeouts: number;
+  latency: McpLatencyHistogram;
+}
+
+export interface McpServerMetrics extends McpCallMetrics {
+  starts: number;
+  startFailures: number;
+  restarts: number;
+  lastStartupMs?: number | null;
+  tools: Record<string, McpCallMetrics>;
+}
+
+export interface McpServerDiagnostics {
+  diagnostics: string[];
+  stderr: string[];
+}
+
+export interface McpProcessInfo {
+  executable: string;
+  pid?: number;
+  cwd?: string;
+}
+
+export interface McpBatchCall {
+  serverId: string;
+  toolName: string;
+  arguments?: Record<string, unknown> | null;
+  timeoutMs?: number;
+  approval?: McpApprovalDecision;
+}
+
+/** Outcome of one batch call; exactly one of `result` and `error` is set */
+export interface McpBatchCallResult {
+  result?: unknown;
+  error?: McpCommandError;
+}
+
+export type McpCommandError =
+  | { kind: 'timeout'; message: string; method: string; timeoutMs: number }
+  | { kind: 'sandbox'; message: string; denials: string[] }
+  | { kind: 'other'; message: string };
+
 // JSON-RPC types for MCP protocol
 export interface JsonRpcRequest {
   jsonrpc: '2.0';
@@ -70,6 +251,20 @@ export interface McpToolsListResult {
 export interface McpToolCallResult {
   content: Array<{
     type: string;
-    text: string;
+    text?: string;
+    [field: string]: unknown;
   }>;
 }
+
+export interface McpImportIssue {
+  name: string;
+  message: string;
+}
+
+// Result of import_mcp_server_configs
+export interface McpImportReport {
+  format: 'claudeDesktop' | 'cursor' | 'vsCode';
+  servers: McpServerBackendConfig[];
+  warnings: McpImportIssue[];
+  skipped: McpImportIssue[];
+}
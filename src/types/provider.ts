@@ -0,0 +1,88 @@
+import type { McpProcessInfo, McpRateLimit, McpServerDiagnostics } from './mcp';
+
+/** API a chat provider speaks, from `ProviderKind` */
+export type ProviderKind =
+  | 'openRouter'
+  | 'openAi'
+  | 'anthropic'
+  | 'openAiCompatible'
+  | 'ollama'
+  | 'llamaCpp';
+
+/** ID of the OpenRouter provider that uses the API key from the settings */
+export const DEFAULT_PROVIDER_ID = 'openrouter';
+
+/** Chat provider saved in `providers.json` */
+export interface ProviderConfig {
+  id: string;
+  name: string;
+  kind: ProviderKind;
+  /** Required for `openAiCompatible`; local servers default to their usual port */
+  baseUrl?: string;
+  /** Plain key or a `secret:`, `env:` or `file:` reference; not needed for local servers */
+  apiKey?: string;
+  /** Fetched from the provider when empty */
+  models: string[];
+  /** Local server Orion launches itself; only for `ollama` and `llamaCpp` */
+  managed?: ManagedServer;
+  limits?: RequestLimits;
+}
+
+/** How hard Orion may use a provider; requests over the limits wait in line */
+export interface RequestLimits {
+  maxConcurrentRequests?: number;
+  rateLimit?: McpRateLimit;
+  retry?: RetryPolicy;
+}
+
+/** Retries of rate limited and failed requests, honoring `Retry-After` */
+export interface RetryPolicy {
+  /** Default 3; 0 disables retrying */
+  maxRetries?: number;
+  /** Doubled for every further retry, default 1 second */
+  baseDelayMs?: number;
+  /** A longer `Retry-After` fails the request instead, default 1 minute */
+  maxDelayMs?: number;
+}
+
+/** A model and the provider to ask for it */
+export interface ModelChoice {
+  /** OpenRouter with the key from the settings when not given */
+  providerId?: string;
+  model: string;
+}
+
+/** Launch settings of a local inference server */
+export interface ManagedServer {
+  /** `llama-server` or `ollama` from PATH by default */
+  command?: string;
+  /** GGUF file for llama.cpp, or the models directory for Ollama */
+  modelPath?: string;
+  /** 8080 for llama.cpp and 11434 for Ollama by default */
+  port?: number;
+  contextSize?: number;
+  /** llama.cpp only */
+  threads?: number;
+  args: string[];
+  /** Environment variables for the server; Orion's own environment isn't passed on */
+  env?: Record<string, string>;
+  autostart: boolean;
+  /** Default 2 minutes */
+  startupTimeoutMs?: number;
+}
+
+/** Returned by `start_inference_server` and `list_inference_servers` */
+export interface InferenceServerStatus {
+  providerId: string;
+  state: 'starting' | 'ready' | 'exited';
+  baseUrl: string;
+  process: McpProcessInfo;
+  exitCode?: number;
+  diagnostics: McpServerDiagnostics;
+}
+
+/** Model returned by `list_provider_models` */
+export interface ModelInfo {
+  id: string;
+  name?: string;
+}